@@ -2,21 +2,102 @@ use anyhow::{anyhow, Result};
 use eframe::egui;
 use rfd::FileDialog;
 use std::{
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::mpsc::{Receiver, Sender},
     thread,
+    time::{Duration, Instant},
 };
 
 use crate::{
-    encoding::{run_encoding, EncodingConfig},
-    models::Resolution,
-    utils::{find_ffmpeg, get_duration, get_frame_rate, get_resolution, open_folder},
+    archive::{spawn_archive, ArchiveStatus},
+    audio::AudioLayout,
+    crash,
+    encoding::{
+        check_job_support, inverse_telecine_rate, leader_frames, output_frame_rate, run_encoding,
+        EncodingConfig,
+    },
+    errors::{user_message, EncoderError, IoSide},
+    models::{
+        AlphaMode, Av1Speed, BlendMode, DnxhrProfile, ExrCompression, FitMode, FormatOptions,
+        FrameRange, HevcProfile, LeaderSpec, MatteSpec, NamingRules, OutputFormat, OverlaySpec,
+        OverlayStyle, PatternKind, RateControl, Resolution, SourceCheck, StemMode, StillHold,
+        SubtitleSpec, TestPattern,
+    },
+    permissions::OutputPermissions,
+    player::{SequencePlayer, SourceClip},
+    profiles::ShowProfile,
+    provenance::{conflicting_source, disambiguated_dir},
+    proxy::{spawn_proxy, ProxyStatus},
+    purge::{spawn_purge, spawn_scan, PurgeStatus},
+    queue::{Job, JobQueue, JobState},
+    renumber::{
+        plan_renames, scan_sequence, spawn_renumber, RenamePlan, RenumberStatus, SequenceScan,
+        SequenceTemplate,
+    },
+    replication::{replicate_all, ReplicationStatus},
+    rerender::{spawn_rerender, RerenderStatus},
+    schedule::{format_wait, EncodeWindows},
+    script::export_script,
+    settings::Settings,
+    share::{spawn_share, ShareFormat, ShareRequest, ShareStatus},
+    split::{plan_split, spawn_consolidate, ConsolidateStatus, SplitManifest, SplitPart},
+    spool::{self, spawn_watcher, SpoolEvent, SpoolWatcher},
+    staging,
+    stills::{spawn_stills, StillStatus},
+    summary::DeliverySummary,
+    telemetry::{self, TelemetryEvent},
+    trash::{move_to_trash, TrashSummary},
+    update::{spawn_check, spawn_download, update_url, UpdateStatus},
+    utils::{
+        app_data_dir, find_ffmpeg, get_audio_layout, get_duration, get_frame_rate,
+        get_picture_tags, get_resolution, get_start_time, has_video_stream, is_output_file,
+        is_still_image, open_folder, output_frame_number, parse_frame_list, png_has_alpha,
+        sanitize_base_name, set_probe_timeout, FfmpegCapabilities,
+    },
 };
 
+// Containers accepted when a file is dropped onto the window
+const VIDEO_EXTENSIONS: [&str; 8] = ["mov", "mp4", "m4v", "mxf", "mkv", "avi", "mts", "webm"];
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum DialogState {
     None,
-    CancelConfirmation(bool),
+    // Carries what a delete would move to the trash
+    CancelConfirmation(Option<TrashSummary>),
+    PurgeConfirmation(TrashSummary),
+    CrashReports(Vec<PathBuf>),
+    FrameRateMismatch {
+        source: f64,
+        delivery: f64,
+    },
+    // The output folder already holds this base name's frames from another file
+    SourceConflict {
+        recorded: PathBuf,
+        proposed: PathBuf,
+    },
+}
+
+// Which overlay a job burns in
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverlayChoice {
+    // overlay_<resolution>.png from the show's overlay folder
+    ShowDefault,
+    File(PathBuf),
+    None,
+}
+
+enum QueueAction {
+    Up,
+    Down,
+    Remove,
+    Retry,
+    Depend { on: u64, wanted: bool },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FpsDecision {
+    Retime,
+    KeepSource,
 }
 
 pub struct DeliveryEncoderApp {
@@ -28,22 +109,116 @@ pub struct DeliveryEncoderApp {
     pub progress_receiver: Receiver<(f32, u32, String)>,
     pub cancel_sender: Option<Sender<()>>,
     pub ffmpeg_path: PathBuf,
+    // None until a probe of ffmpeg_path succeeds; nothing is greyed out meanwhile
+    pub ffmpeg_capabilities: Option<FfmpegCapabilities>,
     pub ffprobe_path: PathBuf,
     pub current_frame: String,
     pub resolution: Resolution,
+    pub fit_mode: FitMode,
     pub input_video: PathBuf,
     pub sufficient_storage: bool,
     pub storage_error: Option<String>,
+    // Volumes after the output folder the next sequence continues on, from the storage check
+    pub volume_split: Vec<SplitPart>,
+    pub consolidate_status: Option<ConsolidateStatus>,
+    pub consolidate_receiver: Receiver<ConsolidateStatus>,
     pub base_name: String,
     pub original_base_name: String,
     pub has_existing_frames: bool,
     pub dialog_state: DialogState,
     pub instructions: String,
+    pub replica_dirs: Vec<PathBuf>,
+    pub replication_status: Vec<ReplicationStatus>,
+    pub queue: JobQueue,
+    // Lets the queue start outside the encode windows until it stops
+    pub window_override: bool,
+    // Queue job the current encode belongs to
+    pub active_job: Option<u64>,
+    // Finished job whose copies or archive are still running
+    pub delivering_job: Option<u64>,
+    pub queue_state_filter: Option<&'static str>,
+    pub queue_search: String,
+    pub spool: Option<SpoolWatcher>,
+    pub archive_status: Option<ArchiveStatus>,
+    pub archive_receiver: Receiver<ArchiveStatus>,
+    pub replication_receiver: Receiver<(usize, ReplicationStatus)>,
+    pub replication_threads: Vec<thread::JoinHandle<()>>,
+    // Last time any destination reported progress
+    pub replication_heartbeat: Instant,
+    pub player: Option<SequencePlayer>,
+    pub proxy_status: Option<ProxyStatus>,
+    pub proxy_receiver: Receiver<ProxyStatus>,
+    pub update_status: Option<UpdateStatus>,
+    pub update_receiver: Receiver<UpdateStatus>,
+    pub install_update_on_exit: bool,
+    pub settings: Settings,
+    pub encode_started: Option<Instant>,
+    pub shows: Vec<ShowProfile>,
+    pub active_show: Option<usize>,
+    pub new_show_name: String,
+    pub subtitles: Option<SubtitleSpec>,
+    pub audio_layout: Option<AudioLayout>,
+    pub audio_stems: Option<StemMode>,
+    pub output_format: OutputFormat,
+    pub format_options: FormatOptions,
+    // Used when the input is a still picture
+    pub still_hold: StillHold,
+    // 1 keeps every frame of a sequence
+    pub every_nth: u32,
+    pub qc_sample_enabled: bool,
+    pub qc_sample: u32,
+    // Bars replace the input while this is set; size and rate follow the job
+    pub generate_pattern: bool,
+    pub test_pattern: TestPattern,
+    pub job_notes: String,
+    pub share_start: f64,
+    pub share_length: f64,
+    pub share_format: ShareFormat,
+    // Exports the preview as soon as a job finishes
+    pub share_after_encode: bool,
+    pub share_status: Option<ShareStatus>,
+    pub share_receiver: Receiver<ShareStatus>,
+    pub still_frames: String,
+    pub still_status: Option<StillStatus>,
+    pub still_receiver: Receiver<StillStatus>,
+    // Frames the size watch flagged, re-encoded in place rather than pulled as stills
+    pub rerender_frames: Vec<u32>,
+    pub rerender_status: Option<RerenderStatus>,
+    pub rerender_receiver: Receiver<RerenderStatus>,
+    pub purge_status: Option<PurgeStatus>,
+    pub purge_receiver: Receiver<PurgeStatus>,
+    pub renumber_scan: Option<SequenceScan>,
+    pub renumber_template: Option<SequenceTemplate>,
+    pub renumber_plan: Option<RenamePlan>,
+    pub renumber_status: Option<RenumberStatus>,
+    pub renumber_receiver: Receiver<RenumberStatus>,
+    pub naming: NamingRules,
+    pub stalled_since: Option<Instant>,
+    pub resume_after_pause: bool,
+    pub pause_at_frame: Option<u32>,
+    pub pause_frame_input: u32,
+    pub overlay_choice: OverlayChoice,
+    pub overlay_style: OverlayStyle,
+    // Composited over the chosen overlay in order; kept with the show
+    pub extra_overlays: Vec<OverlaySpec>,
+    pub matte: Option<MatteSpec>,
+    pub source_fps: Option<f64>,
+    pub source_check: Option<SourceCheck>,
+    // Why the source could not be probed, e.g. it has no video stream
+    pub source_error: Option<String>,
+    pub inverse_telecine: bool,
+    pub tolerate_errors: bool,
+    pub pipe_enabled: bool,
+    pub pipe_command: String,
+    pub fps_decision: Option<FpsDecision>,
+    // Operator chose to encode over another source's frames anyway
+    pub source_conflict_accepted: bool,
 }
 
 impl DeliveryEncoderApp {
     pub fn new() -> Self {
-        let (ffmpeg_path, ffprobe_path, _) = find_ffmpeg();
+        let (ffmpeg_path, ffprobe_path) = find_ffmpeg();
+        let ffmpeg_capabilities = FfmpegCapabilities::probe(&ffmpeg_path).ok();
 
         let input_video = std::fs::read_dir("assets")
             .and_then(|entries| {
@@ -51,7 +226,7 @@ impl DeliveryEncoderApp {
                     .filter_map(Result::ok)
                     .find(|entry| {
                         entry.path().is_file()
-                            && entry.path().extension().map_or(false, |ext| ext == "mov")
+                            && entry.path().extension().is_some_and(|ext| ext == "mov")
                     })
                     .map(|entry| entry.path())
                     .ok_or_else(|| {
@@ -62,7 +237,7 @@ impl DeliveryEncoderApp {
 
         let original_base_name = input_video
             .file_stem()
-            .map(|s| s.to_string_lossy().into_owned())
+            .map(|s| sanitize_base_name(&s.to_string_lossy()))
             .unwrap_or_else(|| "video".to_string());
 
         let base_name = original_base_name.clone();
@@ -78,7 +253,7 @@ impl DeliveryEncoderApp {
             })
             .unwrap_or_else(|_| "Could not load instructions.".to_string());
 
-        Self {
+        let mut app = Self {
             output_dir: None,
             status: "Ready".to_string(),
             progress: 0.0,
@@ -87,17 +262,283 @@ impl DeliveryEncoderApp {
             progress_receiver: std::sync::mpsc::channel().1,
             cancel_sender: None,
             ffmpeg_path,
+            ffmpeg_capabilities,
             ffprobe_path,
             current_frame: "File: -- | Idle | ETA: --:--".to_string(),
             resolution: Resolution::K6,
+            fit_mode: FitMode::Pad,
             input_video,
             sufficient_storage: false,
             storage_error: Some("Please select output directory".to_string()),
+            volume_split: Vec::new(),
+            consolidate_status: None,
+            consolidate_receiver: std::sync::mpsc::channel().1,
             base_name,
             original_base_name,
             has_existing_frames: false,
             dialog_state: DialogState::None,
             instructions,
+            replica_dirs: Vec::new(),
+            replication_status: Vec::new(),
+            queue: JobQueue::default(),
+            window_override: false,
+            delivering_job: None,
+            queue_state_filter: None,
+            queue_search: String::new(),
+            active_job: None,
+            spool: None,
+            replication_heartbeat: Instant::now(),
+            archive_status: None,
+            archive_receiver: std::sync::mpsc::channel().1,
+            replication_receiver: std::sync::mpsc::channel().1,
+            replication_threads: Vec::new(),
+            player: None,
+            proxy_status: None,
+            proxy_receiver: std::sync::mpsc::channel().1,
+            update_status: None,
+            update_receiver: std::sync::mpsc::channel().1,
+            install_update_on_exit: false,
+            settings: Settings::load(),
+            encode_started: None,
+            shows: ShowProfile::load_all(),
+            active_show: None,
+            new_show_name: String::new(),
+            subtitles: None,
+            audio_layout: None,
+            audio_stems: None,
+            output_format: OutputFormat::PngSequence,
+            format_options: FormatOptions::default(),
+            still_hold: StillHold::default(),
+            every_nth: 1,
+            qc_sample_enabled: false,
+            qc_sample: 10,
+            generate_pattern: false,
+            test_pattern: TestPattern::default(),
+            job_notes: String::new(),
+            share_start: 0.0,
+            share_length: 5.0,
+            share_format: ShareFormat::Gif,
+            share_after_encode: false,
+            share_status: None,
+            share_receiver: std::sync::mpsc::channel().1,
+            still_frames: String::new(),
+            still_status: None,
+            still_receiver: std::sync::mpsc::channel().1,
+            rerender_frames: Vec::new(),
+            rerender_status: None,
+            rerender_receiver: std::sync::mpsc::channel().1,
+            purge_status: None,
+            purge_receiver: std::sync::mpsc::channel().1,
+            renumber_scan: None,
+            renumber_template: None,
+            renumber_plan: None,
+            renumber_status: None,
+            renumber_receiver: std::sync::mpsc::channel().1,
+            naming: NamingRules::default(),
+            stalled_since: None,
+            resume_after_pause: false,
+            pause_at_frame: None,
+            pause_frame_input: 0,
+            overlay_choice: OverlayChoice::ShowDefault,
+            overlay_style: OverlayStyle::default(),
+            extra_overlays: Vec::new(),
+            matte: None,
+            source_fps: None,
+            source_check: None,
+            source_error: None,
+            inverse_telecine: false,
+            tolerate_errors: false,
+            pipe_enabled: false,
+            pipe_command: String::new(),
+            fps_decision: None,
+            source_conflict_accepted: false,
+        };
+        app.load_proxy();
+        app.probe_audio();
+        app.source_fps = app.probe_frame_rate();
+        app.check_source();
+
+        if let Some(index) = app
+            .shows
+            .iter()
+            .position(|show| show.name == app.settings.active_show)
+        {
+            app.apply_show(Some(index));
+        }
+
+        if let Some(url) = update_url() {
+            app.update_receiver = spawn_check(url);
+        }
+
+        staging::sweep_orphans(&app.settings.job_environment());
+        set_probe_timeout(Duration::from_secs(app.settings.probe_timeout_secs));
+        app.spool = app.settings.spool_dir.clone().map(spawn_watcher);
+
+        let crash_reports = crash::pending_reports();
+        if !crash_reports.is_empty() {
+            app.dialog_state = DialogState::CrashReports(crash_reports);
+        }
+
+        app
+    }
+
+    fn delivery_fps(&self) -> Option<f64> {
+        self.active_show
+            .and_then(|index| self.shows.get(index))
+            .and_then(|show| show.delivery_fps)
+    }
+
+    fn show_leader(&self) -> Option<LeaderSpec> {
+        self.active_show
+            .and_then(|index| self.shows.get(index))
+            .and_then(|show| show.leader)
+    }
+
+    // Source rate after pull-down removal, before any retime
+    fn progressive_fps(&self) -> Option<f64> {
+        self.source_fps.map(|fps| {
+            if self.inverse_telecine {
+                inverse_telecine_rate(fps)
+            } else {
+                fps
+            }
+        })
+    }
+
+    // Swaps in a new source and re-probes everything derived from it
+    pub fn load_input(&mut self, path: PathBuf) {
+        self.original_base_name = path
+            .file_stem()
+            .map(|s| sanitize_base_name(&s.to_string_lossy()))
+            .unwrap_or_else(|| "video".to_string());
+        self.input_video = path;
+        self.fps_decision = None;
+        self.source_conflict_accepted = false;
+        self.job_notes.clear();
+        self.player = None;
+        self.update_base_name();
+        self.load_proxy();
+        self.probe_audio();
+        self.source_fps = self.probe_frame_rate();
+        if let Some(fps) = self.delivery_fps() {
+            self.still_hold.fps = fps;
+        }
+        self.check_source();
+        self.update_storage_status();
+        self.status = format!("Loaded {}", self.input_video.display());
+    }
+
+    // A still reports image2's nominal 25 fps, which isn't a rate worth matching a show to
+    fn probe_frame_rate(&self) -> Option<f64> {
+        if is_still_image(&self.input_video) {
+            return None;
+        }
+        get_frame_rate(&self.input_video, &self.ffprobe_path).ok()
+    }
+
+    fn handle_dropped_files(&mut self, ctx: &egui::Context) {
+        let dropped: Vec<PathBuf> = ctx.input(|i| {
+            i.raw
+                .dropped_files
+                .iter()
+                .filter_map(|file| file.path.clone())
+                .collect()
+        });
+        if dropped.is_empty() {
+            return;
+        }
+        if self.encoding {
+            self.status = "Cannot change the input while encoding".to_string();
+            return;
+        }
+
+        let (videos, rejected): (Vec<PathBuf>, Vec<PathBuf>) =
+            dropped.into_iter().partition(|path| {
+                is_still_image(path)
+                    || path
+                        .extension()
+                        .and_then(|ext| ext.to_str())
+                        .is_some_and(|ext| {
+                            VIDEO_EXTENSIONS
+                                .iter()
+                                .any(|known| known.eq_ignore_ascii_case(ext))
+                        })
+            });
+        let Some(first) = videos.first().cloned() else {
+            self.status = format!(
+                "Not a video file or still: {}",
+                rejected
+                    .first()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_default()
+            );
+            return;
+        };
+        self.load_input(first);
+        // Several files at once become queue jobs with the current settings
+        if videos.len() > 1 {
+            match self.output_dir.clone() {
+                Some(output_dir) => {
+                    for video in &videos {
+                        self.queue.add(
+                            video.clone(),
+                            self.resolution,
+                            output_dir.clone(),
+                            self.output_format,
+                            String::new(),
+                        );
+                    }
+                    self.status = format!("Queued {} dropped files", videos.len());
+                }
+                None => {
+                    self.status = format!(
+                        "{} | Set an output directory to queue the other {} files",
+                        self.status,
+                        videos.len() - 1
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_source(&mut self) {
+        let size = get_resolution(&self.input_video, &self.ffprobe_path);
+        let tags = get_picture_tags(&self.input_video, &self.ffprobe_path);
+        self.source_error = None;
+        self.source_check = match (size, tags) {
+            (Ok(size), Ok(tags)) => Some(SourceCheck::of(size, &tags)),
+            (Err(e), _) | (_, Err(e)) => {
+                if self.input_video.exists() {
+                    self.source_error = Some(user_message(&e));
+                }
+                None
+            }
+        };
+    }
+
+    // (source, delivery) when the show's spec asks for a different rate than the source has
+    fn fps_mismatch(&self) -> Option<(f64, f64)> {
+        let (source, delivery) = (self.progressive_fps()?, self.delivery_fps()?);
+        ((source - delivery).abs() > 0.01).then_some((source, delivery))
+    }
+
+    pub fn probe_audio(&mut self) {
+        self.audio_layout = get_audio_layout(&self.input_video, &self.ffprobe_path)
+            .ok()
+            .flatten();
+        if self.audio_layout.is_none() {
+            self.audio_stems = None;
+        }
+    }
+
+    pub fn load_proxy(&mut self) {
+        self.proxy_status = None;
+        if self.input_video.exists() && !is_still_image(&self.input_video) {
+            self.proxy_receiver = spawn_proxy(
+                self.ffmpeg_path.clone(),
+                self.input_video.clone(),
+                self.settings.job_environment(),
+            );
         }
     }
 
@@ -118,78 +559,686 @@ impl DeliveryEncoderApp {
             }
             Err(e) => {
                 self.sufficient_storage = false;
-                self.storage_error = Some(e.to_string());
+                self.storage_error = Some(user_message(&e));
             }
         }
     }
 
     fn check_for_existing_frames(&self) -> bool {
+        !self.existing_output_files().is_empty()
+    }
+
+    // Outputs of the current job already in the output directory
+    fn existing_output_files(&self) -> Vec<PathBuf> {
+        let Some(output_dir) = &self.output_dir else {
+            return Vec::new();
+        };
+        let Ok(entries) = std::fs::read_dir(output_dir) else {
+            return Vec::new();
+        };
+
+        let extension = self.output_extension();
+        entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|s| s.to_str())
+                    .is_some_and(|name| is_output_file(&self.base_name, &extension, name))
+            })
+            .collect()
+    }
+
+    pub fn check_storage_availability(&mut self) -> Result<f64> {
+        use fs2::available_space;
+
+        self.volume_split.clear();
+        let output_dir = self
+            .output_dir
+            .clone()
+            .ok_or_else(|| anyhow!("Output directory not set"))?;
+        let output_dir = &output_dir;
+        // A resumed split keeps the volumes it started on, whatever is free now
+        if let Some(manifest) = SplitManifest::read(output_dir, &self.base_name) {
+            self.volume_split = manifest.parts.into_iter().skip(1).collect();
+        }
+
+        // The same raster plan_encoding writes: a broadcast format's own size first
+        let (width, height) = match self
+            .output_format
+            .frame_size()
+            .or(self.resolution.target_size())
+        {
+            Some(size) => size,
+            None => get_resolution(&self.input_video, &self.ffprobe_path)?,
+        };
+
+        let (duration, frame_rate) = if let Some(pattern) = self.generated_pattern() {
+            (pattern.seconds, pattern.fps)
+        } else if is_still_image(&self.input_video) {
+            (self.still_hold.seconds, self.still_hold.fps)
+        } else {
+            let leader_secs = self
+                .show_leader()
+                .map_or(0.0, |leader| leader.seconds as f64);
+            let source_rate = get_frame_rate(&self.input_video, &self.ffprobe_path)?;
+            // A retime keeps the running time, so the delivery rate sets the frame count;
+            // inverse telecine keeps four frames of every five
+            let frame_rate = match (self.fps_decision, self.fps_mismatch()) {
+                (Some(FpsDecision::Retime), Some((_, delivery))) => delivery,
+                _ if self.inverse_telecine => inverse_telecine_rate(source_rate),
+                _ => source_rate,
+            };
+            (
+                get_duration(&self.input_video, &self.ffprobe_path)? + leader_secs,
+                frame_rate,
+            )
+        };
+        let mbps_to_frame_bytes =
+            |mbps: u32| (mbps as f64 * 1_000_000.0 / 8.0 / frame_rate.max(1.0)) as u64;
+        let mut bytes_per_frame = match self.format_options.bitrate_mbps(self.output_format) {
+            Some(mbps) => mbps_to_frame_bytes(mbps),
+            None => self
+                .format_options
+                .sequence_frame_bytes(self.output_format, width, height),
+        };
+        if self.output_format.is_sequence() && self.format_options.review_mp4 {
+            bytes_per_frame += mbps_to_frame_bytes(self.format_options.h264_rate.estimated_mbps());
+        }
+        if self.format_options.review_webm && self.output_format != OutputFormat::WebmVp9 {
+            bytes_per_frame +=
+                mbps_to_frame_bytes(OutputFormat::WebmVp9.bitrate_mbps().unwrap_or(0));
+        }
+        let every_nth = if self.output_format.is_sequence() {
+            self.every_nth.max(1) as u64
+        } else {
+            1
+        };
+        let total_frames = ((duration * frame_rate).ceil() as u64).div_ceil(every_nth);
+        let mut required_bytes = bytes_per_frame * total_frames;
+        if self.output_format.is_sequence() && self.qc_sample_enabled {
+            let sampled = (self.qc_sample as u64 + 3).min(total_frames);
+            required_bytes += sampled
+                * self
+                    .format_options
+                    .sequence_frame_bytes(self.output_format, width, height);
+        }
+        let required_bytes_with_buffer = (required_bytes as f64 * 1.2) as u64;
+
+        let free_space = available_space(output_dir)
+            .map_err(|e| EncoderError::from_io(&e, output_dir, IoSide::Output))?;
+
+        if free_space < required_bytes_with_buffer {
+            if !self.volume_split.is_empty() {
+                return Ok(required_bytes_with_buffer as f64 / (1024.0 * 1024.0 * 1024.0));
+            }
+            if let Some(split) =
+                self.plan_volume_split(output_dir, free_space, bytes_per_frame, total_frames)
+            {
+                self.volume_split = split;
+                return Ok(required_bytes_with_buffer as f64 / (1024.0 * 1024.0 * 1024.0));
+            }
+            let required_gb = required_bytes_with_buffer as f64 / (1024.0 * 1024.0 * 1024.0);
+            let available_gb = free_space as f64 / (1024.0 * 1024.0 * 1024.0);
+            return Err(EncoderError::DiskFull {
+                required_gb,
+                available_gb,
+            }
+            .into());
+        }
+
+        Ok(required_bytes_with_buffer as f64 / (1024.0 * 1024.0 * 1024.0))
+    }
+
+    // The output folder's share of a sequence that didn't fit, and the later volumes
+    // that get the rest; None when it can't or needn't be split
+    fn plan_volume_split(
+        &self,
+        output_dir: &Path,
+        free_space: u64,
+        bytes_per_frame: u64,
+        total_frames: u64,
+    ) -> Option<Vec<SplitPart>> {
+        use fs2::available_space;
+
+        let config = self.encoding_config(output_dir.to_path_buf());
+        if self.settings.overflow_dirs.is_empty()
+            || !self.output_format.is_sequence()
+            || config.pipe_command.is_some()
+            || config.every_nth.is_some()
+            || config.leader.is_some()
+            || config.still_hold.is_some()
+            || config.generator.is_some()
+        {
+            return None;
+        }
+
+        // Each overflow volume gets a folder of the same name
+        let folder = output_dir.file_name()?;
+        let mut volumes = vec![(output_dir.to_path_buf(), free_space)];
+        for dir in &self.settings.overflow_dirs {
+            volumes.push((dir.join(folder), available_space(dir).ok()?));
+        }
+        let parts = plan_split(&volumes, bytes_per_frame, total_frames as u32)?;
+        // The manifest lives in the output folder, so it has to take the first frames
+        (parts.first()?.dir == output_dir).then(|| parts.into_iter().skip(1).collect())
+    }
+
+    fn output_frames(&self) -> Vec<PathBuf> {
+        let mut frames = Vec::new();
         if let Some(output_dir) = &self.output_dir {
-            if let Ok(entries) = std::fs::read_dir(output_dir) {
+            // A split sequence's later frames sit on the volumes its manifest lists
+            let mut dirs = vec![output_dir.clone()];
+            if let Some(manifest) = SplitManifest::read(output_dir, &self.base_name) {
+                dirs.extend(manifest.parts.into_iter().skip(1).map(|part| part.dir));
+            }
+            for dir in dirs {
+                let Ok(entries) = std::fs::read_dir(&dir) else {
+                    continue;
+                };
                 for entry in entries.flatten() {
                     let path = entry.path();
                     if let Some(file_name) = path.file_name().and_then(|s| s.to_str()) {
-                        if file_name.starts_with(&self.base_name) && file_name.ends_with(".png") {
-                            return true;
+                        if is_output_file(&self.base_name, &self.output_extension(), file_name) {
+                            frames.push(path.clone());
                         }
                     }
                 }
             }
         }
-        false
+        frames.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+        frames
     }
 
-    pub fn check_storage_availability(&self) -> Result<f64> {
-        use fs2::available_space;
+    pub fn start_consolidate(&mut self) {
+        let Some(output_dir) = self.output_dir.clone() else {
+            return;
+        };
+        self.consolidate_receiver = spawn_consolidate(output_dir, self.base_name.clone());
+        self.consolidate_status = Some(ConsolidateStatus::Moving { done: 0, total: 0 });
+    }
 
-        let output_dir = self
-            .output_dir
+    pub fn is_replicating(&self) -> bool {
+        !self.replication_threads.is_empty()
+    }
+
+    pub fn start_replication(&mut self) {
+        if self.replica_dirs.is_empty() || self.is_replicating() {
+            return;
+        }
+
+        let frames = self.output_frames();
+        let (status_sender, status_receiver) = std::sync::mpsc::channel();
+
+        self.replication_receiver = status_receiver;
+        self.replication_status = vec![ReplicationStatus::Pending; self.replica_dirs.len()];
+        self.replication_threads = replicate_all(frames, self.replica_dirs.clone(), status_sender);
+        self.replication_heartbeat = Instant::now();
+    }
+
+    fn start_archive(&mut self) {
+        let Some(archive_dir) = self.settings.archive_dir.clone() else {
+            return;
+        };
+        if self
+            .archive_status
             .as_ref()
-            .ok_or_else(|| anyhow!("Output directory not set"))?;
+            .is_some_and(|status| !status.is_finished())
+        {
+            return;
+        }
+        self.archive_status = Some(ArchiveStatus::Verifying);
+        self.archive_receiver = spawn_archive(
+            self.input_video.clone(),
+            archive_dir,
+            self.settings.archive_move,
+        );
+    }
+
+    pub fn open_player(&mut self) {
+        let frames = self.output_frames();
+        if frames.is_empty() {
+            self.status = "No frames to play".to_string();
+            self.current_frame = "File: -- | No frames to play | ETA: --:--".to_string();
+            return;
+        }
+
+        let config = self.encoding_config(PathBuf::new());
+        let fps = output_frame_rate(&config).unwrap_or(24.0);
+        let source = self.input_video.exists().then(|| {
+            let proxy = self
+                .proxy_status
+                .as_ref()
+                .and_then(|status| status.path())
+                .map(|path| path.to_path_buf());
+            let decoded = proxy.as_ref().unwrap_or(&self.input_video);
+            SourceClip {
+                ffmpeg_path: self.ffmpeg_path.clone(),
+                input_video: self.input_video.clone(),
+                stream_start: get_start_time(decoded, &self.ffprobe_path).unwrap_or(0.0),
+                proxy,
+                fit: self.fit_mode,
+                leader_frames: leader_frames(&config, fps),
+                environment: config.environment.clone(),
+            }
+        });
+        self.player = Some(SequencePlayer::new(frames, fps, source));
+    }
+
+    fn overlay_dir(&self) -> PathBuf {
+        self.active_show
+            .and_then(|index| self.shows.get(index))
+            .and_then(|show| show.overlay_dir.clone())
+            .unwrap_or_else(|| PathBuf::from("assets"))
+    }
+
+    fn overlay_image(&self, resolution: Resolution) -> Option<PathBuf> {
+        match &self.overlay_choice {
+            OverlayChoice::ShowDefault => Some(resolution.overlay_file(&self.overlay_dir())),
+            OverlayChoice::File(path) => Some(path.clone()),
+            OverlayChoice::None => None,
+        }
+    }
+
+    // The chosen overlay under the extra layers, bottom first
+    fn overlays(&self, resolution: Resolution) -> Vec<OverlaySpec> {
+        self.overlay_image(resolution)
+            .map(|image| OverlaySpec {
+                image,
+                style: self.overlay_style,
+            })
+            .into_iter()
+            .chain(self.extra_overlays.iter().cloned())
+            .collect()
+    }
+
+    // Without alpha the overlay would cover the whole picture
+    fn pick_overlay_file(&mut self) -> Option<PathBuf> {
+        let path = FileDialog::new().add_filter("PNG", &["png"]).pick_file()?;
+        match png_has_alpha(&path) {
+            Ok(true) => return Some(path),
+            Ok(false) => {
+                self.status = format!(
+                    "Error: {} has no alpha channel and would hide the picture",
+                    path.display()
+                );
+            }
+            Err(e) => self.status = format!("Error: {}", user_message(&e)),
+        }
+        None
+    }
+
+    // At the delivery raster and rate, or the loaded source's when the job keeps those
+    fn generated_pattern(&self) -> Option<TestPattern> {
+        self.generate_pattern.then(|| TestPattern {
+            size: self
+                .output_format
+                .frame_size()
+                .or(self.resolution.target_size())
+                .or(self.source_check.as_ref().map(|check| check.size))
+                .unwrap_or(self.test_pattern.size),
+            fps: self
+                .delivery_fps()
+                .or(self.source_fps)
+                .unwrap_or(self.test_pattern.fps),
+            ..self.test_pattern
+        })
+    }
+
+    fn encoding_config(&self, output_dir: PathBuf) -> EncodingConfig {
+        EncodingConfig {
+            input_video: self.input_video.clone(),
+            still_hold: is_still_image(&self.input_video).then_some(self.still_hold),
+            generator: self.generated_pattern(),
+            leader: self.show_leader(),
+            every_nth: (self.every_nth > 1).then_some(self.every_nth),
+            qc_sample: self.qc_sample_enabled.then_some(self.qc_sample),
+            split: self.volume_split.clone(),
+            frame_window: None,
+            overlays: self.overlays(self.resolution),
+            output_dir,
+            ffmpeg_path: self.ffmpeg_path.clone(),
+            ffprobe_path: self.ffprobe_path.clone(),
+            resolution: self.resolution,
+            fit: self.fit_mode,
+            base_name: self.base_name.clone(),
+            subtitles: self.subtitles.clone(),
+            audio_stems: self.audio_stems,
+            output_format: self.output_format,
+            format_options: self.format_options,
+            notes: self.job_notes.trim().to_string(),
+            naming: self.naming.clone(),
+            stall_timeout: Duration::from_secs(self.settings.stall_timeout_secs.max(10)),
+            first_frame_timeout: Duration::from_secs(
+                self.settings.first_frame_timeout_secs.max(10),
+            ),
+            matte: self.matte,
+            retime_fps: match (self.fps_decision, self.fps_mismatch()) {
+                (Some(FpsDecision::Retime), Some((_, delivery))) => Some(delivery),
+                _ => None,
+            },
+            inverse_telecine: self.inverse_telecine,
+            tolerate_errors: self.tolerate_errors,
+            pipe_command: (self.pipe_enabled
+                && self.output_format.is_sequence()
+                && !self.pipe_command.trim().is_empty())
+            .then(|| self.pipe_command.trim().to_string()),
+            environment: self.settings.job_environment(),
+            show: self
+                .active_show
+                .and_then(|index| self.shows.get(index))
+                .map(|show| show.name.clone()),
+            energy: self.settings.energy_rates(),
+            permissions: self.settings.output_permissions(),
+        }
+    }
 
-        let (width, height) = match self.resolution {
-            Resolution::K2 => (2048, 2048),
-            Resolution::K4 => (4096, 4096),
-            Resolution::K6 => get_resolution(&self.input_video, &self.ffprobe_path)?,
+    // Markdown summary of what's in the output folder, for the delivery email
+    fn copy_summary(&mut self, ctx: &egui::Context) {
+        let Some(output_dir) = self.output_dir.clone() else {
+            return;
         };
+        let extension = self.output_extension();
+        let summary = DeliverySummary {
+            base_name: &self.base_name,
+            output_dir: &output_dir,
+            extension: &extension,
+            format: self.output_format,
+            size: self
+                .output_format
+                .frame_size()
+                .or(self.resolution.target_size())
+                .or(self.source_check.as_ref().map(|check| check.size)),
+            fps: self.delivery_fps().or(self.source_fps),
+            notes: self.job_notes.trim(),
+        }
+        .render(&self.existing_output_files());
+        ctx.output_mut(|output| output.copied_text = summary);
+        self.status = "Delivery summary copied".to_string();
+    }
 
-        // Updated for 16-bit RGB (6 bytes per pixel instead of 4)
-        let bytes_per_frame = (width as u64) * (height as u64) * 6;
-        let duration = get_duration(&self.input_video, &self.ffprobe_path)?;
-        let frame_rate = get_frame_rate(&self.input_video, &self.ffprobe_path)?;
-        let total_frames = (duration * frame_rate).ceil() as u64;
-        let required_bytes = bytes_per_frame * total_frames;
-        let required_bytes_with_buffer = (required_bytes as f64 * 1.2) as u64;
+    // The form's settings with a queued job's own source, size, format and folder,
+    // the way start_next_job loads it. The job's source hasn't been probed, so it
+    // is encoded at its own rate and without the form's volume split.
+    fn job_config(&self, job: &Job) -> EncodingConfig {
+        let original = job
+            .input_video
+            .file_stem()
+            .map(|s| sanitize_base_name(&s.to_string_lossy()))
+            .unwrap_or_else(|| "video".to_string());
+        EncodingConfig {
+            input_video: job.input_video.clone(),
+            still_hold: is_still_image(&job.input_video).then_some(self.still_hold),
+            split: Vec::new(),
+            overlays: self.overlays(job.resolution),
+            resolution: job.resolution,
+            base_name: self.output_base_name(&original, job.resolution),
+            output_format: job.output_format,
+            notes: job.notes.trim().to_string(),
+            retime_fps: None,
+            ..self.encoding_config(job.output_dir.clone())
+        }
+    }
+
+    // Queued jobs in queue order, each after the jobs it depends on
+    fn queue_configs(&self) -> Vec<EncodingConfig> {
+        let mut ordered: Vec<&Job> = Vec::new();
+        while ordered.len() < self.queue.jobs.len() {
+            let ready = self.queue.jobs.iter().find(|job| {
+                !ordered.iter().any(|done| done.id == job.id)
+                    && job.depends_on.iter().all(|dep| {
+                        ordered.iter().any(|done| done.id == *dep)
+                            || !self.queue.jobs.iter().any(|other| other.id == *dep)
+                    })
+            });
+            // set_dependency never lets a cycle in, so this only guards the loop
+            let Some(job) = ready else {
+                break;
+            };
+            ordered.push(job);
+        }
+        ordered
+            .into_iter()
+            .map(|job| self.job_config(job))
+            .collect()
+    }
 
-        let free_space = available_space(output_dir)?;
+    // Every queued job when there are any, otherwise just the form
+    pub fn export_script(&mut self) {
+        let Some(output_dir) = self.output_dir.clone() else {
+            return;
+        };
+        self.update_base_name();
+        let default_name = if cfg!(windows) {
+            format!("{}.bat", self.base_name)
+        } else {
+            format!("{}.sh", self.base_name)
+        };
+        let Some(path) = FileDialog::new()
+            .add_filter("Shell script", &["sh"])
+            .add_filter("Batch file", &["bat"])
+            .set_file_name(default_name)
+            .save_file()
+        else {
+            return;
+        };
 
-        if free_space < required_bytes_with_buffer {
-            let required_gb = required_bytes_with_buffer as f64 / (1024.0 * 1024.0 * 1024.0);
-            let available_gb = free_space as f64 / (1024.0 * 1024.0 * 1024.0);
-            return Err(anyhow!(
-                "Insufficient storage: {:.2}GB required, {:.2}GB available",
-                required_gb,
-                available_gb
+        let configs = if self.queue.jobs.is_empty() {
+            vec![self.encoding_config(output_dir)]
+        } else {
+            self.queue_configs()
+        };
+        self.status = match export_script(&configs, &path) {
+            Ok(()) if configs.len() > 1 => format!(
+                "Script for {} jobs saved to {}",
+                configs.len(),
+                path.display()
+            ),
+            Ok(()) => format!("Script saved to {}", path.display()),
+            Err(e) => format!("Could not export script: {}", user_message(&e)),
+        };
+    }
+
+    pub fn start_stills(&mut self) {
+        let Some(output_dir) = self.output_dir.clone() else {
+            self.still_status = Some(StillStatus::Failed("Output directory not set".to_string()));
+            return;
+        };
+
+        let frames = match parse_frame_list(&self.still_frames) {
+            Ok(frames) if frames.is_empty() => {
+                self.still_status = Some(StillStatus::Failed("No frames marked".to_string()));
+                return;
+            }
+            Ok(frames) => frames,
+            Err(e) => {
+                self.still_status = Some(StillStatus::Failed(e.to_string()));
+                return;
+            }
+        };
+
+        self.still_status = Some(StillStatus::Exporting {
+            done: 0,
+            total: frames.len(),
+        });
+        self.still_receiver = spawn_stills(self.encoding_config(output_dir), frames);
+    }
+
+    pub fn start_rerender(&mut self) {
+        let Some(output_dir) = self.output_dir.clone() else {
+            self.rerender_status = Some(RerenderStatus::Failed(
+                "Output directory not set".to_string(),
             ));
+            return;
+        };
+
+        self.rerender_status = Some(RerenderStatus::Rendering {
+            done: 0,
+            total: self.rerender_frames.len(),
+        });
+        self.rerender_receiver = spawn_rerender(
+            self.encoding_config(output_dir),
+            self.rerender_frames.clone(),
+        );
+    }
+
+    pub fn start_share(&mut self) {
+        let Some(output_dir) = &self.output_dir else {
+            self.share_status = Some(ShareStatus::Failed("Output directory not set".to_string()));
+            return;
+        };
+
+        let source_size = match get_resolution(&self.input_video, &self.ffprobe_path) {
+            Ok(size) => size,
+            Err(e) => {
+                self.share_status = Some(ShareStatus::Failed(user_message(&e)));
+                return;
+            }
+        };
+
+        let output = output_dir.join(format!(
+            "{}_share_{:.0}s.{}",
+            self.base_name,
+            self.share_start,
+            self.share_format.extension()
+        ));
+        self.share_receiver = spawn_share(ShareRequest {
+            ffmpeg_path: self.ffmpeg_path.clone(),
+            input_video: self.input_video.clone(),
+            overlays: self.overlays(self.resolution),
+            matte: self.matte,
+            source_size,
+            fixed_size: self
+                .output_format
+                .frame_size()
+                .or(self.resolution.target_size()),
+            fit: self.fit_mode,
+            subtitles: self.subtitles.clone(),
+            start_secs: self.share_start,
+            first_frame: self
+                .source_fps
+                .map_or(0, |fps| (self.share_start * fps).round() as u32),
+            duration_secs: self.share_length,
+            format: self.share_format,
+            output,
+            environment: self.settings.job_environment(),
+        });
+        self.share_status = Some(ShareStatus::Exporting);
+    }
+
+    // Switching shows replaces the scoped settings instead of merging them
+    pub fn apply_show(&mut self, index: Option<usize>) {
+        self.active_show = index;
+
+        match index.and_then(|index| self.shows.get(index)).cloned() {
+            Some(show) => {
+                self.resolution = show.resolution;
+                self.output_dir = show.output_dir;
+                self.replica_dirs = show.destinations;
+                self.naming = show.naming;
+                self.overlay_style = show.overlay_style;
+                self.extra_overlays = show.extra_overlays;
+                self.matte = show.matte;
+                self.settings.active_show = show.name;
+            }
+            None => {
+                self.replica_dirs.clear();
+                self.naming = NamingRules::default();
+                self.overlay_style = OverlayStyle::default();
+                self.extra_overlays.clear();
+                self.matte = None;
+                self.settings.active_show.clear();
+            }
         }
 
-        Ok(required_bytes_with_buffer as f64 / (1024.0 * 1024.0 * 1024.0))
+        self.replication_status.clear();
+        self.fps_decision = None;
+        self.update_base_name();
+        self.update_storage_status();
+        if let Err(e) = self.settings.save() {
+            self.status = format!("Could not save settings: {}", e);
+        }
+    }
+
+    fn save_show(&mut self) {
+        let Some(show) = self.active_show.and_then(|index| self.shows.get_mut(index)) else {
+            return;
+        };
+
+        show.resolution = self.resolution;
+        show.output_dir = self.output_dir.clone();
+        show.destinations = self.replica_dirs.clone();
+        show.naming = self.naming.clone();
+        show.overlay_style = self.overlay_style;
+        show.extra_overlays = self.extra_overlays.clone();
+        show.matte = self.matte;
+
+        self.status = match show.save() {
+            Ok(()) => format!("Saved show {}", show.name),
+            Err(e) => format!("Could not save show: {}", e),
+        };
+    }
+
+    fn create_show(&mut self) {
+        let name = self.new_show_name.trim().to_string();
+        if name.is_empty() || self.shows.iter().any(|show| show.name == name) {
+            return;
+        }
+
+        self.shows.push(ShowProfile {
+            name: name.clone(),
+            ..Default::default()
+        });
+        self.shows.sort_by_key(|show| show.name.to_lowercase());
+        self.active_show = self.shows.iter().position(|show| show.name == name);
+        self.settings.active_show = name;
+        self.new_show_name.clear();
+        self.save_show();
+        let _ = self.settings.save();
+    }
+
+    fn send_telemetry(&self, event: &'static str, error: Option<String>) {
+        telemetry::send(
+            &self.settings,
+            TelemetryEvent {
+                event,
+                job: self.base_name.clone(),
+                resolution: self.resolution.as_file_tag().to_string(),
+                duration_secs: self.encode_started.map(|t| t.elapsed().as_secs_f64()),
+                error,
+            },
+        );
     }
 
     // Update base name with current resolution tag
     fn update_base_name(&mut self) {
-        let current_tag = self.resolution.as_file_tag();
-        let mut new_name = self.original_base_name.clone();
+        self.base_name = self.output_base_name(&self.original_base_name, self.resolution);
+    }
+
+    fn output_base_name(&self, original: &str, resolution: Resolution) -> String {
+        let current_tag = resolution.as_file_tag();
+        let mut new_name = if self.generate_pattern {
+            format!("{}_{}", original, self.test_pattern.kind.file_tag())
+        } else {
+            original.to_string()
+        };
 
         // Replace any existing resolution tags (case insensitive)
-        for tag in &["2k", "4k", "6k", "2K", "4K", "6K"] {
+        // Longest first, so "dci4k" isn't read as "4k" nor "uhd" as "hd"
+        for tag in &[
+            "dci4k", "DCI4K", "uhd", "UHD", "hd", "HD", "2k", "4k", "6k", "2K", "4K", "6K",
+        ] {
             if new_name.contains(tag) {
                 new_name = new_name.replace(tag, current_tag);
                 break;
             }
         }
 
-        self.base_name = new_name;
+        self.naming.apply(&new_name)
+    }
+
+    fn output_extension(&self) -> String {
+        self.naming.extension(self.output_format)
     }
 
     pub fn start_encoding(&mut self) {
@@ -208,59 +1257,141 @@ impl DeliveryEncoderApp {
         }
 
         let input_video = self.input_video.clone();
-        let overlay_image = match self.resolution {
-            Resolution::K2 => PathBuf::from("assets/overlay_2k.png"),
-            Resolution::K4 => PathBuf::from("assets/overlay_4k.png"),
-            Resolution::K6 => PathBuf::from("assets/overlay_6k.png"),
-        };
+        let missing_overlay = self
+            .overlays(self.resolution)
+            .into_iter()
+            .map(|layer| layer.image)
+            .find(|path| !path.exists());
 
         let validation_errors = [
             (
                 !self.ffmpeg_path.exists(),
-                format!("Error: FFmpeg not found at {}", self.ffmpeg_path.display()),
+                EncoderError::MissingBinary {
+                    name: "FFmpeg",
+                    path: self.ffmpeg_path.clone(),
+                },
             ),
             (
                 !self.ffprobe_path.exists(),
-                format!(
-                    "Error: FFprobe not found at {}",
-                    self.ffprobe_path.display()
-                ),
+                EncoderError::MissingBinary {
+                    name: "FFprobe",
+                    path: self.ffprobe_path.clone(),
+                },
             ),
             (
-                !input_video.exists(),
-                format!("Error: Input video not found at {}", input_video.display()),
+                !self.generate_pattern && !input_video.exists(),
+                EncoderError::source_unreadable(&input_video, "File not found"),
             ),
             (
-                !overlay_image.exists(),
-                format!(
-                    "Error: Overlay image not found at {}",
-                    overlay_image.display()
+                missing_overlay.is_some(),
+                EncoderError::source_unreadable(
+                    missing_overlay.as_deref().unwrap_or(Path::new("")),
+                    "Overlay image not found",
                 ),
             ),
         ];
 
-        if let Some((_, error)) = validation_errors.iter().find(|(cond, _)| *cond) {
-            self.status = error.clone();
-            self.current_frame = format!("File: -- | {} | ETA: --:--", error);
+        if let Some((_, error)) = validation_errors.into_iter().find(|(cond, _)| *cond) {
+            let message = format!("Error: {}", user_message(&error.into()));
+            self.status = message.clone();
+            self.current_frame = format!("File: -- | {} | ETA: --:--", message);
             return;
         }
 
-        match self.check_storage_availability() {
-            Ok(required_gb) => {
-                self.status = format!(
-                    "Starting... | Free space available: {:.2}GB required",
+        // Checked here too, as the load-time probe may have failed on a slow share
+        if !self.generate_pattern
+            && matches!(
+                has_video_stream(&input_video, &self.ffprobe_path),
+                Ok(false)
+            )
+        {
+            let error = EncoderError::NoVideoStream { path: input_video };
+            let message = format!("Error: {}", user_message(&error.into()));
+            self.status = message.clone();
+            self.current_frame = format!("File: -- | {} | ETA: --:--", message);
+            return;
+        }
+
+        if let Some((source, delivery)) = self.fps_mismatch().filter(|_| !self.generate_pattern) {
+            match self.fps_decision {
+                None => {
+                    self.dialog_state = DialogState::FrameRateMismatch { source, delivery };
+                    return;
+                }
+                Some(decision) => crash::log(format!(
+                    "Frame rate mismatch for {}: source {:.3} fps, delivery {:.3} fps, {}",
+                    self.base_name,
+                    source,
+                    delivery,
+                    match decision {
+                        FpsDecision::Retime => "retimed to delivery rate",
+                        FpsDecision::KeepSource => "operator kept source rate",
+                    }
+                )),
+            }
+        }
+
+        if let Some(output_dir) = self.output_dir.clone() {
+            let conflict = (!self.source_conflict_accepted
+                && !self.generate_pattern
+                && self.check_for_existing_frames())
+            .then(|| conflicting_source(&output_dir, &self.base_name, &self.input_video))
+            .flatten();
+            if let Some(recorded) = conflict {
+                self.dialog_state = DialogState::SourceConflict {
+                    recorded: recorded.path,
+                    proposed: disambiguated_dir(&output_dir, &self.base_name, &self.input_video),
+                };
+                return;
+            }
+        }
+
+        // Probed again when the startup probe failed, e.g. ffmpeg was installed since
+        let capabilities = match self.ffmpeg_capabilities.clone() {
+            Some(capabilities) => Ok(capabilities),
+            None => FfmpegCapabilities::probe(&self.ffmpeg_path),
+        };
+        let supported = capabilities.and_then(|capabilities| {
+            let checked = check_job_support(
+                &self.encoding_config(self.output_dir.clone().unwrap_or_default()),
+                &capabilities,
+            );
+            self.ffmpeg_capabilities = Some(capabilities);
+            checked
+        });
+        if let Err(e) = supported {
+            let message = format!("Error: {}", user_message(&e));
+            self.status = message.clone();
+            self.current_frame = format!("File: -- | {} | ETA: --:--", message);
+            return;
+        }
+
+        match self.check_storage_availability() {
+            Ok(required_gb) => {
+                self.status = format!(
+                    "Starting... | Free space available: {:.2}GB required",
                     required_gb
                 );
             }
             Err(e) => {
-                self.status = format!("Storage error: {}", e);
+                self.status = format!("Storage error: {}", user_message(&e));
                 self.current_frame = format!("File: -- | {} | ETA: --:--", self.status);
                 return;
             }
         }
 
+        crash::set_job_state(format!(
+            "Encoding {} -> {} at {}",
+            self.input_video.display(),
+            self.output_dir.as_ref().unwrap().display(),
+            self.resolution.as_str()
+        ));
+        crash::log(format!("Started encoding {}", self.base_name));
+
         self.status = "Encoding...".to_string();
         self.encoding = true;
+        self.encode_started = Some(Instant::now());
+        self.send_telemetry("job_started", None);
         self.progress = 0.0;
 
         let output_dir = self.output_dir.as_ref().unwrap().clone();
@@ -270,22 +1401,25 @@ impl DeliveryEncoderApp {
             for entry in entries.flatten() {
                 let path = entry.path();
                 if let Some(file_name) = path.file_name().and_then(|s| s.to_str()) {
-                    if file_name.starts_with(&self.base_name) && file_name.ends_with(".png") {
-                        let num_str = file_name
-                            .trim_start_matches(&self.base_name)
-                            .trim_start_matches('-')
-                            .trim_end_matches(".png");
-                        if let Ok(num) = num_str.parse::<u32>() {
-                            if num > max_frame {
-                                max_frame = num;
-                            }
-                        }
+                    if let Some(num) =
+                        output_frame_number(&self.base_name, &self.output_extension(), file_name)
+                    {
+                        max_frame = max_frame.max(num);
                     }
                 }
             }
         }
 
-        let first_file = format!("{}-{:06}.png", self.base_name, max_frame);
+        let first_file = if self.output_format.is_sequence() {
+            format!(
+                "{}-{:06}.{}",
+                self.base_name,
+                max_frame,
+                self.output_extension()
+            )
+        } else {
+            format!("{}.{}", self.base_name, self.output_extension())
+        };
         self.current_frame = format!("File: {} | Starting FFmpeg | ETA: --:--", first_file);
 
         let (progress_sender, progress_receiver) = std::sync::mpsc::channel();
@@ -294,24 +1428,144 @@ impl DeliveryEncoderApp {
         self.progress_receiver = progress_receiver;
         self.cancel_sender = Some(cancel_sender);
 
-        let config = EncodingConfig {
-            input_video,
-            overlay_image,
-            output_dir,
-            ffmpeg_path: self.ffmpeg_path.clone(),
-            ffprobe_path: self.ffprobe_path.clone(),
-            resolution: self.resolution,
-            base_name: self.base_name.clone(),
-        };
+        let config = self.encoding_config(output_dir);
 
         let frame_sender = progress_sender.clone();
         self.worker_thread = Some(thread::spawn(move || {
             if let Err(e) = run_encoding(&config, progress_sender, cancel_receiver) {
-                let _ = frame_sender.send((-1.0, 0, format!("Error: {}", e)));
+                let _ = frame_sender.send((-1.0, 0, format!("Error: {}", user_message(&e))));
             }
         }));
     }
 
+    fn set_active_job_state(&mut self, state: JobState) {
+        if let Some(job) = self.active_job.and_then(|id| self.queue.get_mut(id)) {
+            job.state = state;
+        }
+    }
+
+    // A finished encode stays Uploading/Verifying until its copies and archive land
+    fn track_delivering_job(&mut self) {
+        let Some(id) = self.delivering_job else {
+            return;
+        };
+        let archiving = self
+            .archive_status
+            .as_ref()
+            .is_some_and(|status| !status.is_finished());
+        let state = if self.is_replicating() {
+            JobState::Uploading
+        } else if archiving {
+            JobState::Verifying
+        } else {
+            self.delivering_job = None;
+            let copy_failed = !self.replica_dirs.is_empty()
+                && self
+                    .replication_status
+                    .iter()
+                    .any(|status| matches!(status, ReplicationStatus::Failed(_)));
+            match &self.archive_status {
+                Some(ArchiveStatus::Failed(e)) => JobState::Failed(format!("Archive: {}", e)),
+                _ if copy_failed => JobState::Failed("Copy to a destination failed".to_string()),
+                _ => JobState::Done,
+            }
+        };
+        if let Some(job) = self.queue.get_mut(id) {
+            job.state = state;
+        }
+    }
+
+    fn fail_active_job(&mut self, reason: String) {
+        if let Some(job) = self.active_job.take().and_then(|id| self.queue.get_mut(id)) {
+            job.state = JobState::Failed(reason);
+        }
+    }
+
+    // Loads the next queued job into the form and starts it through the usual checks
+    fn start_next_job(&mut self) {
+        for id in self.queue.fail_blocked() {
+            crash::log(format!("Queue skipping job {}: a dependency failed", id));
+        }
+        let Some(job) = self.queue.next_runnable().cloned() else {
+            if self.queue.is_held() {
+                self.status = "Queue waiting on a job that is still delivering".to_string();
+                return;
+            }
+            self.queue.running = false;
+            self.window_override = false;
+            self.status = match self.queue.blocked_count() {
+                0 => "Queue finished".to_string(),
+                1 => "Queue finished; 1 job failed because a dependency failed".to_string(),
+                n => format!(
+                    "Queue finished; {} jobs failed because a dependency failed",
+                    n
+                ),
+            };
+            return;
+        };
+        if job.input_video != self.input_video {
+            self.load_input(job.input_video.clone());
+        }
+        self.resolution = job.resolution;
+        self.output_dir = Some(job.output_dir.clone());
+        self.output_format = job.output_format;
+        self.job_notes = job.notes.clone();
+        self.update_base_name();
+        self.has_existing_frames = self.check_for_existing_frames();
+        self.update_storage_status();
+
+        crash::log(format!("Queue starting job {} ({})", job.id, job.name()));
+        self.archive_status = None;
+        self.replication_status.clear();
+        self.active_job = Some(job.id);
+        if let Some(job) = self.queue.get_mut(job.id) {
+            job.state = JobState::Running;
+        }
+        self.start_encoding();
+        if !self.encoding && self.dialog_state == DialogState::None {
+            self.fail_active_job(self.status.clone());
+        }
+    }
+
+    fn advance_queue(&mut self) {
+        if self.encoding || self.resume_after_pause || self.dialog_state != DialogState::None {
+            return;
+        }
+        // The copies and archive belong to the finished job until they land, so the
+        // next one waits rather than taking them over
+        if self.delivering_job.is_some() {
+            return;
+        }
+        // The job never got going, e.g. its frame rate dialog was dismissed
+        if self.active_job.is_some() {
+            self.fail_active_job("Not started".to_string());
+        }
+        if self.queue.running && self.window_wait() == 0 {
+            self.start_next_job();
+        }
+    }
+
+    // Seconds the running queue holds its next job for the encode windows
+    fn window_wait(&self) -> u64 {
+        if !self.queue.running
+            || self.window_override
+            || self.active_job.is_some()
+            || self.queue.next_runnable().is_none()
+        {
+            return 0;
+        }
+        self.settings.encode_windows().wait_now()
+    }
+
+    // Kills a hung ffmpeg and restarts from the last written frame once the pause lands
+    pub fn kill_and_resume(&mut self) {
+        crash::log(format!("Killing stalled encode of {}", self.base_name));
+        self.stalled_since = None;
+        self.set_active_job_state(JobState::Running);
+        self.resume_after_pause = true;
+        self.pause_encoding();
+    }
+
     pub fn pause_encoding(&mut self) {
         if let Some(sender) = self.cancel_sender.take() {
             let _ = sender.send(());
@@ -319,290 +1573,2828 @@ impl DeliveryEncoderApp {
     }
 
     pub fn cancel_encoding(&mut self, delete_frames: bool) {
+        crash::log(format!(
+            "Cancelled encoding {} (delete frames: {})",
+            self.base_name, delete_frames
+        ));
+
         if let Some(sender) = self.cancel_sender.take() {
             let _ = sender.send(());
         }
 
-        if delete_frames {
-            if let Some(output_dir) = &self.output_dir {
-                if let Ok(entries) = std::fs::read_dir(output_dir) {
-                    for entry in entries.flatten() {
-                        let path = entry.path();
-                        if let Some(file_name) = path.file_name().and_then(|s| s.to_str()) {
-                            if file_name.starts_with(&self.base_name) && file_name.ends_with(".png")
-                            {
-                                let _ = std::fs::remove_file(&path);
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        let trash_error = if delete_frames {
+            move_to_trash(&self.existing_output_files()).err()
+        } else {
+            None
+        };
 
         self.encoding = false;
-        self.status = "Ready".to_string();
+        self.status = match trash_error {
+            Some(e) => format!("Cancelled, but frames were kept: {}", e),
+            None => "Ready".to_string(),
+        };
         self.progress = 0.0;
         self.current_frame = "File: -- | Idle | ETA: --:--".to_string();
         self.has_existing_frames = self.check_for_existing_frames();
         self.update_storage_status();
         self.dialog_state = DialogState::None;
+        if self.active_job.is_some() {
+            self.queue.running = false;
+            self.fail_active_job("Cancelled".to_string());
+        }
     }
 }
 
-impl eframe::App for DeliveryEncoderApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        let mut style = (*ctx.style()).clone();
-
-        style.text_styles.insert(
-            egui::TextStyle::Body,
-            egui::FontId::new(16.0, egui::FontFamily::Proportional),
-        );
-        style.text_styles.insert(
-            egui::TextStyle::Button,
-            egui::FontId::new(16.0, egui::FontFamily::Proportional),
-        );
-        style.text_styles.insert(
-            egui::TextStyle::Heading,
-            egui::FontId::new(20.0, egui::FontFamily::Proportional),
-        );
-
-        style.visuals = egui::Visuals::dark();
-        style.visuals.widgets.noninteractive.bg_fill = egui::Color32::from_gray(25);
-        style.visuals.widgets.inactive.bg_fill = egui::Color32::from_gray(35);
-        style.visuals.widgets.hovered.bg_fill = egui::Color32::from_gray(45);
-        style.visuals.widgets.active.bg_fill = egui::Color32::from_gray(25);
-        style.visuals.window_fill = egui::Color32::from_gray(20);
-        style.visuals.panel_fill = egui::Color32::from_gray(25);
-        style.visuals.faint_bg_color = egui::Color32::from_gray(35);
+impl DeliveryEncoderApp {
+    fn show_profile_controls(&mut self, ui: &mut egui::Ui) {
+        let mut selected = self.active_show;
 
-        style.visuals.widgets.noninteractive.fg_stroke =
-            egui::Stroke::new(1.0, egui::Color32::from_gray(230));
-        style.visuals.widgets.inactive.fg_stroke =
-            egui::Stroke::new(1.0, egui::Color32::from_gray(240));
-        style.visuals.widgets.hovered.fg_stroke =
-            egui::Stroke::new(1.0, egui::Color32::from_gray(245));
-        style.visuals.widgets.active.fg_stroke =
-            egui::Stroke::new(1.0, egui::Color32::from_gray(250));
+        ui.horizontal(|ui| {
+            ui.label("Show:");
+            let selected_text = self
+                .active_show
+                .and_then(|index| self.shows.get(index))
+                .map_or("None", |show| show.name.as_str())
+                .to_string();
 
-        ctx.set_style(style);
+            ui.add_enabled_ui(!self.encoding, |ui| {
+                egui::ComboBox::from_id_source("show_combo")
+                    .selected_text(selected_text)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut selected, None, "None");
+                        for (index, show) in self.shows.iter().enumerate() {
+                            ui.selectable_value(&mut selected, Some(index), &show.name);
+                        }
+                    });
 
-        while let Ok((progress, frame, message)) = self.progress_receiver.try_recv() {
-            let file_name = format!("{}-{:06}.png", self.base_name, frame);
-            let full_message = format!("File: {} | {}", file_name, message);
+                if self.active_show.is_some() {
+                    let save_button = egui::Button::new("💾 Save Show")
+                        .fill(egui::Color32::from_rgb(30, 90, 100));
+                    if ui.add(save_button).clicked() {
+                        self.save_show();
+                    }
+                }
+            });
+        });
 
-            if progress < 0.0 {
-                self.status = full_message.clone();
-                self.encoding = false;
-                self.current_frame = full_message;
-            } else if progress >= 100.0 {
-                self.progress = 100.0;
-                self.status = "Done!".to_string();
-                self.encoding = false;
-                self.current_frame = full_message;
-            } else {
-                self.progress = progress;
-                self.current_frame = full_message;
-            }
+        if selected != self.active_show {
+            self.apply_show(selected);
         }
 
-        if let Some(handle) = self.worker_thread.take() {
-            if handle.is_finished() {
-                self.cancel_sender = None;
-            } else {
-                self.worker_thread = Some(handle);
+        ui.horizontal(|ui| {
+            ui.label("New Show:");
+            ui.text_edit_singleline(&mut self.new_show_name);
+            let create_enabled = !self.encoding && !self.new_show_name.trim().is_empty();
+            if ui
+                .add_enabled(create_enabled, egui::Button::new("➕ Create"))
+                .clicked()
+            {
+                self.create_show();
             }
-        }
+        });
 
-        if self.encoding {
-            ctx.request_repaint();
-        }
+        if let Some(index) = self.active_show {
+            ui.horizontal(|ui| {
+                ui.label("Overlays:");
+                let browse_button =
+                    egui::Button::new("📂 Browse...").fill(egui::Color32::from_rgb(30, 90, 100));
+                if ui.add_enabled(!self.encoding, browse_button).clicked() {
+                    if let Some(path) = FileDialog::new().pick_folder() {
+                        self.shows[index].overlay_dir = Some(path);
+                        self.save_show();
+                    }
+                }
+                ui.label(self.overlay_dir().display().to_string());
+            });
 
-        // Track previous resolution to detect changes
-        let previous_resolution = self.resolution;
+            ui.horizontal(|ui| {
+                ui.label("Delivery Frame Rate:");
+                let mut enabled = self.shows[index].delivery_fps.is_some();
+                let mut changed = ui
+                    .add_enabled(!self.encoding, egui::Checkbox::new(&mut enabled, ""))
+                    .changed();
+                let mut fps = self.shows[index]
+                    .delivery_fps
+                    .or(self.source_fps)
+                    .unwrap_or(24.0);
+                if enabled {
+                    changed |= ui
+                        .add_enabled(
+                            !self.encoding,
+                            egui::DragValue::new(&mut fps)
+                                .speed(0.01)
+                                .clamp_range(1.0..=120.0)
+                                .max_decimals(3),
+                        )
+                        .changed();
+                }
+                if changed {
+                    self.shows[index].delivery_fps = enabled.then_some(fps);
+                    self.fps_decision = None;
+                    self.save_show();
+                }
+            });
 
-        egui::CentralPanel::default()
-            .frame(egui::Frame {
-                inner_margin: egui::Margin::symmetric(30.0, 30.0),
-                fill: ctx.style().visuals.panel_fill,
-                ..Default::default()
-            })
-            .show(ctx, |ui| {
-                ui.heading("Encoder Settings");
-                ui.add_space(10.0);
+            ui.horizontal(|ui| {
+                ui.label("Countdown Leader:");
+                let mut enabled = self.shows[index].leader.is_some();
+                let mut changed = ui
+                    .add_enabled(!self.encoding, egui::Checkbox::new(&mut enabled, ""))
+                    .on_hover_text(
+                        "Numerals counting down to a 2-pop, two seconds before the first frame",
+                    )
+                    .changed();
+                let mut leader = self.shows[index].leader.unwrap_or_default();
+                if enabled {
+                    changed |= ui
+                        .add_enabled(
+                            !self.encoding,
+                            egui::DragValue::new(&mut leader.seconds)
+                                .clamp_range(3..=20)
+                                .suffix(" s"),
+                        )
+                        .changed();
+                }
+                if changed {
+                    self.shows[index].leader = enabled.then_some(leader);
+                    self.save_show();
+                    self.update_storage_status();
+                }
+            });
+        }
 
-                ui.horizontal(|ui| {
-                    ui.label("Resolution:");
-                    let combo = egui::ComboBox::from_id_source("resolution_combo")
-                        .selected_text(self.resolution.as_str());
+        let source_text = match (self.source_fps, self.progressive_fps()) {
+            (Some(fps), Some(progressive)) if self.inverse_telecine => format!(
+                "{:.3} fps ({:.3} after pull-down removal)",
+                fps, progressive
+            ),
+            (Some(fps), _) => format!("{:.3} fps", fps),
+            _ => "unknown".to_string(),
+        };
+        match self.fps_mismatch() {
+            Some((_, delivery)) => {
+                ui.colored_label(
+                    egui::Color32::LIGHT_YELLOW,
+                    format!(
+                        "Source frame rate: {} (delivery spec: {:.3} fps)",
+                        source_text, delivery
+                    ),
+                );
+            }
+            None => {
+                ui.weak(format!("Source frame rate: {}", source_text));
+            }
+        }
+    }
 
-                    ui.set_enabled(!self.encoding);
-                    combo.show_ui(ui, |ui| {
+    fn show_overlay_controls(&mut self, ui: &mut egui::Ui) {
+        let previous = (self.overlay_style, self.extra_overlays.clone(), self.matte);
+        ui.add_enabled_ui(!self.encoding, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Overlay:");
+                let selected_text = match &self.overlay_choice {
+                    OverlayChoice::ShowDefault => "Show default".to_string(),
+                    OverlayChoice::File(path) => path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    OverlayChoice::None => "No overlay".to_string(),
+                };
+                egui::ComboBox::from_id_source("overlay_choice_combo")
+                    .selected_text(selected_text)
+                    .show_ui(ui, |ui| {
                         ui.selectable_value(
-                            &mut self.resolution,
-                            Resolution::K2,
-                            Resolution::K2.as_str(),
+                            &mut self.overlay_choice,
+                            OverlayChoice::ShowDefault,
+                            "Show default",
                         );
                         ui.selectable_value(
-                            &mut self.resolution,
-                            Resolution::K4,
-                            Resolution::K4.as_str(),
-                        );
-                        ui.selectable_value(
-                            &mut self.resolution,
-                            Resolution::K6,
-                            Resolution::K6.as_str(),
+                            &mut self.overlay_choice,
+                            OverlayChoice::None,
+                            "No overlay",
                         );
                     });
-                });
+                if ui
+                    .button("📂 Pick PNG...")
+                    .on_hover_text("Any PNG with an alpha channel, for this job only")
+                    .clicked()
+                {
+                    if let Some(path) = self.pick_overlay_file() {
+                        self.overlay_choice = OverlayChoice::File(path);
+                    }
+                }
+                if let Some(path) = self.overlay_image(self.resolution) {
+                    ui.weak(path.display().to_string());
+                }
+            });
+        });
 
-                ui.add_space(10.0);
+        ui.add_enabled_ui(
+            !self.encoding && self.overlay_choice != OverlayChoice::None,
+            |ui| {
                 ui.horizontal(|ui| {
-                    ui.label("Output Directory:");
-                    let browse_button = egui::Button::new("📂 Browse...")
-                        .fill(egui::Color32::from_rgb(30, 90, 100));
+                    ui.label("Overlay Blend:");
+                    Self::overlay_style_controls(ui, 0, &mut self.overlay_style);
+                });
+            },
+        );
 
-                    if ui.add_enabled(!self.encoding, browse_button).clicked() {
-                        if let Some(path) = FileDialog::new().pick_folder() {
-                            self.output_dir = Some(path);
-                            self.update_storage_status();
-                        }
+        ui.add_enabled_ui(!self.encoding, |ui| {
+            let mut raise = None;
+            let mut remove = None;
+            for (index, layer) in self.extra_overlays.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Layer {}:", index + 2));
+                    let name = layer
+                        .image
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned())
+                        .unwrap_or_default();
+                    ui.label(name)
+                        .on_hover_text(layer.image.display().to_string());
+                    Self::overlay_style_controls(ui, index + 1, &mut layer.style);
+                    if ui
+                        .add_enabled(index > 0, egui::Button::new("⬆").small())
+                        .on_hover_text("Composite before the layer above")
+                        .clicked()
+                    {
+                        raise = Some(index);
                     }
-                    match &self.output_dir {
-                        Some(path) => ui.label(path.display().to_string()),
-                        None => ui.label("Not selected"),
+                    if ui.small_button("✖").clicked() {
+                        remove = Some(index);
                     }
                 });
-
-                ui.add_space(20.0);
-                ui.separator();
-                ui.add_space(20.0);
-
-                ui.vertical(|ui| {
-                    ui.label(
-                        egui::RichText::new("Current Status:")
-                            .heading()
-                            .color(egui::Color32::LIGHT_BLUE),
-                    );
-                    ui.add_space(5.0);
-
-                    let status_color = if self.encoding {
-                        egui::Color32::LIGHT_GREEN
-                    } else if self.progress >= 100.0 {
-                        egui::Color32::DARK_GREEN
-                    } else if !self.sufficient_storage {
-                        egui::Color32::LIGHT_RED
-                    } else {
-                        egui::Color32::LIGHT_BLUE
-                    };
-
-                    ui.label(egui::RichText::new(&self.current_frame).color(status_color));
-
-                    ui.add_space(10.0);
-
-                    let progress_color = if self.encoding {
-                        egui::Color32::from_rgb(0, 180, 100)
-                    } else if self.progress >= 100.0 {
-                        egui::Color32::DARK_GREEN
-                    } else {
-                        egui::Color32::LIGHT_BLUE
-                    };
-
-                    ui.add(
-                        egui::ProgressBar::new(self.progress / 100.0)
-                            .fill(progress_color)
-                            .show_percentage()
-                            .text(format!("{:.1}%", self.progress)),
-                    );
-                });
-
-                if !self.encoding {
-                    if let Some(err) = &self.storage_error {
-                        ui.add_space(10.0);
-                        ui.colored_label(egui::Color32::LIGHT_RED, err);
-                    }
+            }
+            if let Some(index) = raise {
+                self.extra_overlays.swap(index - 1, index);
+            }
+            if let Some(index) = remove {
+                self.extra_overlays.remove(index);
+            }
+            if ui
+                .button("➕ Add Layer...")
+                .on_hover_text("Another PNG over the overlay, e.g. a client logo or legal text")
+                .clicked()
+            {
+                if let Some(image) = self.pick_overlay_file() {
+                    self.extra_overlays.push(OverlaySpec {
+                        image,
+                        style: OverlayStyle::default(),
+                    });
                 }
+            }
+        });
 
-                ui.add_space(20.0);
-
-                ui.horizontal(|ui| {
-                    if self.encoding {
-                        let pause_button = egui::Button::new("⏸ Pause")
-                            .fill(egui::Color32::from_rgb(200, 150, 50));
-                        if ui.add(pause_button).clicked() {
-                            self.pause_encoding();
-                        }
-
-                        let cancel_button = egui::Button::new("⏹ Cancel")
-                            .fill(egui::Color32::from_rgb(180, 80, 80));
-                        if ui.add(cancel_button).clicked() {
-                            self.dialog_state = DialogState::CancelConfirmation(false);
-                        }
-
-                        let cancel_delete_button = egui::Button::new("⏹ Cancel and Delete")
-                            .fill(egui::Color32::from_rgb(150, 40, 40));
-                        if ui.add(cancel_delete_button).clicked() {
-                            self.dialog_state = DialogState::CancelConfirmation(true);
+        ui.add_enabled_ui(!self.encoding, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Matte:");
+                let selected_text = self.matte.map_or("Off".to_string(), |matte| matte.label());
+                egui::ComboBox::from_id_source("matte_combo")
+                    .selected_text(selected_text)
+                    .show_ui(ui, |ui| {
+                        if ui.selectable_label(self.matte.is_none(), "Off").clicked() {
+                            self.matte = None;
                         }
-                    } else {
-                        let start_enabled = self.sufficient_storage;
-                        let button_color = if start_enabled {
-                            egui::Color32::from_rgb(0, 140, 70)
-                        } else {
-                            egui::Color32::GRAY
-                        };
-
-                        let start_button = egui::Button::new("▶ Start Encoding").fill(button_color);
-                        if ui.add_enabled(start_enabled, start_button).clicked() {
-                            self.start_encoding();
+                        for (aspect, label) in MatteSpec::PRESETS {
+                            let selected = self.matte.is_some_and(|matte| matte.aspect == aspect);
+                            if ui.selectable_label(selected, label).clicked() {
+                                let opacity = self.matte.map_or(1.0, |matte| matte.opacity);
+                                let frames = self.matte.and_then(|matte| matte.frames);
+                                self.matte = Some(MatteSpec {
+                                    aspect,
+                                    opacity,
+                                    frames,
+                                });
+                            }
                         }
-                    }
-
-                    let open_enabled = self.output_dir.is_some();
-                    let button_color = if open_enabled {
-                        egui::Color32::from_rgb(50, 120, 180)
-                    } else {
-                        egui::Color32::GRAY
-                    };
+                    });
 
-                    let open_button = egui::Button::new("📂 Open Output Folder").fill(button_color);
-                    if ui.add_enabled(open_enabled, open_button).clicked() {
-                        if let Some(path) = &self.output_dir {
-                            open_folder(path);
-                        }
+                if let Some(matte) = &mut self.matte {
+                    ui.label("Opacity:");
+                    let mut percent = matte.opacity * 100.0;
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut percent)
+                                .clamp_range(0.0..=100.0)
+                                .suffix("%"),
+                        )
+                        .changed()
+                    {
+                        matte.opacity = percent / 100.0;
                     }
-                });
+                    Self::frame_range_controls(ui, &mut matte.frames);
+                }
+            });
+        });
 
-                if !self.instructions.is_empty() {
-                    ui.add_space(20.0);
-                    ui.separator();
-                    ui.add_space(10.0);
+        if (self.overlay_style, self.extra_overlays.clone(), self.matte) != previous {
+            self.save_show();
+        }
+    }
 
-                    ui.vertical(|ui| {
-                        ui.label(
-                            egui::RichText::new(" ") //instrukce:
-                                .heading()
-                                .color(egui::Color32::LIGHT_YELLOW),
-                        );
-                        ui.add_space(5.0);
-                        ui.label(&self.instructions);
-                    });
+    // Blend, alpha and frame span of overlay layer `id`
+    fn overlay_style_controls(ui: &mut egui::Ui, id: usize, style: &mut OverlayStyle) {
+        egui::ComboBox::from_id_source(("blend_combo", id))
+            .selected_text(style.blend.as_str())
+            .show_ui(ui, |ui| {
+                for mode in BlendMode::ALL {
+                    ui.selectable_value(&mut style.blend, mode, mode.as_str());
+                }
+            });
+        egui::ComboBox::from_id_source(("alpha_combo", id))
+            .selected_text(style.alpha.as_str())
+            .show_ui(ui, |ui| {
+                for mode in [AlphaMode::Straight, AlphaMode::Premultiplied] {
+                    ui.selectable_value(&mut style.alpha, mode, mode.as_str());
                 }
             });
+        Self::frame_range_controls(ui, &mut style.frames);
+    }
 
-        // Check if resolution changed and update base name
-        if previous_resolution != self.resolution {
-            self.update_base_name();
-            self.update_storage_status();
+    // "All frames" or an inclusive first..last span for one burn-in layer
+    fn frame_range_controls(ui: &mut egui::Ui, range: &mut Option<FrameRange>) {
+        let mut limited = range.is_some();
+        if ui
+            .checkbox(&mut limited, "Frames only")
+            .on_hover_text("Burn this layer in only between two delivery frames (counted from 0)")
+            .changed()
+        {
+            *range = limited.then_some(FrameRange { first: 0, last: 47 });
+        }
+        if let Some(range) = range {
+            ui.add(egui::DragValue::new(&mut range.first).clamp_range(0..=range.last));
+            ui.label("to");
+            ui.add(egui::DragValue::new(&mut range.last).clamp_range(range.first..=u32::MAX));
         }
+    }
 
-        if let DialogState::CancelConfirmation(delete_frames) = self.dialog_state {
-            egui::Window::new("Cancel Encoding?")
-                .collapsible(false)
-                .resizable(false)
-                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
-                .show(ctx, |ui| {
+    fn show_source_check(&mut self, ui: &mut egui::Ui) {
+        if let Some(error) = &self.source_error {
+            ui.colored_label(egui::Color32::LIGHT_RED, error);
+        }
+        let Some(check) = &self.source_check else {
+            return;
+        };
+        if check.suggested != self.resolution {
+            let suggested = check.suggested;
+            let mut apply = false;
+            ui.horizontal(|ui| {
+                ui.weak(format!(
+                    "Suggested for a {}x{} source: {}",
+                    check.size.0,
+                    check.size.1,
+                    suggested.as_str()
+                ));
+                apply = ui
+                    .add_enabled(!self.encoding, egui::Button::new("Use"))
+                    .clicked();
+            });
+            if apply {
+                self.resolution = suggested;
+                self.update_base_name();
+                self.update_storage_status();
+            }
+        }
+        if let Some(check) = &self.source_check {
+            for warning in &check.warnings {
+                ui.colored_label(egui::Color32::LIGHT_YELLOW, warning);
+            }
+        }
+    }
+
+    fn show_still_hold_controls(&mut self, ui: &mut egui::Ui) {
+        if !is_still_image(&self.input_video) {
+            return;
+        }
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(!self.encoding, |ui| {
+                ui.label("Hold still for");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut self.still_hold.seconds)
+                            .speed(0.1)
+                            .clamp_range(0.1..=3600.0)
+                            .suffix(" s"),
+                    )
+                    .changed();
+                ui.label("at");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut self.still_hold.fps)
+                            .speed(0.01)
+                            .clamp_range(1.0..=120.0)
+                            .max_decimals(3)
+                            .suffix(" fps"),
+                    )
+                    .changed();
+            });
+        });
+        if changed {
+            self.update_storage_status();
+        }
+    }
+
+    fn show_pattern_controls(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        egui::CollapsingHeader::new("Bars & Tone")
+            .id_source("bars_and_tone")
+            .show(ui, |ui| {
+                ui.add_enabled_ui(!self.encoding, |ui| {
+                    changed |= ui
+                        .checkbox(&mut self.generate_pattern, "Encode a leader instead of the input")
+                        .on_hover_text("Uses the selected resolution, format and naming; the rate follows the show or the loaded source")
+                        .changed();
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_source("pattern_combo")
+                            .selected_text(self.test_pattern.kind.as_str())
+                            .show_ui(ui, |ui| {
+                                for kind in PatternKind::ALL {
+                                    changed |= ui
+                                        .selectable_value(
+                                            &mut self.test_pattern.kind,
+                                            kind,
+                                            kind.as_str(),
+                                        )
+                                        .changed();
+                                }
+                            });
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut self.test_pattern.seconds)
+                                    .speed(0.5)
+                                    .clamp_range(1.0..=600.0)
+                                    .suffix(" s"),
+                            )
+                            .changed();
+                        ui.checkbox(&mut self.test_pattern.tone, "1 kHz tone")
+                            .on_hover_text("-20 dBFS, in formats that carry audio");
+                    });
+                });
+            });
+        if changed {
+            self.update_base_name();
+            self.update_storage_status();
+        }
+    }
+
+    fn show_naming_controls(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        ui.add_enabled_ui(!self.encoding, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Naming:");
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut self.naming.prefix)
+                            .hint_text("prefix")
+                            .desired_width(80.0),
+                    )
+                    .changed();
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut self.naming.suffix)
+                            .hint_text("suffix")
+                            .desired_width(80.0),
+                    )
+                    .changed();
+                changed |= ui
+                    .checkbox(&mut self.naming.uppercase_extension, "Uppercase extension")
+                    .changed();
+            });
+        });
+
+        let example = if self.output_format.is_sequence() {
+            format!("{}-000000.{}", self.base_name, self.output_extension())
+        } else {
+            format!("{}.{}", self.base_name, self.output_extension())
+        };
+        ui.weak(format!("Files: {}", example));
+        if let Some(warning) = self.naming.validate() {
+            ui.colored_label(egui::Color32::LIGHT_YELLOW, warning);
+        }
+
+        if changed {
+            self.update_base_name();
+            self.update_storage_status();
+            self.save_show();
+        }
+    }
+
+    fn show_subtitle_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Subtitles:");
+            let browse_button =
+                egui::Button::new("📂 Browse...").fill(egui::Color32::from_rgb(30, 90, 100));
+
+            if ui.add_enabled(!self.encoding, browse_button).clicked() {
+                if let Some(path) = FileDialog::new()
+                    .add_filter("Subtitles", &["srt", "ass", "ssa"])
+                    .pick_file()
+                {
+                    self.subtitles = Some(SubtitleSpec {
+                        path,
+                        ..Default::default()
+                    });
+                }
+            }
+
+            match &self.subtitles {
+                Some(spec) => {
+                    ui.label(
+                        spec.path
+                            .file_name()
+                            .map(|name| name.to_string_lossy().into_owned())
+                            .unwrap_or_default(),
+                    );
+                    if ui
+                        .add_enabled(!self.encoding, egui::Button::new("✖"))
+                        .clicked()
+                    {
+                        self.subtitles = None;
+                    }
+                }
+                None => {
+                    ui.label("None");
+                }
+            }
+        });
+
+        if let Some(spec) = &mut self.subtitles {
+            ui.add_enabled_ui(!self.encoding, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Font:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut spec.font)
+                            .hint_text("from file")
+                            .desired_width(120.0),
+                    );
+                    ui.label("Size:");
+                    ui.add(egui::DragValue::new(&mut spec.font_size).clamp_range(0..=200));
+                    ui.label("Bottom Margin:");
+                    ui.add(egui::DragValue::new(&mut spec.margin).clamp_range(0..=2000));
+                });
+            });
+        }
+    }
+
+    fn show_audio_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Audio Stems:");
+            let Some(layout) = &self.audio_layout else {
+                ui.weak("No audio stream");
+                return;
+            };
+
+            let selected_text = self.audio_stems.map_or("Off", |mode| mode.as_str());
+            ui.add_enabled_ui(!self.encoding, |ui| {
+                egui::ComboBox::from_id_source("stems_combo")
+                    .selected_text(selected_text)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.audio_stems, None, "Off");
+                        for mode in [StemMode::PerChannel, StemMode::StereoPairs] {
+                            ui.selectable_value(&mut self.audio_stems, Some(mode), mode.as_str());
+                        }
+                    });
+            });
+            ui.weak(layout.describe());
+        });
+    }
+
+    // Why this ffmpeg build can't write `format`; None when it can or wasn't probed
+    fn missing_support(&self, format: OutputFormat) -> Option<String> {
+        self.ffmpeg_capabilities
+            .as_ref()
+            .and_then(|capabilities| capabilities.missing_for(format))
+    }
+
+    // CRF or a fixed bitrate; true when the choice changed
+    // `codec` is the format whose default CRF a switch back from bitrate starts at
+    fn show_rate_control(ui: &mut egui::Ui, rate: &mut RateControl, codec: OutputFormat) -> bool {
+        let mut changed = false;
+        let is_crf = matches!(rate, RateControl::Crf(_));
+        if ui.radio(is_crf, "CRF").clicked() && !is_crf {
+            *rate = RateControl::default_for(codec);
+            changed = true;
+        }
+        if ui.radio(!is_crf, "Bitrate").clicked() && is_crf {
+            *rate = RateControl::BitrateMbps(rate.estimated_mbps());
+            changed = true;
+        }
+        match rate {
+            RateControl::Crf(crf) => {
+                changed |= ui
+                    .add(egui::Slider::new(crf, 0..=51))
+                    .on_hover_text(
+                        "Lower is better quality and bigger files; 18 looks like the source",
+                    )
+                    .changed();
+            }
+            RateControl::BitrateMbps(mbps) => {
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(mbps)
+                            .clamp_range(1..=400)
+                            .suffix(" Mb/s"),
+                    )
+                    .changed();
+            }
+        }
+        changed
+    }
+
+    // Choices within the selected output format, if it has any
+    fn show_format_options(&mut self, ui: &mut egui::Ui) {
+        let has_options = self.output_format.is_sequence()
+            || matches!(
+                self.output_format,
+                OutputFormat::MovDnxhr
+                    | OutputFormat::MxfDnxhr
+                    | OutputFormat::Mp4H264
+                    | OutputFormat::Mp4Hevc
+                    | OutputFormat::Mp4Av1Svt
+                    | OutputFormat::Mp4Av1Aom
+                    | OutputFormat::WebmVp9
+            );
+        let mut changed = false;
+        if has_options {
+            ui.horizontal(|ui| {
+                ui.add_enabled_ui(!self.encoding, |ui| match self.output_format {
+                    OutputFormat::DpxSequence => {
+                        ui.label("DPX:");
+                        ui.checkbox(&mut self.format_options.dpx_log, "10-bit log (Cineon)")
+                            .on_hover_text(
+                                "Log code values for DI grading instead of video levels",
+                            );
+                    }
+                    OutputFormat::JpegSequence => {
+                        ui.label("JPEG quality:");
+                        changed |= ui
+                            .add(
+                                egui::Slider::new(&mut self.format_options.jpeg_quality, 2..=31)
+                                    .text("q:v"),
+                            )
+                            .on_hover_text("2 is best; higher numbers give smaller files")
+                            .changed();
+                    }
+                    OutputFormat::ExrSequence => {
+                        ui.label("EXR:");
+                        changed |= ui
+                            .radio_value(
+                                &mut self.format_options.exr_half_float,
+                                true,
+                                "Half float",
+                            )
+                            .changed();
+                        changed |= ui
+                            .radio_value(
+                                &mut self.format_options.exr_half_float,
+                                false,
+                                "Full float",
+                            )
+                            .changed();
+                        ui.label("Compression:");
+                        egui::ComboBox::from_id_source("exr_compression_combo")
+                            .selected_text(self.format_options.exr_compression.as_str())
+                            .show_ui(ui, |ui| {
+                                for compression in ExrCompression::ALL {
+                                    changed |= ui
+                                        .selectable_value(
+                                            &mut self.format_options.exr_compression,
+                                            compression,
+                                            compression.as_str(),
+                                        )
+                                        .changed();
+                                }
+                            });
+                    }
+                    OutputFormat::MovDnxhr | OutputFormat::MxfDnxhr => {
+                        ui.label("DNxHR profile:");
+                        egui::ComboBox::from_id_source("dnxhr_profile_combo")
+                            .selected_text(self.format_options.dnxhr_profile.as_str())
+                            .show_ui(ui, |ui| {
+                                for profile in DnxhrProfile::ALL {
+                                    changed |= ui
+                                        .selectable_value(
+                                            &mut self.format_options.dnxhr_profile,
+                                            profile,
+                                            profile.as_str(),
+                                        )
+                                        .changed();
+                                }
+                            });
+                    }
+                    OutputFormat::Mp4H264 => {
+                        ui.label("H.264:");
+                        changed |= Self::show_rate_control(
+                            ui,
+                            &mut self.format_options.h264_rate,
+                            OutputFormat::Mp4H264,
+                        );
+                    }
+                    OutputFormat::Mp4Hevc => {
+                        ui.label("HEVC:");
+                        egui::ComboBox::from_id_source("hevc_profile_combo")
+                            .selected_text(self.format_options.hevc_profile.as_str())
+                            .show_ui(ui, |ui| {
+                                for profile in HevcProfile::ALL {
+                                    ui.selectable_value(
+                                        &mut self.format_options.hevc_profile,
+                                        profile,
+                                        profile.as_str(),
+                                    );
+                                }
+                            });
+                        changed |= Self::show_rate_control(
+                            ui,
+                            &mut self.format_options.hevc_rate,
+                            OutputFormat::Mp4Hevc,
+                        );
+                    }
+                    OutputFormat::Mp4Av1Svt | OutputFormat::Mp4Av1Aom => {
+                        ui.label("AV1 speed:");
+                        egui::ComboBox::from_id_source("av1_speed_combo")
+                            .selected_text(self.format_options.av1_speed.as_str())
+                            .show_ui(ui, |ui| {
+                                for speed in Av1Speed::ALL {
+                                    ui.selectable_value(
+                                        &mut self.format_options.av1_speed,
+                                        speed,
+                                        speed.as_str(),
+                                    );
+                                }
+                            })
+                            .response
+                            .on_hover_text("Slower presets give smaller files at the same quality");
+                        ui.add(
+                            egui::Slider::new(&mut self.format_options.av1_crf, 0..=63).text("CRF"),
+                        )
+                        .on_hover_text(
+                            "Lower is better quality and bigger files; 30 suits streaming",
+                        );
+                    }
+                    OutputFormat::WebmVp9 => {
+                        ui.label("VP9:");
+                        changed |= ui
+                            .add(
+                                egui::Slider::new(&mut self.format_options.vp9_crf, 0..=63)
+                                    .text("CRF"),
+                            )
+                            .on_hover_text("Lower is better quality and bigger files")
+                            .changed();
+                    }
+                    _ => {}
+                });
+            });
+        }
+        if self.output_format.is_sequence() {
+            let missing = self.missing_support(OutputFormat::Mp4H264);
+            ui.horizontal(|ui| {
+                ui.add_enabled_ui(!self.encoding && missing.is_none(), |ui| {
+                    changed |= ui
+                        .checkbox(
+                            &mut self.format_options.review_mp4,
+                            "Also write an H.264 review MP4",
+                        )
+                        .on_hover_text("Encoded from the finished frames as <name>_review.mp4")
+                        .on_disabled_hover_text(missing.as_deref().unwrap_or_default())
+                        .changed();
+                    if self.format_options.review_mp4 {
+                        changed |= Self::show_rate_control(
+                            ui,
+                            &mut self.format_options.h264_rate,
+                            OutputFormat::Mp4H264,
+                        );
+                    }
+                });
+            });
+            ui.horizontal(|ui| {
+                ui.add_enabled_ui(!self.encoding, |ui| {
+                    ui.label("Keep every");
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut self.every_nth).clamp_range(1..=1000))
+                        .on_hover_text("1 writes every frame; 10 writes frames 0, 10, 20... under their own numbers, for reference pulls and plates")
+                        .changed();
+                    ui.label(if self.every_nth == 1 { "frame" } else { "frames" });
+                });
+            });
+            ui.horizontal(|ui| {
+                ui.add_enabled_ui(!self.encoding, |ui| {
+                    changed |= ui
+                        .checkbox(&mut self.qc_sample_enabled, "Copy a QC sample of")
+                        .on_hover_text("First, middle and last frame plus this many random ones go to <name>_qc")
+                        .changed();
+                    changed |= ui
+                        .add_enabled(
+                            self.qc_sample_enabled,
+                            egui::DragValue::new(&mut self.qc_sample).clamp_range(0..=500),
+                        )
+                        .changed();
+                    ui.label("random frames");
+                });
+            });
+        }
+        if self.output_format != OutputFormat::WebmVp9 {
+            let missing = self.missing_support(OutputFormat::WebmVp9);
+            ui.horizontal(|ui| {
+                ui.add_enabled_ui(!self.encoding && missing.is_none(), |ui| {
+                    changed |= ui
+                        .checkbox(
+                            &mut self.format_options.review_webm,
+                            "Also write a VP9 WebM for the web",
+                        )
+                        .on_hover_text("Encoded from the finished delivery as <name>_review.webm")
+                        .on_disabled_hover_text(missing.as_deref().unwrap_or_default())
+                        .changed();
+                    if self.format_options.review_webm {
+                        changed |= ui
+                            .add(
+                                egui::Slider::new(&mut self.format_options.vp9_crf, 0..=63)
+                                    .text("CRF"),
+                            )
+                            .changed();
+                    }
+                });
+            });
+        }
+        if changed {
+            self.update_storage_status();
+        }
+    }
+
+    fn show_queue_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new(format!("Job Queue ({})", self.queue.jobs.len()))
+            .id_source("job_queue")
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let add_button = egui::Button::new("➕ Add Current Setup")
+                        .fill(egui::Color32::from_rgb(30, 90, 100));
+                    if ui
+                        .add_enabled(self.output_dir.is_some(), add_button)
+                        .on_hover_text("Queue this input with the selected resolution, format and output directory")
+                        .clicked()
+                    {
+                        if let Some(output_dir) = self.output_dir.clone() {
+                            self.queue.add(
+                                self.input_video.clone(),
+                                self.resolution,
+                                output_dir,
+                                self.output_format,
+                                self.job_notes.trim().to_string(),
+                            );
+                        }
+                    }
+
+                    if self.queue.running {
+                        let label = if self.active_job.is_some() {
+                            "⏹ Stop After Current Job"
+                        } else {
+                            "⏹ Stop Queue"
+                        };
+                        if ui.button(label).on_hover_text("The running job finishes first").clicked() {
+                            self.queue.running = false;
+                            self.window_override = false;
+                        }
+                        let wait = self.window_wait();
+                        if wait > 0 {
+                            ui.weak(format!("Encode window opens in {}", format_wait(wait)));
+                            if ui
+                                .button("⚡ Run Now Anyway")
+                                .on_hover_text("Starts outside the encode windows until the queue stops")
+                                .clicked()
+                            {
+                                crash::log("Encode window overridden by the operator".to_string());
+                                self.window_override = true;
+                            }
+                        }
+                    } else if self.active_job.is_some() {
+                        ui.weak("Queue stops after the current job");
+                        if ui.button("▶ Keep Going").clicked() {
+                            self.queue.running = true;
+                        }
+                    } else {
+                        let run_enabled = self.queue.next_runnable().is_some();
+                        let run_button = egui::Button::new("▶ Run Queue")
+                            .fill(egui::Color32::from_rgb(0, 140, 70));
+                        if ui.add_enabled(run_enabled, run_button).clicked() {
+                            self.queue.running = true;
+                        }
+                    }
+
+                    if ui.button("Clear Done").clicked() {
+                        self.queue.clear_finished();
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Show:");
+                    egui::ComboBox::from_id_source("queue_state_filter")
+                        .selected_text(self.queue_state_filter.unwrap_or("All"))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.queue_state_filter, None, "All");
+                            for label in JobState::LABELS {
+                                ui.selectable_value(&mut self.queue_state_filter, Some(label), label);
+                            }
+                        });
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.queue_search)
+                            .hint_text("Shot name or YYYY-MM-DD"),
+                    );
+                });
+
+                let mut action = None;
+                let all_jobs: Vec<(u64, String)> =
+                    self.queue.jobs.iter().map(|job| (job.id, job.name())).collect();
+                egui::Grid::new("queue_grid").striped(true).show(ui, |ui| {
+                    let shown = self.queue.jobs.iter().filter(|job| {
+                        self.queue_state_filter
+                            .is_none_or(|label| job.state.label() == label)
+                            && job.matches(&self.queue_search)
+                    });
+                    for job in shown {
+                        let movable = !job.state.is_active();
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(movable, egui::Button::new("⬆")).clicked() {
+                                action = Some((job.id, QueueAction::Up));
+                            }
+                            if ui.add_enabled(movable, egui::Button::new("⬇")).clicked() {
+                                action = Some((job.id, QueueAction::Down));
+                            }
+                            if ui.add_enabled(movable, egui::Button::new("✖")).clicked() {
+                                action = Some((job.id, QueueAction::Remove));
+                            }
+                            let pending = job.state == JobState::Pending;
+                            ui.add_enabled_ui(pending, |ui| {
+                                ui.menu_button("⛓", |ui| {
+                                    ui.label("Start after:");
+                                    for (other, other_name) in &all_jobs {
+                                        if *other == job.id {
+                                            continue;
+                                        }
+                                        let mut wanted = job.depends_on.contains(other);
+                                        let label = format!("#{} {}", other, other_name);
+                                        if ui.checkbox(&mut wanted, label).changed() {
+                                            action = Some((
+                                                job.id,
+                                                QueueAction::Depend { on: *other, wanted },
+                                            ));
+                                        }
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Jobs that have to be done before this one starts");
+                            });
+                        });
+                        ui.weak(format!("#{}", job.id));
+                        let name = ui.label(job.name());
+                        if !job.notes.is_empty() {
+                            name.on_hover_text(&job.notes);
+                        }
+                        ui.label(job.resolution.as_file_tag());
+                        ui.label(job.output_format.as_str());
+                        ui.label(job.output_dir.display().to_string());
+                        ui.weak(job.added_date());
+                        let color = match job.state {
+                            JobState::Done => egui::Color32::LIGHT_GREEN,
+                            JobState::Failed(_) => egui::Color32::LIGHT_RED,
+                            JobState::Running => egui::Color32::LIGHT_BLUE,
+                            JobState::Paused => egui::Color32::LIGHT_YELLOW,
+                            JobState::Stalled => egui::Color32::from_rgb(255, 160, 60),
+                            JobState::Verifying => egui::Color32::from_rgb(190, 150, 255),
+                            JobState::Uploading => egui::Color32::from_rgb(100, 210, 230),
+                            JobState::Pending => egui::Color32::GRAY,
+                        };
+                        let waiting: Vec<String> = self
+                            .queue
+                            .waiting_on(job)
+                            .iter()
+                            .map(|id| format!("#{}", id))
+                            .collect();
+                        let state = if job.state == JobState::Pending && !waiting.is_empty() {
+                            format!("Pending, after {}", waiting.join(", "))
+                        } else {
+                            job.state.describe()
+                        };
+                        ui.horizontal(|ui| {
+                            ui.colored_label(color, state);
+                            if matches!(job.state, JobState::Failed(_))
+                                && ui.small_button("⟳ Retry").clicked()
+                            {
+                                action = Some((job.id, QueueAction::Retry));
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+                match action {
+                    Some((id, QueueAction::Up)) => self.queue.shift(id, true),
+                    Some((id, QueueAction::Down)) => self.queue.shift(id, false),
+                    Some((id, QueueAction::Remove)) => self.queue.remove(id),
+                    Some((id, QueueAction::Retry)) => {
+                        if let Some(job) = self.queue.get_mut(id) {
+                            job.state = JobState::Pending;
+                        }
+                    }
+                    Some((id, QueueAction::Depend { on, wanted }))
+                        if !self.queue.set_dependency(id, on, wanted) =>
+                    {
+                        self.status = format!("Job #{} already waits on #{}", on, id);
+                    }
+                    Some((_, QueueAction::Depend { .. })) | None => {}
+                }
+            });
+    }
+
+    // Offered while the current job's sequence is spread over several volumes
+    fn show_split_controls(&mut self, ui: &mut egui::Ui) {
+        let Some(manifest) = self
+            .output_dir
+            .as_ref()
+            .and_then(|dir| SplitManifest::read(dir, &self.base_name))
+        else {
+            return;
+        };
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "Sequence is split over {} volumes",
+                manifest.parts.len()
+            ));
+            let moving = self
+                .consolidate_status
+                .as_ref()
+                .is_some_and(|status| !status.is_finished());
+            if ui
+                .add_enabled(!moving, egui::Button::new("⇲ Consolidate"))
+                .on_hover_text(
+                    "Moves the later volumes' frames into the output folder once it has room",
+                )
+                .clicked()
+            {
+                self.start_consolidate();
+            }
+            if let Some(status) = &self.consolidate_status {
+                ui.weak(status.describe());
+            }
+        });
+    }
+
+    fn show_share_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Quick Share").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("From (s):");
+                ui.add(
+                    egui::DragValue::new(&mut self.share_start)
+                        .speed(0.1)
+                        .clamp_range(0.0..=f64::MAX),
+                );
+                ui.label("Length (s):");
+                ui.add(
+                    egui::DragValue::new(&mut self.share_length)
+                        .speed(0.1)
+                        .clamp_range(0.5..=30.0),
+                );
+
+                egui::ComboBox::from_id_source("share_format_combo")
+                    .selected_text(self.share_format.as_str())
+                    .show_ui(ui, |ui| {
+                        for format in [ShareFormat::Gif, ShareFormat::WebP, ShareFormat::Mp4] {
+                            ui.selectable_value(&mut self.share_format, format, format.as_str());
+                        }
+                    });
+            });
+
+            ui.horizontal(|ui| {
+                let exporting = self.share_status == Some(ShareStatus::Exporting);
+                let export_enabled = !exporting && self.output_dir.is_some();
+                if ui
+                    .add_enabled(export_enabled, egui::Button::new("🎬 Export Preview"))
+                    .clicked()
+                {
+                    self.start_share();
+                }
+
+                ui.checkbox(&mut self.share_after_encode, "After each encode")
+                    .on_hover_text(
+                        "Exports this preview when a job finishes, for a quick overlay check",
+                    );
+
+                if let Some(status) = &self.share_status {
+                    ui.weak(status.describe());
+                    if let ShareStatus::Done(path) = status {
+                        if ui.small_button("Open").clicked() {
+                            open_folder(path);
+                        }
+                    }
+                }
+            });
+        });
+    }
+
+    fn show_still_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Still Pulls").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Frames:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.still_frames)
+                        .hint_text("12, 240, 1000-1004 or 📌 in the player")
+                        .desired_width(280.0),
+                );
+            });
+
+            ui.horizontal(|ui| {
+                let exporting = self
+                    .still_status
+                    .as_ref()
+                    .is_some_and(|status| !status.is_finished());
+                let export_enabled = !exporting && self.output_dir.is_some();
+                if ui
+                    .add_enabled(export_enabled, egui::Button::new("🖼 Export Stills"))
+                    .clicked()
+                {
+                    self.start_stills();
+                }
+
+                if let Some(status) = &self.still_status {
+                    ui.weak(status.describe());
+                    if let StillStatus::Done(path) = status {
+                        if ui.small_button("Open").clicked() {
+                            open_folder(path);
+                        }
+                    }
+                }
+            });
+        });
+    }
+
+    fn show_rerender_controls(&mut self, ui: &mut egui::Ui) {
+        if self.rerender_frames.is_empty() && self.rerender_status.is_none() {
+            return;
+        }
+        egui::CollapsingHeader::new("Suspect Frames")
+            .default_open(true)
+            .show(ui, |ui| {
+                let frames: Vec<String> = self
+                    .rerender_frames
+                    .iter()
+                    .map(|frame| format!("{:06}", frame))
+                    .collect();
+                ui.label(if frames.is_empty() {
+                    "None flagged".to_string()
+                } else {
+                    frames.join(", ")
+                });
+
+                ui.horizontal(|ui| {
+                    let rendering = self
+                        .rerender_status
+                        .as_ref()
+                        .is_some_and(|status| !status.is_finished());
+                    let enabled = !rendering
+                        && !self.encoding
+                        && !self.rerender_frames.is_empty()
+                        && self.output_dir.is_some();
+                    if ui
+                        .add_enabled(enabled, egui::Button::new("🔁 Re-render"))
+                        .on_hover_text("Encode just these frames again, over the delivered ones")
+                        .clicked()
+                    {
+                        self.start_rerender();
+                    }
+                    if ui
+                        .add_enabled(!rendering, egui::Button::new("Clear"))
+                        .clicked()
+                    {
+                        self.rerender_frames.clear();
+                        self.rerender_status = None;
+                    }
+
+                    if let Some(status) = &self.rerender_status {
+                        ui.weak(status.describe());
+                    }
+                });
+            });
+    }
+
+    fn purge_files(&self) -> Vec<PathBuf> {
+        match &self.purge_status {
+            Some(PurgeStatus::Listed(versions)) => versions
+                .iter()
+                .filter(|version| version.purge)
+                .flat_map(|version| version.files.iter().cloned())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn show_purge_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Version Purge").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                let mut changed = false;
+                ui.label("Keep newest:");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut self.settings.keep_versions).clamp_range(1..=50))
+                    .changed();
+                ui.label("Purge older than (days):");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut self.settings.purge_after_days)
+                            .clamp_range(0..=3650),
+                    )
+                    .on_hover_text("0 keeps versions regardless of age")
+                    .changed();
+                if changed {
+                    if let Err(e) = self.settings.save() {
+                        self.status = format!("Could not save settings: {}", e);
+                    }
+                }
+            });
+
+            ui.horizontal(|ui| {
+                let busy = matches!(
+                    self.purge_status,
+                    Some(PurgeStatus::Scanning | PurgeStatus::Purging)
+                );
+                let scan_enabled = !busy && self.output_dir.is_some();
+                if ui
+                    .add_enabled(scan_enabled, egui::Button::new("🔍 Scan Output Directory"))
+                    .clicked()
+                {
+                    if let Some(output_dir) = self.output_dir.clone() {
+                        self.purge_status = Some(PurgeStatus::Scanning);
+                        self.purge_receiver =
+                            spawn_scan(output_dir, self.settings.retention_policy());
+                    }
+                }
+                if let Some(status) = &self.purge_status {
+                    ui.weak(status.describe());
+                }
+            });
+
+            let Some(PurgeStatus::Listed(versions)) = &self.purge_status else {
+                return;
+            };
+            if versions.is_empty() {
+                return;
+            }
+
+            egui::Grid::new("purge_grid").striped(true).show(ui, |ui| {
+                for version in versions {
+                    ui.label(&version.delivery);
+                    ui.label(format!("v{:03}", version.version));
+                    ui.label(
+                        TrashSummary {
+                            files: version.files.len(),
+                            bytes: version.bytes,
+                        }
+                        .describe(),
+                    );
+                    ui.label(format!("{} days old", version.age_days()));
+                    if version.purge {
+                        ui.colored_label(egui::Color32::LIGHT_RED, "Purge");
+                    } else {
+                        ui.weak("Keep");
+                    }
+                    ui.end_row();
+                }
+            });
+
+            // Sizes come from the scan; re-reading them every frame would hammer the SAN
+            let summary = versions.iter().filter(|version| version.purge).fold(
+                TrashSummary { files: 0, bytes: 0 },
+                |total, version| TrashSummary {
+                    files: total.files + version.files.len(),
+                    bytes: total.bytes + version.bytes,
+                },
+            );
+            let purge_button = egui::Button::new(format!("🗑 Purge {}", summary.describe()))
+                .fill(egui::Color32::from_rgb(150, 40, 40));
+            if summary.files > 0 && ui.add_enabled(!self.encoding, purge_button).clicked() {
+                self.dialog_state = DialogState::PurgeConfirmation(summary);
+            }
+        });
+    }
+
+    // Picks the sequence up again from its first renamed frame
+    fn rescan_renumber(&mut self) {
+        let first = self
+            .renumber_plan
+            .as_ref()
+            .and_then(|plan| plan.renames.first())
+            .map(|(_, target)| target.clone());
+        if let Some(first) = first {
+            self.load_renumber_sequence(&first);
+        }
+    }
+
+    fn load_renumber_sequence(&mut self, frame: &std::path::Path) {
+        match scan_sequence(frame) {
+            Ok(scan) => {
+                let template = SequenceTemplate::from_scan(&scan);
+                self.renumber_plan = Some(plan_renames(&scan, &template));
+                self.renumber_template = Some(template);
+                self.renumber_scan = Some(scan);
+            }
+            Err(e) => {
+                self.renumber_scan = None;
+                self.renumber_template = None;
+                self.renumber_plan = None;
+                self.renumber_status = Some(RenumberStatus::Failed(e.to_string()));
+            }
+        }
+    }
+
+    fn show_renumber_controls(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("Sequence Renumber").show(ui, |ui| {
+            let busy = self.renumber_status == Some(RenumberStatus::Renaming);
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(!busy, egui::Button::new("📂 Pick a Frame..."))
+                    .clicked()
+                {
+                    let mut dialog = FileDialog::new();
+                    if let Some(output_dir) = &self.output_dir {
+                        dialog = dialog.set_directory(output_dir);
+                    }
+                    if let Some(frame) = dialog.pick_file() {
+                        self.renumber_status = None;
+                        self.load_renumber_sequence(&frame);
+                    }
+                }
+                if let Some(scan) = &self.renumber_scan {
+                    ui.label(format!(
+                        "{}{}{}.{} ({} frames)",
+                        scan.base,
+                        scan.delimiter,
+                        "#".repeat(scan.padding),
+                        scan.extension,
+                        scan.frames.len()
+                    ));
+                }
+                if let Some(status) = &self.renumber_status {
+                    ui.weak(status.describe());
+                }
+            });
+
+            let (Some(scan), Some(template)) = (&self.renumber_scan, &mut self.renumber_template)
+            else {
+                return;
+            };
+
+            let mut changed = false;
+            ui.add_enabled_ui(!busy, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    changed |= ui
+                        .add(egui::TextEdit::singleline(&mut template.base).desired_width(180.0))
+                        .changed();
+                    ui.label("Delimiter:");
+                    egui::ComboBox::from_id_source("renumber_delimiter")
+                        .selected_text(match template.delimiter.as_str() {
+                            "" => "None",
+                            delimiter => delimiter,
+                        })
+                        .show_ui(ui, |ui| {
+                            for (delimiter, label) in
+                                [("-", "-"), ("_", "_"), (".", "."), ("", "None")]
+                            {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut template.delimiter,
+                                        delimiter.to_string(),
+                                        label,
+                                    )
+                                    .changed();
+                            }
+                        });
+                    ui.label("Padding:");
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut template.padding).clamp_range(1..=10))
+                        .changed();
+                    ui.label("Start at:");
+                    changed |= ui.add(egui::DragValue::new(&mut template.start)).changed();
+                });
+            });
+            if changed {
+                self.renumber_plan = Some(plan_renames(scan, template));
+            }
+
+            let Some(plan) = &self.renumber_plan else {
+                return;
+            };
+            if plan.renames.is_empty() {
+                ui.weak("Names already match the template");
+                return;
+            }
+
+            // Dry run: every rename is listed before anything on disk changes. Only the
+            // visible rows are laid out, as sequences run to hundreds of thousands of frames.
+            let row_height = ui.text_style_height(&egui::TextStyle::Body);
+            let name = |path: &PathBuf| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            };
+            egui::ScrollArea::vertical()
+                .id_source("renumber_preview")
+                .max_height(160.0)
+                .show_rows(ui, row_height, plan.renames.len(), |ui, rows| {
+                    for (source, target) in &plan.renames[rows] {
+                        ui.horizontal(|ui| {
+                            ui.label(name(source));
+                            ui.label("→");
+                            if plan.conflicts.contains(target) {
+                                ui.colored_label(egui::Color32::LIGHT_RED, name(target));
+                            } else {
+                                ui.label(name(target));
+                            }
+                        });
+                    }
+                });
+
+            if !plan.conflicts.is_empty() {
+                ui.colored_label(
+                    egui::Color32::LIGHT_RED,
+                    format!(
+                        "{} target names are taken by files outside this sequence",
+                        plan.conflicts.len()
+                    ),
+                );
+            }
+            let rename_button =
+                egui::Button::new(format!("✏ Rename {} Frames", plan.renames.len()));
+            if ui
+                .add_enabled(!busy && plan.conflicts.is_empty(), rename_button)
+                .clicked()
+            {
+                self.renumber_status = Some(RenumberStatus::Renaming);
+                self.renumber_receiver = spawn_renumber(plan.clone());
+            }
+        });
+    }
+
+    fn show_update_banner(&mut self, ui: &mut egui::Ui) {
+        let Some(status) = self.update_status.clone() else {
+            return;
+        };
+
+        match status {
+            UpdateStatus::Available(manifest) => {
+                ui.horizontal(|ui| {
+                    ui.colored_label(
+                        egui::Color32::LIGHT_YELLOW,
+                        format!("Update {} available", manifest.version),
+                    );
+                    let download_button =
+                        egui::Button::new("⬇ Download").fill(egui::Color32::from_rgb(50, 120, 180));
+                    if ui.add(download_button).clicked() {
+                        self.update_receiver = spawn_download(manifest.clone());
+                    }
+                });
+                if !manifest.notes.is_empty() {
+                    ui.weak(&manifest.notes);
+                }
+            }
+            UpdateStatus::Downloading(manifest) => {
+                ui.colored_label(
+                    egui::Color32::LIGHT_BLUE,
+                    format!("Downloading update {}...", manifest.version),
+                );
+            }
+            UpdateStatus::Staged(path) => {
+                ui.horizontal(|ui| {
+                    ui.colored_label(egui::Color32::LIGHT_GREEN, "Update downloaded");
+                    ui.checkbox(&mut self.install_update_on_exit, "Install on exit");
+                    if ui.button("📂 Show").clicked() {
+                        if let Some(dir) = path.parent() {
+                            open_folder(dir);
+                        }
+                    }
+                });
+            }
+            UpdateStatus::UpToDate | UpdateStatus::Failed(_) => return,
+        }
+
+        ui.add_space(10.0);
+    }
+}
+
+impl eframe::App for DeliveryEncoderApp {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Hands the staged package to the OS so its installer runs after we quit
+        if let Some(UpdateStatus::Staged(path)) = &self.update_status {
+            if self.install_update_on_exit {
+                open_folder(path);
+            }
+        }
+    }
+
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let mut style = (*ctx.style()).clone();
+
+        style.text_styles.insert(
+            egui::TextStyle::Body,
+            egui::FontId::new(16.0, egui::FontFamily::Proportional),
+        );
+        style.text_styles.insert(
+            egui::TextStyle::Button,
+            egui::FontId::new(16.0, egui::FontFamily::Proportional),
+        );
+        style.text_styles.insert(
+            egui::TextStyle::Heading,
+            egui::FontId::new(20.0, egui::FontFamily::Proportional),
+        );
+
+        style.visuals = egui::Visuals::dark();
+        style.visuals.widgets.noninteractive.bg_fill = egui::Color32::from_gray(25);
+        style.visuals.widgets.inactive.bg_fill = egui::Color32::from_gray(35);
+        style.visuals.widgets.hovered.bg_fill = egui::Color32::from_gray(45);
+        style.visuals.widgets.active.bg_fill = egui::Color32::from_gray(25);
+        style.visuals.window_fill = egui::Color32::from_gray(20);
+        style.visuals.panel_fill = egui::Color32::from_gray(25);
+        style.visuals.faint_bg_color = egui::Color32::from_gray(35);
+
+        style.visuals.widgets.noninteractive.fg_stroke =
+            egui::Stroke::new(1.0, egui::Color32::from_gray(230));
+        style.visuals.widgets.inactive.fg_stroke =
+            egui::Stroke::new(1.0, egui::Color32::from_gray(240));
+        style.visuals.widgets.hovered.fg_stroke =
+            egui::Stroke::new(1.0, egui::Color32::from_gray(245));
+        style.visuals.widgets.active.fg_stroke =
+            egui::Stroke::new(1.0, egui::Color32::from_gray(250));
+
+        ctx.set_style(style);
+
+        self.handle_dropped_files(ctx);
+
+        while let Ok((progress, frame, message)) = self.progress_receiver.try_recv() {
+            let file_name = format!(
+                "{}-{:06}.{}",
+                self.base_name,
+                frame,
+                self.output_extension()
+            );
+            let full_message = format!("File: {} | {}", file_name, message);
+
+            // Stalled jobs keep running, so only the status line changes
+            if progress == -3.0 {
+                if self.stalled_since.is_none() {
+                    crash::log(full_message.clone());
+                    self.stalled_since = Some(Instant::now());
+                    self.set_active_job_state(JobState::Stalled);
+                }
+                self.current_frame = full_message;
+                continue;
+            }
+            // The encode carries on; the frame just joins the re-render list
+            if progress == -4.0 {
+                crash::log(full_message);
+                if let Err(index) = self.rerender_frames.binary_search(&frame) {
+                    self.rerender_frames.insert(index, frame);
+                }
+                continue;
+            }
+            if self.stalled_since.take().is_some() {
+                self.set_active_job_state(JobState::Running);
+            }
+
+            if !(0.0..100.0).contains(&progress) {
+                crash::log(full_message.clone());
+            }
+
+            if progress == -1.0 {
+                self.send_telemetry("job_failed", Some(message.clone()));
+            } else if progress >= 100.0 {
+                self.send_telemetry("job_finished", None);
+            }
+
+            let finished_job = if progress >= 100.0 {
+                self.active_job
+            } else {
+                None
+            };
+            let job_state = if progress >= 100.0 {
+                Some(JobState::Done)
+            } else if progress == -1.0 {
+                Some(JobState::Failed(message.clone()))
+            } else if progress == -2.0 && !self.resume_after_pause {
+                // A kill-and-resume pause is part of the same run; one by the
+                // operator holds the rest of the queue too
+                self.queue.running = false;
+                Some(JobState::Paused)
+            } else {
+                None
+            };
+            if let (Some(id), Some(state)) = (self.active_job, job_state) {
+                if let Some(job) = self.queue.get_mut(id) {
+                    job.state = state;
+                }
+                self.active_job = None;
+            }
+
+            if progress < 0.0 {
+                self.status = full_message.clone();
+                self.encoding = false;
+                self.current_frame = full_message;
+            } else if progress >= 100.0 {
+                self.progress = 100.0;
+                self.status = "Done!".to_string();
+                self.encoding = false;
+                self.current_frame = full_message;
+                self.start_replication();
+                self.start_archive();
+                if self.share_after_encode
+                    && !self.generate_pattern
+                    && self.share_status != Some(ShareStatus::Exporting)
+                {
+                    self.start_share();
+                }
+                if finished_job.is_some() {
+                    self.delivering_job = finished_job;
+                    self.track_delivering_job();
+                }
+            } else {
+                self.progress = progress;
+                self.current_frame = full_message;
+                match self.pause_at_frame {
+                    Some(target) if frame >= target => {
+                        crash::log(format!("Pausing {} at frame {:06}", self.base_name, frame));
+                        self.pause_at_frame = None;
+                        self.pause_encoding();
+                    }
+                    Some(_) => {}
+                    None => self.pause_frame_input = self.pause_frame_input.max(frame),
+                }
+            }
+            if !(0.0..100.0).contains(&progress) && !self.resume_after_pause {
+                self.pause_at_frame = None;
+            }
+        }
+
+        if let Some(since) = self.stalled_since {
+            let limit = self.settings.auto_resume_stalled_mins as u64 * 60;
+            if limit > 0 && since.elapsed().as_secs() >= limit {
+                self.kill_and_resume();
+            }
+        }
+
+        if self.resume_after_pause && !self.encoding {
+            self.resume_after_pause = false;
+            self.start_encoding();
+        }
+
+        self.advance_queue();
+        if self.window_wait() > 0 {
+            ctx.request_repaint_after(Duration::from_secs(30));
+        }
+
+        if let Some(handle) = self.worker_thread.take() {
+            if handle.is_finished() {
+                self.cancel_sender = None;
+            } else {
+                self.worker_thread = Some(handle);
+            }
+        }
+
+        while let Ok(status) = self.proxy_receiver.try_recv() {
+            self.proxy_status = Some(status);
+        }
+
+        while let Ok(status) = self.share_receiver.try_recv() {
+            self.share_status = Some(status);
+        }
+
+        while let Ok(status) = self.purge_receiver.try_recv() {
+            self.purge_status = Some(status);
+        }
+
+        while let Ok(status) = self.consolidate_receiver.try_recv() {
+            if status.is_finished() {
+                self.update_storage_status();
+            }
+            self.consolidate_status = Some(status);
+        }
+        if self
+            .consolidate_status
+            .as_ref()
+            .is_some_and(|status| !status.is_finished())
+        {
+            ctx.request_repaint_after(Duration::from_millis(250));
+        }
+
+        while let Ok(status) = self.renumber_receiver.try_recv() {
+            if let RenumberStatus::Done(_) = status {
+                self.rescan_renumber();
+            }
+            self.renumber_status = Some(status);
+        }
+
+        while let Ok(status) = self.still_receiver.try_recv() {
+            self.still_status = Some(status);
+        }
+
+        while let Ok(status) = self.rerender_receiver.try_recv() {
+            if matches!(status, RerenderStatus::Done(_)) {
+                self.rerender_frames.clear();
+            }
+            self.rerender_status = Some(status);
+        }
+
+        while let Ok(status) = self.update_receiver.try_recv() {
+            if let UpdateStatus::Failed(e) = &status {
+                crash::log(e.clone());
+            }
+            self.update_status = Some(status);
+        }
+
+        let spool_events: Vec<SpoolEvent> = self
+            .spool
+            .as_ref()
+            .map(|spool| spool.events.try_iter().collect())
+            .unwrap_or_default();
+        for event in spool_events {
+            match event {
+                SpoolEvent::Accepted(request) => {
+                    crash::log(format!("Spooled job for {}", request.input_video.display()));
+                    self.queue.add(
+                        request.input_video,
+                        request.resolution,
+                        request.output_dir,
+                        request.output_format,
+                        request.notes,
+                    );
+                    self.status = "Job received from spool folder".to_string();
+                }
+                SpoolEvent::Rejected { file, reason } => {
+                    crash::log(format!(
+                        "Rejected spool file {}: {}",
+                        file.display(),
+                        reason
+                    ));
+                    self.status = format!("Rejected {}: {}", file.display(), reason);
+                }
+            }
+        }
+        if self.spool.is_some() {
+            ctx.request_repaint_after(spool::POLL_INTERVAL);
+        }
+
+        while let Ok(status) = self.archive_receiver.try_recv() {
+            match &status {
+                // The master now only exists in the archive; the queue may already
+                // have loaded the next job's input
+                ArchiveStatus::Done { path, moved: true }
+                    if self.input_video.file_name() == path.file_name() =>
+                {
+                    self.input_video = path.clone()
+                }
+                ArchiveStatus::Failed(e) => crash::log(format!("Archive failed: {}", e)),
+                _ => {}
+            }
+            self.archive_status = Some(status);
+        }
+
+        while let Ok((index, status)) = self.replication_receiver.try_recv() {
+            self.replication_heartbeat = Instant::now();
+            if let Some(slot) = self.replication_status.get_mut(index) {
+                *slot = status;
+            }
+        }
+
+        // A copy blocked on a dead destination never returns, so its thread is left
+        // behind and the destination marked failed for a retry
+        let copy_timeout = self.settings.copy_stall_timeout_secs.max(10);
+        if self.is_replicating() && self.replication_heartbeat.elapsed().as_secs() >= copy_timeout {
+            let error = EncoderError::Timeout {
+                phase: "Copy",
+                secs: copy_timeout,
+            };
+            crash::log(format!("Replication stalled: {}", error));
+            for status in &mut self.replication_status {
+                if !status.is_finished() {
+                    *status = ReplicationStatus::Failed(error.to_string());
+                }
+            }
+            self.replication_threads.clear();
+        }
+
+        if self
+            .replication_status
+            .iter()
+            .all(ReplicationStatus::is_finished)
+        {
+            self.replication_threads.clear();
+        }
+        self.track_delivering_job();
+
+        let archiving = self
+            .archive_status
+            .as_ref()
+            .is_some_and(|status| !status.is_finished());
+        if self.encoding || self.is_replicating() || archiving {
+            ctx.request_repaint();
+        }
+
+        // Track previous resolution to detect changes
+        let previous_resolution = self.resolution;
+
+        egui::CentralPanel::default()
+            .frame(egui::Frame {
+                inner_margin: egui::Margin::symmetric(30.0, 30.0),
+                fill: ctx.style().visuals.panel_fill,
+                ..Default::default()
+            })
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    self.show_update_banner(ui);
+
+                    ui.heading("Encoder Settings");
+                    ui.add_space(10.0);
+
+                    self.show_profile_controls(ui);
+                    ui.add_space(10.0);
+
+                    ui.horizontal(|ui| {
+                        ui.label("Resolution:");
+                        let combo = egui::ComboBox::from_id_source("resolution_combo")
+                            .selected_text(self.resolution.as_str());
+
+                        ui.set_enabled(!self.encoding);
+                        combo.show_ui(ui, |ui| {
+                            for resolution in Resolution::ALL {
+                                ui.selectable_value(
+                                    &mut self.resolution,
+                                    resolution,
+                                    resolution.as_str(),
+                                );
+                            }
+                        });
+
+                        let fixed = self.output_format.frame_size().is_some()
+                            || self.resolution.target_size().is_some();
+                        ui.add_enabled_ui(fixed, |ui| {
+                            egui::ComboBox::from_id_source("fit_combo")
+                                .selected_text(self.fit_mode.as_str())
+                                .show_ui(ui, |ui| {
+                                    for mode in FitMode::ALL {
+                                        ui.selectable_value(&mut self.fit_mode, mode, mode.as_str());
+                                    }
+                                })
+                                .response
+                                .on_hover_text("How a source of another aspect fills the preset");
+                        });
+                    });
+                    self.show_source_check(ui);
+                    self.show_still_hold_controls(ui);
+                    self.show_pattern_controls(ui);
+
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        ui.label("Output Format:");
+                        ui.add_enabled_ui(!self.encoding, |ui| {
+                            egui::ComboBox::from_id_source("format_combo")
+                                .selected_text(self.output_format.as_str())
+                                .show_ui(ui, |ui| {
+                                    for format in OutputFormat::ALL {
+                                        let missing = self.missing_support(format);
+                                        let mut response = ui.add_enabled(
+                                            missing.is_none(),
+                                            egui::SelectableLabel::new(
+                                                self.output_format == format,
+                                                format.as_str(),
+                                            ),
+                                        );
+                                        if let Some(reason) = &missing {
+                                            response = response.on_disabled_hover_text(reason);
+                                        }
+                                        if response.clicked() && self.output_format != format {
+                                            self.output_format = format;
+                                            self.update_storage_status();
+                                        }
+                                    }
+                                });
+                        });
+                        // A profile or queued job can still pick a format this build lacks
+                        if let Some(reason) = self.missing_support(self.output_format) {
+                            ui.colored_label(egui::Color32::LIGHT_YELLOW, "⚠").on_hover_text(reason);
+                        }
+                    });
+
+                    self.show_format_options(ui);
+
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        ui.label("Pull-down Removal:");
+                        let checkbox = egui::Checkbox::new(
+                            &mut self.inverse_telecine,
+                            "Inverse telecine (29.97 → 23.976)",
+                        );
+                        if ui.add_enabled(!self.encoding, checkbox).changed() {
+                            self.fps_decision = None;
+                        }
+                        // Only NTSC telecine follows the 3:2 cadence this undoes
+                        let telecine_rate =
+                            self.source_fps.is_none_or(|fps| (fps - 29.97).abs() < 0.01);
+                        if self.inverse_telecine && !telecine_rate {
+                            ui.colored_label(
+                                egui::Color32::LIGHT_YELLOW,
+                                "Source is not 29.97 fps",
+                            );
+                        }
+                    });
+
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        ui.label("Decode Errors:");
+                        let checkbox = egui::Checkbox::new(
+                            &mut self.tolerate_errors,
+                            "Continue past corrupt frames",
+                        );
+                        ui.add_enabled(!self.encoding, checkbox).on_hover_text(
+                            "Affected frames are listed in <name>_decode_errors.txt next to the output",
+                        );
+                    });
+
+                    if self.output_format.is_sequence() {
+                        ui.add_space(10.0);
+                        ui.horizontal(|ui| {
+                            ui.label("Pipe To:");
+                            ui.add_enabled(
+                                !self.encoding,
+                                egui::Checkbox::new(&mut self.pipe_enabled, ""),
+                            )
+                            .on_hover_text(
+                                "Stream frames to a command's stdin instead of writing them to disk",
+                            );
+                            ui.add_enabled(
+                                !self.encoding && self.pipe_enabled,
+                                egui::TextEdit::singleline(&mut self.pipe_command)
+                                    .hint_text("e.g. oiiotool - -o out.exr")
+                                    .desired_width(320.0),
+                            );
+                        });
+                        if self.pipe_enabled {
+                            ui.weak("Runs in the output directory; piped jobs cannot resume.");
+                        }
+                    }
+
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        ui.label("Output Directory:");
+                        let browse_button = egui::Button::new("📂 Browse...")
+                            .fill(egui::Color32::from_rgb(30, 90, 100));
+
+                        if ui.add_enabled(!self.encoding, browse_button).clicked() {
+                            if let Some(path) = FileDialog::new().pick_folder() {
+                                self.output_dir = Some(path);
+                                self.update_storage_status();
+                            }
+                        }
+                        match &self.output_dir {
+                            Some(path) => ui.label(path.display().to_string()),
+                            None => ui.label("Not selected"),
+                        }
+                    });
+
+                    if let Some(proxy) = &self.proxy_status {
+                        ui.add_space(5.0);
+                        ui.horizontal(|ui| {
+                            ui.label("Preview Proxy:");
+                            ui.weak(proxy.describe());
+                        });
+                    }
+
+                    ui.add_space(10.0);
+                    self.show_overlay_controls(ui);
+
+                    ui.add_space(10.0);
+                    self.show_naming_controls(ui);
+
+                    ui.add_space(10.0);
+                    self.show_subtitle_controls(ui);
+
+                    ui.add_space(10.0);
+                    self.show_audio_controls(ui);
+
+                    ui.add_space(10.0);
+                    ui.horizontal(|ui| {
+                        ui.label("Notes:");
+                        ui.add_enabled(
+                            !self.encoding,
+                            egui::TextEdit::singleline(&mut self.job_notes)
+                                .hint_text("e.g. client requested darker slate")
+                                .desired_width(f32::INFINITY),
+                        )
+                        .on_hover_text("Kept with the job in the queue and the job ledger");
+                    });
+
+                    ui.add_space(10.0);
+                    let editable = !self.encoding && !self.is_replicating();
+                    ui.horizontal(|ui| {
+                        ui.label("Replicate To:");
+                        let add_button = egui::Button::new("➕ Add Destination")
+                            .fill(egui::Color32::from_rgb(30, 90, 100));
+
+                        if ui.add_enabled(editable, add_button).clicked() {
+                            if let Some(path) = FileDialog::new().pick_folder() {
+                                if !self.replica_dirs.contains(&path) {
+                                    self.replica_dirs.push(path);
+                                    self.replication_status.clear();
+                                }
+                            }
+                        }
+
+                        let any_failed = self
+                            .replication_status
+                            .iter()
+                            .any(|status| matches!(status, ReplicationStatus::Failed(_)));
+                        if any_failed
+                            && ui
+                                .add_enabled(editable, egui::Button::new("⟳ Retry Copies"))
+                                .clicked()
+                        {
+                            self.start_replication();
+                        }
+                    });
+
+                    let mut removed = None;
+                    for (index, dir) in self.replica_dirs.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui.add_enabled(editable, egui::Button::new("✖")).clicked() {
+                                removed = Some(index);
+                            }
+                            ui.label(dir.display().to_string());
+                            if let Some(status) = self.replication_status.get(index) {
+                                let color = match status {
+                                    ReplicationStatus::Done(_) => egui::Color32::LIGHT_GREEN,
+                                    ReplicationStatus::Failed(_) => egui::Color32::LIGHT_RED,
+                                    _ => egui::Color32::LIGHT_BLUE,
+                                };
+                                ui.colored_label(color, status.describe());
+                            }
+                        });
+                    }
+                    if let Some(index) = removed {
+                        self.replica_dirs.remove(index);
+                        self.replication_status.clear();
+                    }
+
+                    let archiving = self
+                        .archive_status
+                        .as_ref()
+                        .is_some_and(|status| !status.is_finished());
+                    let mut archive_changed = false;
+                    ui.horizontal(|ui| {
+                        ui.label("Archive Source To:");
+                        let browse_button = egui::Button::new("📂 Browse...")
+                            .fill(egui::Color32::from_rgb(30, 90, 100));
+                        if ui.add_enabled(!archiving, browse_button).clicked() {
+                            if let Some(path) = FileDialog::new().pick_folder() {
+                                self.settings.archive_dir = Some(path);
+                                archive_changed = true;
+                            }
+                        }
+                        match &self.settings.archive_dir {
+                            Some(dir) => {
+                                ui.label(dir.display().to_string());
+                                if ui.add_enabled(!archiving, egui::Button::new("✖")).clicked() {
+                                    self.settings.archive_dir = None;
+                                    archive_changed = true;
+                                }
+                                archive_changed |= ui
+                                    .add_enabled(
+                                        !archiving,
+                                        egui::Checkbox::new(
+                                            &mut self.settings.archive_move,
+                                            "Move",
+                                        ),
+                                    )
+                                    .on_hover_text(
+                                        "Remove the original once the archived copy verifies",
+                                    )
+                                    .changed();
+                            }
+                            None => {
+                                ui.weak("Off");
+                            }
+                        }
+                        if let Some(status) = &self.archive_status {
+                            let color = match status {
+                                ArchiveStatus::Done { .. } => egui::Color32::LIGHT_GREEN,
+                                ArchiveStatus::Failed(_) => egui::Color32::LIGHT_RED,
+                                _ => egui::Color32::LIGHT_BLUE,
+                            };
+                            ui.colored_label(color, status.describe());
+                        }
+                    });
+                    if archive_changed {
+                        if let Err(e) = self.settings.save() {
+                            self.status = format!("Could not save settings: {}", e);
+                        }
+                    }
+
+                    ui.add_space(20.0);
+                    ui.separator();
+                    ui.add_space(20.0);
+
+                    ui.vertical(|ui| {
+                        ui.label(
+                            egui::RichText::new("Current Status:")
+                                .heading()
+                                .color(egui::Color32::LIGHT_BLUE),
+                        );
+                        ui.add_space(5.0);
+
+                        let status_color = if self.encoding {
+                            egui::Color32::LIGHT_GREEN
+                        } else if self.progress >= 100.0 {
+                            egui::Color32::DARK_GREEN
+                        } else if !self.sufficient_storage {
+                            egui::Color32::LIGHT_RED
+                        } else {
+                            egui::Color32::LIGHT_BLUE
+                        };
+
+                        ui.label(egui::RichText::new(&self.current_frame).color(status_color));
+
+                        ui.add_space(10.0);
+
+                        let progress_color = if self.encoding {
+                            egui::Color32::from_rgb(0, 180, 100)
+                        } else if self.progress >= 100.0 {
+                            egui::Color32::DARK_GREEN
+                        } else {
+                            egui::Color32::LIGHT_BLUE
+                        };
+
+                        ui.add(
+                            egui::ProgressBar::new(self.progress / 100.0)
+                                .fill(progress_color)
+                                .show_percentage()
+                                .text(format!("{:.1}%", self.progress)),
+                        );
+                    });
+
+                    if !self.encoding {
+                        if let Some(err) = &self.storage_error {
+                            ui.add_space(10.0);
+                            ui.colored_label(egui::Color32::LIGHT_RED, err);
+                        } else if !self.volume_split.is_empty() {
+                            ui.add_space(10.0);
+                            ui.colored_label(
+                                egui::Color32::LIGHT_YELLOW,
+                                format!(
+                                    "Not enough room on one volume: frames will continue on {}",
+                                    self.volume_split
+                                        .iter()
+                                        .map(|part| format!(
+                                            "{} from {:06}",
+                                            part.dir.display(),
+                                            part.first_frame
+                                        ))
+                                        .collect::<Vec<_>>()
+                                        .join(", ")
+                                ),
+                            );
+                        }
+                        self.show_split_controls(ui);
+                    }
+
+                    ui.add_space(20.0);
+
+                    ui.horizontal(|ui| {
+                        if self.encoding {
+                            let pause_button = egui::Button::new("⏸ Pause")
+                                .fill(egui::Color32::from_rgb(200, 150, 50));
+                            if ui.add(pause_button).clicked() {
+                                self.pause_encoding();
+                            }
+
+                            match self.pause_at_frame {
+                                Some(target) => {
+                                    ui.label(format!("Pausing at frame {:06}", target));
+                                    if ui.small_button("✖").on_hover_text("Keep encoding").clicked() {
+                                        self.pause_at_frame = None;
+                                    }
+                                }
+                                None => {
+                                    if ui
+                                        .button("⏸ Pause at Frame")
+                                        .on_hover_text("Finish up to this frame, then pause so the job resumes from there")
+                                        .clicked()
+                                    {
+                                        self.pause_at_frame = Some(self.pause_frame_input);
+                                    }
+                                    ui.add(egui::DragValue::new(&mut self.pause_frame_input));
+                                }
+                            }
+
+                            if self.active_job.is_some()
+                                && self.queue.running
+                                && ui
+                                    .button("⏹ Stop After This Job")
+                                    .on_hover_text("Let this job finish, then hold the rest of the queue")
+                                    .clicked()
+                            {
+                                self.queue.running = false;
+                            }
+
+                            let cancel_button = egui::Button::new("⏹ Cancel")
+                                .fill(egui::Color32::from_rgb(180, 80, 80));
+                            if ui.add(cancel_button).clicked() {
+                                self.dialog_state = DialogState::CancelConfirmation(None);
+                            }
+
+                            let cancel_delete_button = egui::Button::new("⏹ Cancel and Delete")
+                                .fill(egui::Color32::from_rgb(150, 40, 40));
+                            if ui.add(cancel_delete_button).clicked() {
+                                let summary = TrashSummary::of(&self.existing_output_files());
+                                self.dialog_state = DialogState::CancelConfirmation(Some(summary));
+                            }
+
+                            if self.stalled_since.is_some() {
+                                let resume_button = egui::Button::new("⟳ Kill and Resume")
+                                    .fill(egui::Color32::from_rgb(200, 110, 30));
+                                if ui.add(resume_button).clicked() {
+                                    self.kill_and_resume();
+                                }
+                            }
+                        } else {
+                            let start_enabled = self.sufficient_storage;
+                            let button_color = if start_enabled {
+                                egui::Color32::from_rgb(0, 140, 70)
+                            } else {
+                                egui::Color32::GRAY
+                            };
+
+                            let start_button =
+                                egui::Button::new("▶ Start Encoding").fill(button_color);
+                            if ui.add_enabled(start_enabled, start_button).clicked() {
+                                self.start_encoding();
+                            }
+                        }
+
+                        let open_enabled = self.output_dir.is_some();
+                        let button_color = if open_enabled {
+                            egui::Color32::from_rgb(50, 120, 180)
+                        } else {
+                            egui::Color32::GRAY
+                        };
+
+                        let open_button =
+                            egui::Button::new("📂 Open Output Folder").fill(button_color);
+                        if ui.add_enabled(open_enabled, open_button).clicked() {
+                            if let Some(path) = &self.output_dir {
+                                open_folder(path);
+                            }
+                        }
+
+                        // The image decoder the player uses has no DPX support
+                        let play_enabled = open_enabled
+                            && !self.encoding
+                            && self.output_format.is_sequence()
+                            && self.output_format != OutputFormat::DpxSequence;
+                        let play_color = if play_enabled {
+                            egui::Color32::from_rgb(90, 80, 160)
+                        } else {
+                            egui::Color32::GRAY
+                        };
+
+                        let play_button = egui::Button::new("🎞 Play Output").fill(play_color);
+                        if ui
+                            .add_enabled(play_enabled, play_button)
+                            .on_disabled_hover_text(
+                                "Plays image sequences other than DPX once encoding is done",
+                            )
+                            .clicked()
+                        {
+                            self.open_player();
+                        }
+
+                        let script_button = egui::Button::new("📜 Export Script");
+                        if ui
+                            .add_enabled(open_enabled, script_button)
+                            .on_hover_text(
+                                "Save the ffmpeg commands for the queue, or this job when the \
+                                 queue is empty, as a .sh or .bat script",
+                            )
+                            .clicked()
+                        {
+                            self.export_script();
+                        }
+
+                        let summary_button = egui::Button::new("📋 Copy Summary");
+                        if ui
+                            .add_enabled(open_enabled && !self.encoding, summary_button)
+                            .on_hover_text("Path, frames, specs and a listing checksum as Markdown")
+                            .clicked()
+                        {
+                            let ctx = ui.ctx().clone();
+                            self.copy_summary(&ctx);
+                        }
+                    });
+
+                    ui.add_space(10.0);
+                    self.show_queue_controls(ui);
+
+                    ui.add_space(10.0);
+                    self.show_share_controls(ui);
+                    self.show_still_controls(ui);
+                    self.show_rerender_controls(ui);
+
+                    ui.add_space(10.0);
+                    self.show_purge_controls(ui);
+                    self.show_renumber_controls(ui);
+
+                    ui.add_space(10.0);
+                    egui::CollapsingHeader::new("Station Settings").show(ui, |ui| {
+                        let mut changed = ui
+                            .checkbox(
+                                &mut self.settings.telemetry_enabled,
+                                "Send job telemetry to the pipeline dashboard",
+                            )
+                            .changed();
+
+                        ui.add_enabled_ui(self.settings.telemetry_enabled, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Endpoint:");
+                                changed |= ui
+                                    .text_edit_singleline(&mut self.settings.telemetry_url)
+                                    .lost_focus();
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label("Machine ID:");
+                                changed |= ui
+                                    .text_edit_singleline(&mut self.settings.machine_id)
+                                    .lost_focus();
+                            });
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label("Stall timeout (s):");
+                            changed |= ui
+                                .add(
+                                    egui::DragValue::new(&mut self.settings.stall_timeout_secs)
+                                        .clamp_range(10..=3600),
+                                )
+                                .changed();
+                            ui.label("Auto kill-and-resume after (min):");
+                            changed |= ui
+                                .add(
+                                    egui::DragValue::new(
+                                        &mut self.settings.auto_resume_stalled_mins,
+                                    )
+                                    .clamp_range(0..=240),
+                                )
+                                .on_hover_text("0 leaves stalled jobs to the operator")
+                                .changed();
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label("Timeouts (s) - probe:");
+                            changed |= ui
+                                .add(
+                                    egui::DragValue::new(&mut self.settings.probe_timeout_secs)
+                                        .clamp_range(5..=600),
+                                )
+                                .changed();
+                            ui.label("first frame:");
+                            changed |= ui
+                                .add(
+                                    egui::DragValue::new(
+                                        &mut self.settings.first_frame_timeout_secs,
+                                    )
+                                    .clamp_range(10..=3600),
+                                )
+                                .changed();
+                            ui.label("copy stall:");
+                            changed |= ui
+                                .add(
+                                    egui::DragValue::new(&mut self.settings.copy_stall_timeout_secs)
+                                        .clamp_range(10..=3600),
+                                )
+                                .changed();
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label("CPU power (W):");
+                            changed |= ui
+                                .add(
+                                    egui::DragValue::new(&mut self.settings.cpu_tdp_watts)
+                                        .clamp_range(5.0..=1000.0),
+                                )
+                                .on_hover_text("Used to estimate energy where the CPU's own counters can't be read")
+                                .changed();
+                            ui.label("Price per kWh:");
+                            changed |= ui
+                                .add(
+                                    egui::DragValue::new(&mut self.settings.energy_price_per_kwh)
+                                        .speed(0.01)
+                                        .clamp_range(0.0..=100.0),
+                                )
+                                .on_hover_text("0 records energy only")
+                                .changed();
+                            if ui.button("📂 Cost Ledger").clicked() {
+                                open_folder(&app_data_dir());
+                            }
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label("Overflow volumes:");
+                            if ui
+                                .button("➕ Add...")
+                                .on_hover_text("A sequence too big for its output volume continues on these, in order")
+                                .clicked()
+                            {
+                                if let Some(path) = FileDialog::new().pick_folder() {
+                                    if !self.settings.overflow_dirs.contains(&path) {
+                                        self.settings.overflow_dirs.push(path);
+                                        changed = true;
+                                    }
+                                }
+                            }
+                            if self.settings.overflow_dirs.is_empty() {
+                                ui.weak("Off");
+                            }
+                        });
+                        let mut removed = None;
+                        for (index, dir) in self.settings.overflow_dirs.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("✖").clicked() {
+                                    removed = Some(index);
+                                }
+                                ui.label(dir.display().to_string());
+                            });
+                        }
+                        if let Some(index) = removed {
+                            self.settings.overflow_dirs.remove(index);
+                            changed = true;
+                        }
+
+                        ui.horizontal(|ui| {
+                            ui.label("Job spool folder:");
+                            if ui.button("📂 Browse...").clicked() {
+                                if let Some(path) = FileDialog::new().pick_folder() {
+                                    self.spool = Some(spawn_watcher(path.clone()));
+                                    self.settings.spool_dir = Some(path);
+                                    changed = true;
+                                }
+                            }
+                            match &self.settings.spool_dir {
+                                Some(path) => {
+                                    ui.label(path.display().to_string());
+                                    if ui.small_button("Off").clicked() {
+                                        self.settings.spool_dir = None;
+                                        self.spool = None;
+                                        changed = true;
+                                    }
+                                }
+                                None => {
+                                    ui.weak("Off");
+                                }
+                            }
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label("Scratch directory:");
+                            if ui.button("📂 Browse...").clicked() {
+                                if let Some(path) = FileDialog::new().pick_folder() {
+                                    self.settings.scratch_dir = Some(path);
+                                    changed = true;
+                                }
+                            }
+                            match &self.settings.scratch_dir {
+                                Some(path) => {
+                                    ui.label(path.display().to_string());
+                                    if ui.small_button("Use system temp").clicked() {
+                                        self.settings.scratch_dir = None;
+                                        changed = true;
+                                    }
+                                }
+                                None => {
+                                    ui.weak(format!(
+                                        "System temp ({})",
+                                        std::env::temp_dir().display()
+                                    ));
+                                }
+                            }
+                        });
+
+                        if cfg!(unix) {
+                            ui.horizontal(|ui| {
+                                ui.label("Output mode:");
+                                changed |= ui
+                                    .add(
+                                        egui::TextEdit::singleline(&mut self.settings.output_mode)
+                                            .desired_width(50.0)
+                                            .hint_text("664"),
+                                    )
+                                    .lost_focus();
+                                ui.label("Group:");
+                                changed |= ui
+                                    .add(
+                                        egui::TextEdit::singleline(&mut self.settings.output_group)
+                                            .desired_width(100.0),
+                                    )
+                                    .lost_focus();
+                                let mode = self.settings.output_mode.trim();
+                                if !mode.is_empty() && OutputPermissions::parse_mode(mode).is_none()
+                                {
+                                    ui.colored_label(
+                                        egui::Color32::LIGHT_RED,
+                                        "Mode must be octal, e.g. 664",
+                                    );
+                                }
+                            });
+                        }
+
+                        ui.horizontal(|ui| {
+                            ui.label("Encode windows:");
+                            changed |= ui
+                                .add(
+                                    egui::TextEdit::singleline(&mut self.settings.encode_windows)
+                                        .desired_width(220.0)
+                                        .hint_text("Mon-Fri 19:00-08:00; Sat-Sun any"),
+                                )
+                                .on_hover_text("When the queue may start jobs; empty is any time")
+                                .lost_focus();
+                            if let Err(e) = EncodeWindows::parse(&self.settings.encode_windows) {
+                                ui.colored_label(egui::Color32::LIGHT_RED, e.to_string());
+                            }
+                        });
+
+                        ui.label("FFmpeg environment (one KEY=VALUE per line):");
+                        changed |= ui
+                            .add(
+                                egui::TextEdit::multiline(&mut self.settings.job_env)
+                                    .desired_rows(2)
+                                    .font(egui::TextStyle::Monospace),
+                            )
+                            .lost_focus();
+
+                        if changed {
+                            set_probe_timeout(Duration::from_secs(
+                                self.settings.probe_timeout_secs,
+                            ));
+                            if let Err(e) = self.settings.save() {
+                                self.status = format!("Could not save settings: {}", e);
+                            }
+                        }
+                    });
+
+                    if !self.instructions.is_empty() {
+                        ui.add_space(20.0);
+                        ui.separator();
+                        ui.add_space(10.0);
+
+                        ui.vertical(|ui| {
+                            ui.label(
+                                egui::RichText::new(" ") //instrukce:
+                                    .heading()
+                                    .color(egui::Color32::LIGHT_YELLOW),
+                            );
+                            ui.add_space(5.0);
+                            ui.label(&self.instructions);
+                        });
+                    }
+                });
+            });
+
+        // Check if resolution changed and update base name
+        if previous_resolution != self.resolution {
+            self.update_base_name();
+            self.update_storage_status();
+        }
+
+        if let Some(player) = &mut self.player {
+            player.show(ctx);
+            for frame in player.take_marks() {
+                if !self.still_frames.trim().is_empty() {
+                    self.still_frames.push_str(", ");
+                }
+                self.still_frames.push_str(&frame.to_string());
+            }
+            if !player.open {
+                self.player = None;
+            }
+        }
+
+        if let DialogState::SourceConflict { recorded, proposed } = &self.dialog_state {
+            let (recorded, proposed) = (recorded.clone(), proposed.clone());
+            egui::Window::new("Output Folder In Use")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.label(format!(
+                            "{} frames in this folder were encoded from a different file:",
+                            self.base_name
+                        ));
+                        ui.monospace(recorded.display().to_string());
+                        ui.label("Encoding here would mix the two shots under one name.");
+                        ui.add_space(10.0);
+
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add(
+                                    egui::Button::new(format!("Use {}", proposed.display()))
+                                        .fill(egui::Color32::from_rgb(0, 140, 70)),
+                                )
+                                .clicked()
+                            {
+                                self.dialog_state = DialogState::None;
+                                match std::fs::create_dir_all(&proposed) {
+                                    Ok(()) => {
+                                        self.output_dir = Some(proposed.clone());
+                                        self.update_storage_status();
+                                        self.start_encoding();
+                                    }
+                                    Err(e) => {
+                                        self.status = format!(
+                                            "Error: {}",
+                                            user_message(
+                                                &EncoderError::from_io(
+                                                    &e,
+                                                    &proposed,
+                                                    IoSide::Output
+                                                )
+                                                .into()
+                                            )
+                                        );
+                                    }
+                                }
+                            }
+
+                            if ui
+                                .add(
+                                    egui::Button::new("Encode Here Anyway")
+                                        .fill(egui::Color32::from_rgb(200, 150, 50)),
+                                )
+                                .clicked()
+                            {
+                                crash::log(format!(
+                                    "Operator encoded {} over frames from {}",
+                                    self.base_name,
+                                    recorded.display()
+                                ));
+                                self.source_conflict_accepted = true;
+                                self.dialog_state = DialogState::None;
+                                self.start_encoding();
+                            }
+
+                            if ui
+                                .add(egui::Button::new("Cancel").fill(egui::Color32::GRAY))
+                                .clicked()
+                            {
+                                self.dialog_state = DialogState::None;
+                            }
+                        });
+                    });
+                });
+        }
+
+        if let DialogState::FrameRateMismatch { source, delivery } = self.dialog_state {
+            egui::Window::new("Frame Rate Mismatch")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.label(format!(
+                            "The source runs at {:.3} fps but the delivery spec requires {:.3} fps.",
+                            source, delivery
+                        ));
+                        ui.add_space(10.0);
+
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add(
+                                    egui::Button::new(format!("Retime to {:.3} fps", delivery))
+                                        .fill(egui::Color32::from_rgb(0, 140, 70)),
+                                )
+                                .clicked()
+                            {
+                                self.fps_decision = Some(FpsDecision::Retime);
+                                self.dialog_state = DialogState::None;
+                                self.start_encoding();
+                            }
+
+                            if ui
+                                .add(
+                                    egui::Button::new("Proceed at Source Rate")
+                                        .fill(egui::Color32::from_rgb(200, 150, 50)),
+                                )
+                                .clicked()
+                            {
+                                self.fps_decision = Some(FpsDecision::KeepSource);
+                                self.dialog_state = DialogState::None;
+                                self.start_encoding();
+                            }
+
+                            if ui
+                                .add(egui::Button::new("Cancel").fill(egui::Color32::GRAY))
+                                .clicked()
+                            {
+                                self.dialog_state = DialogState::None;
+                            }
+                        });
+                    });
+                });
+        }
+
+        if let DialogState::CrashReports(reports) = &self.dialog_state {
+            let reports = reports.clone();
+            egui::Window::new("Previous Session Crashed")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.label(format!(
+                            "The app closed unexpectedly {} time(s) since it was last opened.",
+                            reports.len()
+                        ));
+                        ui.label("Crash reports were saved and can be sent to the pipeline team.");
+                        ui.add_space(10.0);
+
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add(
+                                    egui::Button::new("📂 Open Reports")
+                                        .fill(egui::Color32::from_rgb(50, 120, 180)),
+                                )
+                                .clicked()
+                            {
+                                // The reports move in the same click, so open where they go
+                                crash::mark_reports_seen(&reports);
+                                open_folder(&crash::seen_dir());
+                                self.dialog_state = DialogState::None;
+                            }
+
+                            if ui
+                                .add(egui::Button::new("Dismiss").fill(egui::Color32::GRAY))
+                                .clicked()
+                            {
+                                crash::mark_reports_seen(&reports);
+                                self.dialog_state = DialogState::None;
+                            }
+                        });
+                    });
+                });
+        }
+
+        if let DialogState::PurgeConfirmation(summary) = self.dialog_state {
+            egui::Window::new("Purge Old Versions?")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.vertical_centered(|ui| {
+                        ui.label(format!(
+                            "{} will be moved to the trash.",
+                            summary.describe()
+                        ));
+                        ui.add_space(10.0);
+
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add(
+                                    egui::Button::new("Purge")
+                                        .fill(egui::Color32::from_rgb(180, 80, 80)),
+                                )
+                                .clicked()
+                            {
+                                crash::log(format!("Purging old versions: {}", summary.describe()));
+                                self.purge_receiver = spawn_purge(self.purge_files());
+                                self.purge_status = Some(PurgeStatus::Purging);
+                                self.dialog_state = DialogState::None;
+                            }
+
+                            if ui
+                                .add(egui::Button::new("Cancel").fill(egui::Color32::GRAY))
+                                .clicked()
+                            {
+                                self.dialog_state = DialogState::None;
+                            }
+                        });
+                    });
+                });
+        }
+
+        if let DialogState::CancelConfirmation(summary) = self.dialog_state {
+            egui::Window::new("Cancel Encoding?")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
                     ui.vertical_centered(|ui| {
                         ui.label("Are you sure you want to cancel?");
+                        if let Some(summary) = summary {
+                            ui.label(format!(
+                                "{} will be moved to the trash.",
+                                summary.describe()
+                            ));
+                        }
                         ui.add_space(10.0);
 
                         ui.horizontal(|ui| {
@@ -613,7 +4405,7 @@ impl eframe::App for DeliveryEncoderApp {
                                 )
                                 .clicked()
                             {
-                                self.cancel_encoding(delete_frames);
+                                self.cancel_encoding(summary.is_some());
                             }
 
                             if ui
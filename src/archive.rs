@@ -0,0 +1,128 @@
+use anyhow::{anyhow, Result};
+use sha1::{Digest, Sha1};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::{
+    crash,
+    errors::{EncoderError, IoSide},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArchiveStatus {
+    Copying,
+    Verifying,
+    Done { path: PathBuf, moved: bool },
+    Failed(String),
+}
+
+impl ArchiveStatus {
+    pub fn describe(&self) -> String {
+        match self {
+            ArchiveStatus::Copying => "Copying source...".to_string(),
+            ArchiveStatus::Verifying => "Verifying checksum...".to_string(),
+            ArchiveStatus::Done { path, moved: true } => format!("Moved to {}", path.display()),
+            ArchiveStatus::Done { path, moved: false } => format!("Copied to {}", path.display()),
+            ArchiveStatus::Failed(e) => format!("Failed: {}", e),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self, ArchiveStatus::Done { .. } | ArchiveStatus::Failed(_))
+    }
+}
+
+fn sha1_hex(path: &Path, side: IoSide) -> Result<String> {
+    let mut file = fs::File::open(path).map_err(|e| EncoderError::from_io(&e, path, side))?;
+    let mut hasher = Sha1::new();
+    io::copy(&mut file, &mut hasher).map_err(|e| EncoderError::from_io(&e, path, side))?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+// Copies under a temporary name and only takes the real name once both checksums
+// agree, so a half-written master never looks archived. With `move_source` the
+// original is only removed after that.
+fn archive(
+    source: &Path,
+    archive_dir: &Path,
+    move_source: bool,
+    sender: &Sender<ArchiveStatus>,
+) -> Result<PathBuf> {
+    let file_name = source
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid source path {}", source.display()))?;
+    fs::create_dir_all(archive_dir)
+        .map_err(|e| EncoderError::from_io(&e, archive_dir, IoSide::Output))?;
+    let target = archive_dir.join(file_name);
+
+    let _ = sender.send(ArchiveStatus::Verifying);
+    let source_hash = sha1_hex(source, IoSide::Source)?;
+    if target.exists() {
+        // A rerun of the same delivery finds its master already archived
+        if sha1_hex(&target, IoSide::Output)? != source_hash {
+            return Err(anyhow!(
+                "{} already exists with different contents",
+                target.display()
+            ));
+        }
+    } else {
+        let _ = sender.send(ArchiveStatus::Copying);
+        let partial = archive_dir.join(format!("{}.partial", file_name.to_string_lossy()));
+        fs::copy(source, &partial)
+            .map_err(|e| EncoderError::from_io(&e, &partial, IoSide::Output))?;
+
+        let _ = sender.send(ArchiveStatus::Verifying);
+        let copy_hash = sha1_hex(&partial, IoSide::Output)?;
+        if copy_hash != source_hash {
+            let _ = fs::remove_file(&partial);
+            return Err(anyhow!(
+                "Checksum mismatch for {} (source {}, copy {})",
+                target.display(),
+                source_hash,
+                copy_hash
+            ));
+        }
+        fs::rename(&partial, &target)
+            .map_err(|e| EncoderError::from_io(&e, &target, IoSide::Output))?;
+    }
+
+    if move_source {
+        fs::remove_file(source).map_err(|e| EncoderError::from_io(&e, source, IoSide::Source))?;
+    }
+    crash::log(format!(
+        "Archived {} to {} (sha1 {})",
+        source.display(),
+        target.display(),
+        source_hash
+    ));
+    Ok(target)
+}
+
+pub fn spawn_archive(
+    source: PathBuf,
+    archive_dir: PathBuf,
+    move_source: bool,
+) -> Receiver<ArchiveStatus> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let status = match archive(&source, &archive_dir, move_source, &sender) {
+            Ok(path) => ArchiveStatus::Done {
+                path,
+                moved: move_source,
+            },
+            Err(e) => ArchiveStatus::Failed(e.to_string()),
+        };
+        let _ = sender.send(status);
+    });
+
+    receiver
+}
@@ -0,0 +1,146 @@
+use anyhow::Result;
+use std::{
+    path::Path,
+    process::{Command, Stdio},
+};
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+use crate::{
+    errors::{EncoderError, IoSide},
+    models::StemMode,
+    utils::JobEnvironment,
+};
+
+// Channel order of the common FFmpeg layouts, used to label stems
+fn layout_channels(layout: &str) -> Option<&'static [&'static str]> {
+    let channels: &'static [&'static str] = match layout {
+        "mono" => &["FC"],
+        "stereo" => &["FL", "FR"],
+        "2.1" => &["FL", "FR", "LFE"],
+        "3.0" => &["FL", "FR", "FC"],
+        "quad" => &["FL", "FR", "BL", "BR"],
+        "5.0" => &["FL", "FR", "FC", "BL", "BR"],
+        "5.0(side)" => &["FL", "FR", "FC", "SL", "SR"],
+        "5.1" => &["FL", "FR", "FC", "LFE", "BL", "BR"],
+        "5.1(side)" => &["FL", "FR", "FC", "LFE", "SL", "SR"],
+        "7.1" => &["FL", "FR", "FC", "LFE", "BL", "BR", "SL", "SR"],
+        "7.1(wide)" => &["FL", "FR", "FC", "LFE", "BL", "BR", "FLC", "FRC"],
+        _ => return None,
+    };
+    Some(channels)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioLayout {
+    pub channels: u32,
+    pub layout: String,
+}
+
+impl AudioLayout {
+    pub fn describe(&self) -> String {
+        if self.layout.is_empty() {
+            format!("{} ch", self.channels)
+        } else {
+            format!("{} ({} ch)", self.layout, self.channels)
+        }
+    }
+
+    // Falls back to A1, A2... when the layout is unknown or does not match the count
+    fn channel_label(&self, index: u32) -> String {
+        layout_channels(&self.layout)
+            .filter(|names| names.len() == self.channels as usize)
+            .map(|names| names[index as usize].to_string())
+            .unwrap_or_else(|| format!("A{}", index + 1))
+    }
+
+    // (label, pan filter) for every stem the mode produces
+    fn stems(&self, mode: StemMode) -> Vec<(String, String)> {
+        match mode {
+            StemMode::PerChannel => (0..self.channels)
+                .map(|c| (self.channel_label(c), format!("pan=mono|c0=c{}", c)))
+                .collect(),
+            StemMode::StereoPairs => (0..self.channels)
+                .step_by(2)
+                .map(|c| {
+                    if c + 1 < self.channels {
+                        (
+                            format!("A{}-{}", c + 1, c + 2),
+                            format!("pan=stereo|c0=c{}|c1=c{}", c, c + 1),
+                        )
+                    } else {
+                        (format!("A{}", c + 1), format!("pan=mono|c0=c{}", c))
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+pub fn export_stems(
+    ffmpeg_path: &Path,
+    environment: &JobEnvironment,
+    input: &Path,
+    output_dir: &Path,
+    base_name: &str,
+    layout: &AudioLayout,
+    mode: StemMode,
+) -> Result<usize> {
+    let stems = layout.stems(mode);
+    if stems.is_empty() {
+        return Ok(0);
+    }
+
+    let filter_complex = stems
+        .iter()
+        .enumerate()
+        .map(|(index, (_, pan))| format!("[0:a:0]{}[stem{}]", pan, index))
+        .collect::<Vec<_>>()
+        .join("; ");
+
+    let mut command = Command::new(ffmpeg_path);
+    environment.apply(&mut command);
+    command
+        .args(["-v", "error", "-i"])
+        .arg(input)
+        .arg("-filter_complex")
+        .arg(&filter_complex);
+
+    for (index, (label, _)) in stems.iter().enumerate() {
+        command
+            .args(["-map", &format!("[stem{}]", index)])
+            .args(["-c:a", "pcm_s24le"])
+            .arg(output_dir.join(format!("{}_{}.wav", base_name, label)));
+    }
+
+    command
+        .arg("-y")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    let output = {
+        #[cfg(windows)]
+        {
+            command.creation_flags(0x08000000).output()
+        }
+        #[cfg(not(windows))]
+        {
+            command.output()
+        }
+    }
+    .map_err(|e| EncoderError::from_io(&e, output_dir, IoSide::Output))?;
+
+    if !output.status.success() {
+        return Err(EncoderError::EncoderFailed {
+            stderr: format!(
+                "audio stems: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            resumes: false,
+        }
+        .into());
+    }
+
+    Ok(stems.len())
+}
@@ -0,0 +1,436 @@
+use anyhow::{anyhow, Result};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use crate::{
+    encoding::{check_job_support, run_encoding, EncodingConfig},
+    errors::user_message,
+    models::{
+        Av1Speed, DnxhrProfile, FitMode, FormatOptions, HevcProfile, LeaderSpec, NamingRules,
+        OutputFormat, OverlaySpec, OverlayStyle, PatternKind, RateControl, Resolution, StillHold,
+        TestPattern,
+    },
+    settings::Settings,
+    utils::{
+        find_ffmpeg, is_still_image, sanitize_base_name, set_probe_timeout, FfmpegCapabilities,
+    },
+};
+
+const USAGE: &str = "\
+Usage: delivery_encoder --cli --input <video> --output-dir <dir> [options]
+
+Options:
+  --resolution <2k|4k|6k|hd|uhd|dci4k>
+                            Delivery preset (default 6k, the source size)
+  --fit <pad|crop|stretch>  How another aspect fills the preset (default pad)
+  --overlay <png>           Overlay image (default assets/overlay_<resolution>.png);
+                            repeat to stack layers, composited in order
+  --no-overlay              Deliver the picture without an overlay
+  --format <name>           png, exr, dpx, jpeg, prores-422, prores-4444,
+                            dnxhr-mov, dnxhr-mxf, h264, hevc, av1, av1-aom, webm, xdcam, avc-intra, imf-j2k or imf-prores (default png)
+  --dnxhr <lb|sq|hq|444>    DNxHR profile (default hq)
+  --crf <n>                 Quality: 0-51 for H.264/HEVC (default 18, HEVC 20),
+                            0-63 for AV1/VP9 (default 30, VP9 32)
+  --bitrate <mbps>          H.264/HEVC fixed bitrate instead of CRF
+  --hevc-8bit               HEVC Main instead of Main 10
+  --av1-speed <kind>        quality, balanced or fast (default balanced)
+  --review-mp4              With a sequence, also write <name>_review.mp4
+  --review-webm             Also write a VP9 <name>_review.webm; --crf applies
+  --every-nth <n>           Sequences keep only every Nth frame, under its own number
+  --qc-sample <k>           Copy first/middle/last plus k random frames to <name>_qc
+  --leader <secs>           Countdown leader with a 2-pop before the program
+  --hold <secs>             Input is a PNG/TIFF still, held this long (default 10)
+  --generate <kind>         Encode bars or testpattern instead of --input
+  --length <secs>           Length of the generated leader (default 30)
+  --no-tone                 Leave out the leader's 1 kHz tone
+  --fps <fps>               Frame rate of a held still or leader (default 24)
+  --name <base>             Output base name (default the input file name)
+  --show <name>             Show the job's energy cost is booked against
+  --notes <text>            Notes kept with the job in the ledger
+
+Station settings (scratch dir, ffmpeg environment, timeouts, permissions) apply as in the app.";
+
+struct CliArgs {
+    input: PathBuf,
+    output_dir: PathBuf,
+    resolution: Resolution,
+    fit: FitMode,
+    // Composited in the order given
+    overlays: Vec<PathBuf>,
+    no_overlay: bool,
+    format: OutputFormat,
+    format_options: FormatOptions,
+    hold: StillHold,
+    generator: Option<TestPattern>,
+    leader: Option<LeaderSpec>,
+    every_nth: Option<u32>,
+    qc_sample: Option<u32>,
+    name: Option<String>,
+    show: Option<String>,
+    notes: Option<String>,
+}
+
+fn parse_resolution(value: &str) -> Result<Resolution> {
+    match value.to_lowercase().as_str() {
+        "2k" => Ok(Resolution::K2),
+        "4k" => Ok(Resolution::K4),
+        "6k" => Ok(Resolution::K6),
+        "hd" | "1080p" => Ok(Resolution::Hd),
+        "uhd" | "2160p" => Ok(Resolution::Uhd),
+        "dci4k" | "dci-4k" => Ok(Resolution::Dci4k),
+        _ => Err(anyhow!("Unknown resolution '{}'", value)),
+    }
+}
+
+fn parse_fit(value: &str) -> Result<FitMode> {
+    match value.to_lowercase().as_str() {
+        "pad" | "letterbox" => Ok(FitMode::Pad),
+        "crop" => Ok(FitMode::Crop),
+        "stretch" => Ok(FitMode::Stretch),
+        _ => Err(anyhow!("Unknown fit mode '{}'", value)),
+    }
+}
+
+fn parse_format(value: &str) -> Result<OutputFormat> {
+    match value.to_lowercase().as_str() {
+        "png" => Ok(OutputFormat::PngSequence),
+        "exr" => Ok(OutputFormat::ExrSequence),
+        "dpx" => Ok(OutputFormat::DpxSequence),
+        "jpeg" | "jpg" => Ok(OutputFormat::JpegSequence),
+        "prores-422" => Ok(OutputFormat::MovProRes422),
+        "prores-4444" => Ok(OutputFormat::MovProRes4444),
+        "dnxhr-mov" => Ok(OutputFormat::MovDnxhr),
+        "dnxhr-mxf" => Ok(OutputFormat::MxfDnxhr),
+        "h264" | "mp4" => Ok(OutputFormat::Mp4H264),
+        "hevc" | "h265" => Ok(OutputFormat::Mp4Hevc),
+        "av1" => Ok(OutputFormat::Mp4Av1Svt),
+        "av1-aom" => Ok(OutputFormat::Mp4Av1Aom),
+        "webm" | "vp9" => Ok(OutputFormat::WebmVp9),
+        "xdcam" => Ok(OutputFormat::MxfXdcamHd50),
+        "avc-intra" => Ok(OutputFormat::MxfAvcIntra100),
+        "imf-j2k" => Ok(OutputFormat::ImfJpeg2000),
+        "imf-prores" => Ok(OutputFormat::ImfProRes),
+        _ => Err(anyhow!("Unknown format '{}'", value)),
+    }
+}
+
+fn parse_dnxhr_profile(value: &str) -> Result<DnxhrProfile> {
+    match value.to_lowercase().as_str() {
+        "lb" => Ok(DnxhrProfile::Lb),
+        "sq" => Ok(DnxhrProfile::Sq),
+        "hq" => Ok(DnxhrProfile::Hq),
+        "444" => Ok(DnxhrProfile::Dnx444),
+        _ => Err(anyhow!("Unknown DNxHR profile '{}'", value)),
+    }
+}
+
+fn parse_pattern(value: &str) -> Result<PatternKind> {
+    match value.to_lowercase().as_str() {
+        "bars" => Ok(PatternKind::SmpteHdBars),
+        "testpattern" => Ok(PatternKind::TestSrc2),
+        _ => Err(anyhow!("Unknown pattern '{}'", value)),
+    }
+}
+
+fn parse_positive(flag: &str, value: &str) -> Result<f64> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|number| *number > 0.0)
+        .ok_or_else(|| anyhow!("{} needs a positive number, got '{}'", flag, value))
+}
+
+fn parse_args(args: &[String]) -> Result<CliArgs> {
+    let mut input = None;
+    let mut output_dir = None;
+    let mut resolution = Resolution::K6;
+    let mut fit = FitMode::Pad;
+    let mut overlays = Vec::new();
+    let mut no_overlay = false;
+    let mut format = OutputFormat::PngSequence;
+    let mut format_options = FormatOptions::default();
+    let mut hold = StillHold::default();
+    let mut generate = None;
+    let mut leader = None;
+    let mut every_nth = None;
+    let mut qc_sample = None;
+    let mut pattern = TestPattern::default();
+    let mut name = None;
+    let mut show = None;
+    let mut notes = None;
+
+    let mut rate = None;
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let mut value = || args.next().ok_or_else(|| anyhow!("{} needs a value", flag));
+        match flag.as_str() {
+            "--cli" => {}
+            "--input" => input = Some(PathBuf::from(value()?)),
+            "--output-dir" => output_dir = Some(PathBuf::from(value()?)),
+            "--resolution" => resolution = parse_resolution(value()?)?,
+            "--fit" => fit = parse_fit(value()?)?,
+            "--overlay" => overlays.push(PathBuf::from(value()?)),
+            "--no-overlay" => no_overlay = true,
+            "--format" => format = parse_format(value()?)?,
+            "--dnxhr" => format_options.dnxhr_profile = parse_dnxhr_profile(value()?)?,
+            "--hold" => hold.seconds = parse_positive(flag, value()?)?,
+            "--crf" => {
+                rate = Some(RateControl::Crf(
+                    value()?
+                        .parse::<u8>()
+                        .ok()
+                        .filter(|crf| *crf <= 63)
+                        .ok_or_else(|| anyhow!("--crf needs a number from 0 to 63"))?,
+                ))
+            }
+            "--bitrate" => {
+                rate = Some(RateControl::BitrateMbps(
+                    parse_positive(flag, value()?)?.round() as u32,
+                ))
+            }
+            "--av1-speed" => {
+                format_options.av1_speed = match value()?.to_lowercase().as_str() {
+                    "quality" => Av1Speed::Quality,
+                    "balanced" => Av1Speed::Balanced,
+                    "fast" => Av1Speed::Fast,
+                    other => return Err(anyhow!("Unknown AV1 speed '{}'", other)),
+                }
+            }
+            "--hevc-8bit" => format_options.hevc_profile = HevcProfile::Main,
+            "--review-mp4" => format_options.review_mp4 = true,
+            "--review-webm" => format_options.review_webm = true,
+            "--every-nth" => {
+                every_nth = Some(
+                    value()?
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|n| *n > 0)
+                        .ok_or_else(|| anyhow!("--every-nth needs a whole number above 0"))?,
+                )
+            }
+            "--qc-sample" => {
+                qc_sample = Some(
+                    value()?
+                        .parse::<u32>()
+                        .map_err(|_| anyhow!("--qc-sample needs a whole number"))?,
+                )
+            }
+            "--leader" => {
+                leader = Some(LeaderSpec {
+                    seconds: parse_positive(flag, value()?)?.round().max(3.0) as u32,
+                })
+            }
+            "--generate" => generate = Some(parse_pattern(value()?)?),
+            "--length" => pattern.seconds = parse_positive(flag, value()?)?,
+            "--no-tone" => pattern.tone = false,
+            "--fps" => hold.fps = parse_positive(flag, value()?)?,
+            "--name" => name = Some(value()?.clone()),
+            "--show" => show = Some(value()?.clone()),
+            "--notes" => notes = Some(value()?.clone()),
+            _ => return Err(anyhow!("Unknown argument '{}'", flag)),
+        }
+    }
+
+    // x264 and x265 stop at 51, so a job with either caps the shared --crf there
+    if let Some(RateControl::Crf(crf)) = rate {
+        let x26x = matches!(format, OutputFormat::Mp4H264 | OutputFormat::Mp4Hevc)
+            || (format.is_sequence() && format_options.review_mp4);
+        let (max, codecs) = if x26x {
+            (51, "H.264/HEVC")
+        } else {
+            (63, "AV1/VP9")
+        };
+        if crf > max {
+            return Err(anyhow!(
+                "--crf needs a number from 0 to {} for {}",
+                max,
+                codecs
+            ));
+        }
+    }
+
+    // One rate flag serves whichever codec --format picked
+    if let Some(rate) = rate {
+        format_options.h264_rate = rate;
+        format_options.hevc_rate = rate;
+        if let RateControl::Crf(crf) = rate {
+            format_options.av1_crf = crf;
+            format_options.vp9_crf = crf;
+        }
+    }
+
+    Ok(CliArgs {
+        input: match (input, generate) {
+            (Some(input), _) => input,
+            (None, Some(_)) => PathBuf::new(),
+            (None, None) => return Err(anyhow!("--input is required")),
+        },
+        output_dir: output_dir.ok_or_else(|| anyhow!("--output-dir is required"))?,
+        resolution,
+        fit,
+        overlays,
+        no_overlay,
+        format,
+        format_options,
+        generator: generate.map(|kind| {
+            let size = format
+                .frame_size()
+                .or(resolution.target_size())
+                .unwrap_or(pattern.size);
+            TestPattern {
+                kind,
+                size,
+                fps: hold.fps,
+                ..pattern
+            }
+        }),
+        leader,
+        every_nth,
+        qc_sample,
+        hold,
+        name,
+        show,
+        notes,
+    })
+}
+
+fn config_from(args: CliArgs, settings: &Settings) -> EncodingConfig {
+    let (ffmpeg_path, ffprobe_path) = find_ffmpeg();
+    let base_name = args.name.unwrap_or_else(|| match args.generator {
+        Some(pattern) => pattern.kind.file_tag().to_string(),
+        None => args
+            .input
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "video".to_string()),
+    });
+    let overlays = if args.no_overlay {
+        Vec::new()
+    } else if args.overlays.is_empty() {
+        vec![args.resolution.overlay_file(Path::new("assets"))]
+    } else {
+        args.overlays
+    };
+
+    EncodingConfig {
+        still_hold: is_still_image(&args.input).then_some(args.hold),
+        generator: args.generator,
+        leader: args.leader,
+        every_nth: args.every_nth,
+        qc_sample: args.qc_sample,
+        // Splitting over volumes is planned by the app's storage check
+        split: Vec::new(),
+        frame_window: None,
+        input_video: args.input,
+        overlays: overlays
+            .into_iter()
+            .map(|image| OverlaySpec {
+                image,
+                style: OverlayStyle::default(),
+            })
+            .collect(),
+        output_dir: args.output_dir,
+        ffmpeg_path,
+        ffprobe_path,
+        resolution: args.resolution,
+        fit: args.fit,
+        base_name: sanitize_base_name(&base_name),
+        subtitles: None,
+        audio_stems: None,
+        output_format: args.format,
+        format_options: args.format_options,
+        notes: args.notes.unwrap_or_default(),
+        naming: NamingRules::default(),
+        stall_timeout: Duration::from_secs(settings.stall_timeout_secs.max(10)),
+        first_frame_timeout: Duration::from_secs(settings.first_frame_timeout_secs.max(10)),
+        matte: None,
+        retime_fps: None,
+        inverse_telecine: false,
+        tolerate_errors: false,
+        pipe_command: None,
+        environment: settings.job_environment(),
+        show: args.show,
+        energy: settings.energy_rates(),
+        permissions: settings.output_permissions(),
+    }
+}
+
+// Windows release builds are GUI-subsystem, so output only shows when a parent console is attached
+#[cfg(windows)]
+fn attach_console() {
+    unsafe {
+        winapi::um::wincon::AttachConsole(winapi::um::wincon::ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
+
+// Runs one job without a window, printing progress to stderr. A non-zero exit
+// means the job failed; frames already written stay, so rerunning resumes.
+pub fn run(args: &[String]) -> Result<()> {
+    attach_console();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let args = parse_args(args).map_err(|e| anyhow!("{}\n\n{}", e, USAGE))?;
+
+    let settings = Settings::load();
+    set_probe_timeout(Duration::from_secs(settings.probe_timeout_secs));
+    let config = config_from(args, &settings);
+    // A generated leader has no input file
+    let input = config
+        .generator
+        .is_none()
+        .then_some((&config.input_video, "Input"));
+    let overlays = config
+        .overlays
+        .iter()
+        .map(|layer| (&layer.image, "Overlay"));
+    for (path, what) in input.into_iter().chain(overlays) {
+        if !path.is_file() {
+            return Err(anyhow!("{} {} not found", what, path.display()));
+        }
+    }
+    std::fs::create_dir_all(&config.output_dir)?;
+    FfmpegCapabilities::probe(&config.ffmpeg_path)
+        .and_then(|capabilities| check_job_support(&config, &capabilities))
+        .map_err(|e| anyhow!(user_message(&e)))?;
+
+    let (progress_sender, progress_receiver) = mpsc::channel();
+    // Nothing cancels a headless job short of killing the process
+    let cancel_receiver = mpsc::channel().1;
+    let worker = thread::spawn(move || run_encoding(&config, progress_sender, cancel_receiver));
+
+    let mut stderr = std::io::stderr();
+    let mut failure = None;
+    for (progress, frame, message) in progress_receiver {
+        if progress == -1.0 {
+            failure = Some(message);
+        } else if progress == -3.0 || progress == -4.0 {
+            let _ = writeln!(stderr, "\nframe {:06}: {}", frame, message);
+        } else if progress >= 0.0 {
+            let _ = write!(
+                stderr,
+                "\r{:5.1}% frame {:06} | {}",
+                progress, frame, message
+            );
+        }
+    }
+    let _ = writeln!(stderr);
+
+    worker
+        .join()
+        .map_err(|_| anyhow!("Encoding thread panicked"))?
+        .map_err(|e| anyhow!(user_message(&e)))?;
+    match failure {
+        Some(message) => Err(anyhow!(message)),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,125 @@
+use std::{
+    backtrace::Backtrace,
+    collections::VecDeque,
+    fs,
+    path::PathBuf,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::utils::app_data_dir;
+
+const LOG_CAPACITY: usize = 200;
+
+// State captured into the crash bundle if the app panics
+struct CrashContext {
+    recent_log: VecDeque<String>,
+    job_state: String,
+    ffmpeg_command: String,
+}
+
+static CONTEXT: Mutex<CrashContext> = Mutex::new(CrashContext {
+    recent_log: VecDeque::new(),
+    job_state: String::new(),
+    ffmpeg_command: String::new(),
+});
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+pub fn log(line: impl Into<String>) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        if context.recent_log.len() == LOG_CAPACITY {
+            context.recent_log.pop_front();
+        }
+        context
+            .recent_log
+            .push_back(format!("[{}] {}", unix_time(), line.into()));
+    }
+}
+
+pub fn set_job_state(state: impl Into<String>) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.job_state = state.into();
+    }
+}
+
+pub fn set_ffmpeg_command(command: impl Into<String>) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.ffmpeg_command = command.into();
+    }
+}
+
+pub fn crash_dir() -> PathBuf {
+    app_data_dir().join("crashes")
+}
+
+// Reports already shown once, so they don't prompt again on the next start
+pub fn seen_dir() -> PathBuf {
+    crash_dir().join("seen")
+}
+
+pub fn install_hook() {
+    let default_hook = std::panic::take_hook();
+
+    std::panic::set_hook(Box::new(move |info| {
+        let backtrace = Backtrace::force_capture();
+        let mut report = format!(
+            "Delivery Encoder crash report\nVersion: {}\nTime: {}\nPanic: {}\n\nBacktrace:\n{}\n",
+            env!("CARGO_PKG_VERSION"),
+            unix_time(),
+            info,
+            backtrace
+        );
+
+        // try_lock: the panic may have happened while the context was held
+        if let Ok(context) = CONTEXT.try_lock() {
+            report.push_str(&format!(
+                "\nJob state: {}\nFFmpeg command: {}\n\nRecent log:\n",
+                context.job_state, context.ffmpeg_command
+            ));
+            for line in &context.recent_log {
+                report.push_str(line);
+                report.push('\n');
+            }
+        }
+
+        let dir = crash_dir();
+        if fs::create_dir_all(&dir).is_ok() {
+            let _ = fs::write(dir.join(format!("crash-{}.txt", unix_time())), report);
+        }
+
+        default_hook(info);
+    }));
+}
+
+// Crash bundles written by previous runs that the operator has not looked at yet
+pub fn pending_reports() -> Vec<PathBuf> {
+    let mut reports: Vec<PathBuf> = fs::read_dir(crash_dir())
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "txt"))
+                .collect()
+        })
+        .unwrap_or_default();
+    reports.sort();
+    reports
+}
+
+pub fn mark_reports_seen(reports: &[PathBuf]) {
+    let seen = seen_dir();
+    if fs::create_dir_all(&seen).is_err() {
+        return;
+    }
+    for report in reports {
+        if let Some(name) = report.file_name() {
+            let _ = fs::rename(report, seen.join(name));
+        }
+    }
+}
@@ -1,147 +1,1357 @@
 use anyhow::{anyhow, Result};
 use std::{
-    path::PathBuf,
+    ffi::OsString,
+    path::{Path, PathBuf},
     process::{Command, Stdio},
-    sync::mpsc::{Receiver, Sender},
+    sync::mpsc::{self, Receiver, Sender},
     thread,
     time::{Duration, Instant},
 };
 
 use crate::{
-    models::Resolution,
-    utils::{get_duration, get_frame_rate, get_resolution},
+    audio::export_stems,
+    crash,
+    energy::{self, EnergyMeter, EnergyRates},
+    errors::{EncoderError, IoSide},
+    finder,
+    models::{
+        AlphaMode, FieldOrder, FitMode, FormatOptions, LeaderSpec, MatteSpec, NamingRules,
+        OutputFormat, OutputKind, OverlaySpec, PictureTags, Resolution, StemMode, StillHold,
+        SubtitleSpec, TestPattern,
+    },
+    packaging::{write_imf_package, ImfPackage},
+    permissions::OutputPermissions,
+    provenance,
+    resources::ResourceMonitor,
+    split::{SplitManifest, SplitPart},
+    staging::JobStaging,
+    timecode::TimecodeRate,
+    trash::move_to_trash,
+    utils::{
+        escape_filter_value, escape_image2_pattern, get_audio_layout, get_duration, get_frame_rate,
+        get_picture_tags, get_resolution, get_start_time, is_job_file, output_frame_number,
+        FfmpegCapabilities, JobEnvironment,
+    },
 };
 
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 
+/// Everything one job needs; the gui builds it from the form and station settings.
+#[derive(Clone)]
 pub struct EncodingConfig {
     pub input_video: PathBuf,
-    pub overlay_image: PathBuf,
+    // Set when the input is a single picture to hold instead of a movie
+    pub still_hold: Option<StillHold>,
+    // Bars or a test pattern to encode instead of input_video
+    pub generator: Option<TestPattern>,
+    // Countdown written before the program; not used with generated or held sources
+    pub leader: Option<LeaderSpec>,
+    // Sequences keep only every Nth frame, each named after its delivery frame number
+    pub every_nth: Option<u32>,
+    // Random frames, besides first/middle/last, copied to <base>_qc for the supervisor
+    pub qc_sample: Option<u32>,
+    // Sequence parts after the first when output_dir's volume is too small; frames
+    // before the first part stay in output_dir. Not used with leaders, holds, bars,
+    // every_nth or a pipe.
+    pub split: Vec<SplitPart>,
+    // Delivery frames [first, end) this run writes; set per volume of a split
+    pub frame_window: Option<(u32, u32)>,
+    // Burn-in layers composited in order, e.g. the show overlay, a client logo and
+    // legal text; empty delivers the picture clean
+    pub overlays: Vec<OverlaySpec>,
     pub output_dir: PathBuf,
     pub ffmpeg_path: PathBuf,
     pub ffprobe_path: PathBuf,
     pub resolution: Resolution,
+    // How a source of another aspect fills the resolution's raster
+    pub fit: FitMode,
     pub base_name: String,
+    pub subtitles: Option<SubtitleSpec>,
+    pub audio_stems: Option<StemMode>,
+    pub output_format: OutputFormat,
+    pub format_options: FormatOptions,
+    // Operator notes, kept in the job ledger next to the cost
+    pub notes: String,
+    pub naming: NamingRules,
+    pub stall_timeout: Duration,
+    // How long ffmpeg may take to produce its first frame before the job fails
+    pub first_frame_timeout: Duration,
+    pub matte: Option<MatteSpec>,
+    // Converts to this rate by dropping or repeating frames, keeping the duration
+    pub retime_fps: Option<f64>,
+    // Removes 3:2 pull-down, turning 29.97 telecined video back into 23.976 progressive
+    pub inverse_telecine: bool,
+    // Keep going past corrupt frames in the master and list them afterwards
+    pub tolerate_errors: bool,
+    // Expert mode: sequence frames go to this shell command's stdin instead of disk
+    pub pipe_command: Option<String>,
+    pub environment: JobEnvironment,
+    // Show the job's energy and cost are booked against
+    pub show: Option<String>,
+    pub energy: EnergyRates,
+    pub permissions: OutputPermissions,
 }
 
+// Decimation keeps four frames out of every five
+pub fn inverse_telecine_rate(frame_rate: f64) -> f64 {
+    frame_rate * 4.0 / 5.0
+}
+
+// Rate of the frames run_encoding writes, which frame numbers in the output refer to
+pub fn output_frame_rate(config: &EncodingConfig) -> Result<f64> {
+    if let Some(pattern) = config.generator {
+        return Ok(pattern.fps);
+    }
+    if let Some(hold) = config.still_hold {
+        return Ok(hold.fps);
+    }
+    if let Some(fps) = config.retime_fps {
+        return Ok(fps);
+    }
+    let frame_rate = get_frame_rate(&config.input_video, &config.ffprobe_path)?;
+    Ok(if config.inverse_telecine {
+        inverse_telecine_rate(frame_rate)
+    } else {
+        frame_rate
+    })
+}
+
+// Frames of countdown in front of the program; delivery frame n shows source frame
+// n minus this
+pub fn leader_frames(config: &EncodingConfig, frame_rate: f64) -> u32 {
+    config
+        .leader
+        .filter(|_| config.generator.is_none() && config.still_hold.is_none())
+        .map_or(0, |leader| leader.frames(frame_rate))
+}
+
+// Seeking restarts timestamps at zero, so subtitles are shifted back
+// onto the source timeline before burning in
+pub fn subtitle_filter(subtitles: Option<&SubtitleSpec>, start_time_str: &str) -> Result<String> {
+    let Some(spec) = subtitles else {
+        return Ok(String::new());
+    };
+
+    let path = spec
+        .path
+        .to_str()
+        .ok_or_else(|| anyhow!("Invalid subtitle path"))?;
+    let path = if cfg!(windows) {
+        path.replace('\\', "/")
+    } else {
+        path.to_string()
+    };
+
+    let mut filter = format!(
+        ",setpts=PTS+{}/TB,subtitles=filename={}",
+        start_time_str,
+        escape_filter_value(&path)
+    );
+    let style = spec.force_style();
+    if !style.is_empty() {
+        filter.push_str(&format!(":force_style={}", escape_filter_value(&style)));
+    }
+    Ok(filter)
+}
+
+const SEEK_PREROLL_SECS: f64 = 2.0;
+
+// Frame-accurate start: a coarse input seek a little early keeps decoding short, then
+// a trim on the untouched source timestamps half a frame before the target, so
+// rounding can never land on a neighbouring frame
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameSeek {
+    pub frame: u32,
+    pub frame_rate: f64,
+    // Timestamp of the source's first frame, which -copyts keeps in the graph
+    pub stream_start: f64,
+}
+
+impl FrameSeek {
+    fn rate(&self) -> TimecodeRate {
+        TimecodeRate::from_fps(self.frame_rate)
+    }
+
+    fn target_secs(&self) -> f64 {
+        self.rate()
+            .frames_to_secs((self.frame as f64 - 0.5).max(0.0))
+    }
+
+    // Output time of the first frame, for lining up subtitles
+    pub fn start_time_str(&self) -> String {
+        format!("{:.6}", self.rate().frames_to_secs(self.frame as f64))
+    }
+
+    // Goes before the source's -i
+    pub fn input_args(&self) -> [String; 4] {
+        let coarse = (self.target_secs() - SEEK_PREROLL_SECS).max(0.0);
+        [
+            "-copyts".to_string(),
+            "-noaccurate_seek".to_string(),
+            "-ss".to_string(),
+            format!("{:.6}", coarse),
+        ]
+    }
+
+    // First in the source's filter chain
+    pub fn trim_filter(&self) -> String {
+        format!(
+            "trim=start={:.6},setpts=PTS-STARTPTS",
+            self.stream_start + self.target_secs()
+        )
+    }
+}
+
+// Source on input 0 and overlay on input 1, composited into an [out] pad
+pub struct OverlayGraph<'a> {
+    pub fixed_size: Option<(u32, u32)>,
+    pub source_size: (u32, u32),
+    // How the source goes onto fixed_size
+    pub fit: FitMode,
+    pub flags: &'a str,
+    // Inputs 1.. in order; empty passes the picture through
+    pub overlays: &'a [OverlaySpec],
+    pub matte: Option<MatteSpec>,
+    pub retime_fps: Option<f64>,
+    pub inverse_telecine: bool,
+    pub seek: Option<FrameSeek>,
+    // Delivery frame the graph's first output frame lands on, for frame-ranged layers
+    pub first_frame: u32,
+    pub subtitle_filter: &'a str,
+    // Chain applied to the composite before format conversion, e.g. a log curve
+    pub output_filters: &'a str,
+    pub pix_fmt: &'a str,
+}
+
+impl OverlayGraph<'_> {
+    pub fn build(&self) -> String {
+        let flags = self.flags;
+        let (width, height) = self.fixed_size.unwrap_or(self.source_size);
+        let frame_offset = self.first_frame;
+
+        let mut graph = String::new();
+
+        // Mattes frame the source picture, so they go on before any padding
+        // Pull-down has to come out while the fields are still in their original cadence
+        let source_filters: Vec<String> = self
+            .inverse_telecine
+            .then(|| "fieldmatch,yadif=deint=interlaced,decimate".to_string())
+            .into_iter()
+            // Anchored to the source start so a resumed run keeps the same frame grid
+            .chain(self.retime_fps.map(|fps| match self.seek {
+                Some(seek) => format!("fps={:.3}:start_time={:.6}", fps, seek.stream_start),
+                None => format!("fps={:.3}", fps),
+            }))
+            .chain(self.seek.map(|seek| seek.trim_filter()))
+            .chain(
+                self.matte
+                    .and_then(|matte| matte.drawbox_filter(self.source_size, frame_offset)),
+            )
+            .collect();
+        let source = if source_filters.is_empty() {
+            "[0:v]"
+        } else {
+            graph.push_str(&format!("[0:v]{}[src]; ", source_filters.join(",")));
+            "[src]"
+        };
+
+        let picture = match self.fixed_size {
+            Some(size) => {
+                graph.push_str(&format!(
+                    "{}{}[vid]; ",
+                    source,
+                    self.fit.filter(size, flags, "black")
+                ));
+                "[vid]"
+            }
+            None => source,
+        };
+
+        // Each layer goes over the composite of those before it. Subtitles and
+        // output filters come last, on top of every layer.
+        let mut composite = picture.to_string();
+        for (index, layer) in self.overlays.iter().enumerate() {
+            let input = index + 1;
+            let enable = layer
+                .style
+                .frames
+                .map(|frames| frames.enable_option(frame_offset))
+                .unwrap_or_default();
+
+            // Scaling straight alpha bleeds the hidden colour of transparent pixels into
+            // the edges, so straight assets are premultiplied first
+            let premultiply = match layer.style.alpha {
+                AlphaMode::Straight => "premultiply=inplace=1,",
+                AlphaMode::Premultiplied => "",
+            };
+            // On a preset the overlay is fitted like the picture, so a square one
+            // still lines up with a pillarboxed or cropped square master
+            let overlay_fit = match self.fixed_size {
+                Some(size) => self.fit.filter(size, flags, "black@0"),
+                None => format!("scale={}:{}:flags={}", width, height, flags),
+            };
+            graph.push_str(&format!(
+                "[{}:v]format=rgba64le,{}{}[ovr{}]; ",
+                input, premultiply, overlay_fit, input
+            ));
+
+            match layer.style.blend.blend_filter_mode() {
+                None => graph.push_str(&format!(
+                    "{}[ovr{i}]overlay=0:0:format=rgb:alpha=premultiplied{}[comp{i}]; ",
+                    composite,
+                    enable,
+                    i = input
+                )),
+                // blend ignores alpha, so the overlay is flattened onto the mode's neutral
+                // colour. The colour source never ends, so it is painted over a copy of the
+                // picture to give the plate the picture's length; the single-frame overlay
+                // then repeats over it, and blend stops with the picture.
+                Some(mode) => graph.push_str(&format!(
+                    "{composite}split[base{i}][ref{i}]; \
+                     color=c={neutral}:s={w}x{h},format=rgba64le[fill{i}]; \
+                     [ref{i}][fill{i}]overlay=0:0:format=rgb,format=rgba64le[neutral{i}]; \
+                     [neutral{i}][ovr{i}]overlay=0:0:format=rgb:alpha=premultiplied,format=gbrp16le[flat{i}]; \
+                     [base{i}]format=gbrp16le[under{i}]; \
+                     [under{i}][flat{i}]blend=all_mode={mode}:shortest=1{enable}[comp{i}]; ",
+                    neutral = layer.style.blend.neutral_color(),
+                    w = width,
+                    h = height,
+                    i = input,
+                    composite = composite,
+                    mode = mode,
+                    enable = enable
+                )),
+            }
+            composite = format!("[comp{}]", input);
+        }
+
+        graph.push_str(&format!(
+            "{}null{}{},format={}[out]",
+            composite, self.subtitle_filter, self.output_filters, self.pix_fmt
+        ));
+
+        graph
+    }
+}
+
+// Puts the countdown in front of an OverlayGraph's [out]. With `source_audio` set
+// the movie's audio goes to [aout], pop first and the source's first track after.
+fn prepend_leader(
+    graph: &str,
+    leader: LeaderSpec,
+    size: (u32, u32),
+    fps: f64,
+    pix_fmt: &str,
+    source_audio: Option<bool>,
+) -> String {
+    let program = graph.strip_suffix("[out]").unwrap_or(graph);
+    let mut graph = format!(
+        "{}[prog]; [prog]setsar=1[progsq]; {}[ldr]; [ldr][progsq]concat=n=2:v=1:a=0[out]",
+        program,
+        leader.video_filter(size, fps, pix_fmt)
+    );
+    match source_audio {
+        Some(true) => graph.push_str(&format!(
+            "; {}[pop]; [0:a:0]aresample=48000[srca]; [pop][srca]concat=n=2:v=0:a=1[aout]",
+            leader.pop_filter(fps)
+        )),
+        Some(false) => graph.push_str(&format!("; {}[aout]", leader.pop_filter(fps))),
+        None => {}
+    }
+    graph
+}
+
+const DOWNSTREAM_LOG: &str = "downstream.log";
+
+// Runs through the platform shell so pipelines and quoting work as typed
+fn spawn_downstream(
+    command: &str,
+    config: &EncodingConfig,
+    staging_dir: &Path,
+) -> Result<std::process::Child> {
+    let log = std::fs::File::create(staging_dir.join(DOWNSTREAM_LOG))?;
+    let mut shell = if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    };
+    config.environment.apply(&mut shell);
+    shell
+        .current_dir(&config.output_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::from(log));
+
+    let child = {
+        #[cfg(windows)]
+        {
+            shell.creation_flags(0x08000000).spawn()
+        }
+        #[cfg(not(windows))]
+        {
+            shell.spawn()
+        }
+    };
+    child.map_err(|e| anyhow!("Could not start pipe command: {}", e))
+}
+
+// Picks up stderr lines written since the last poll and pins them to `frame`
+fn collect_new_errors(log: &Path, read: &mut usize, frame: u32, errors: &mut Vec<(u32, String)>) {
+    let Ok(stderr) = std::fs::read_to_string(log) else {
+        return;
+    };
+    if let Some(new_output) = stderr.get(*read..) {
+        errors.extend(
+            new_output
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(|line| (frame, line.to_string())),
+        );
+    }
+    *read = stderr.len();
+}
+
+// Written next to the delivery so QC can check the affected frames by eye
+fn write_error_report(config: &EncodingConfig, errors: &[(u32, String)]) -> Result<()> {
+    let path = config
+        .output_dir
+        .join(format!("{}_decode_errors.txt", config.base_name));
+    let mut report = format!(
+        "Decoder errors in {} (frame numbers are approximate)\n",
+        config.input_video.display()
+    );
+    for (frame, message) in errors {
+        report.push_str(&format!("{:06}  {}\n", frame, message));
+    }
+    std::fs::write(&path, report).map_err(|e| EncoderError::from_io(&e, &path, IoSide::Output))?;
+
+    crash::log(format!(
+        "{} decoder errors tolerated, listed in {}",
+        errors.len(),
+        path.display()
+    ));
+    Ok(())
+}
+
+// A frame a quarter of the size of most of its neighbours is nearly always a
+// truncated write rather than a dark shot
+const SUSPECT_SIZE_RATIO: u64 = 4;
+// Frames either side a frame's size is judged against
+const SIZE_NEIGHBOURS: u32 = 4;
+
+// Sizes sequence frames as ffmpeg finishes them, so a corrupt write is caught
+// during the encode rather than at client QC
+struct FrameSizeWatch {
+    dir: PathBuf,
+    base_name: String,
+    extension: String,
+    sizes: std::collections::HashMap<u32, u64>,
+    // Next frame to be judged
+    next: u32,
+    flagged: u32,
+}
+
+impl FrameSizeWatch {
+    fn new(config: &EncodingConfig, start_frame: u32) -> Self {
+        Self {
+            dir: config.output_dir.clone(),
+            base_name: config.base_name.clone(),
+            extension: config.naming.extension(config.output_format),
+            sizes: std::collections::HashMap::new(),
+            next: start_frame,
+            flagged: 0,
+        }
+    }
+
+    fn size(&mut self, frame: u32) -> Option<u64> {
+        if let Some(size) = self.sizes.get(&frame) {
+            return Some(*size);
+        }
+        let path = self.dir.join(format!(
+            "{}-{:06}.{}",
+            self.base_name, frame, self.extension
+        ));
+        let size = std::fs::metadata(path).ok()?.len();
+        self.sizes.insert(frame, size);
+        Some(size)
+    }
+
+    // Judges frames before `until` against those either side, reading nothing
+    // from `complete` on. Frames without enough neighbours on disk are passed.
+    fn judge(&mut self, until: u32, complete: u32) -> Vec<u32> {
+        let mut suspects = Vec::new();
+        while self.next < until {
+            let frame = self.next;
+            self.next += 1;
+            let Some(size) = self.size(frame) else {
+                continue;
+            };
+            let mut around: Vec<u64> = (frame.saturating_sub(SIZE_NEIGHBOURS)
+                ..(frame + SIZE_NEIGHBOURS + 1).min(complete))
+                .filter(|&f| f != frame)
+                .filter_map(|f| self.size(f))
+                .collect();
+            if around.len() < SIZE_NEIGHBOURS as usize {
+                continue;
+            }
+            // The lower median, so a cut to black isn't mistaken for a bad frame
+            around.sort_unstable();
+            if size * SUSPECT_SIZE_RATIO < around[(around.len() - 1) / 2] {
+                suspects.push(frame);
+            }
+        }
+        let oldest = self.next.saturating_sub(SIZE_NEIGHBOURS);
+        self.sizes.retain(|&f, _| f >= oldest);
+        self.flagged += suspects.len() as u32;
+        suspects
+    }
+
+    // `written` counts ffmpeg's frames so far, the last of which may still be
+    // landing, so only frames with all their later neighbours complete are judged
+    fn check(&mut self, written: u32) -> Vec<u32> {
+        let complete = written.saturating_sub(1);
+        self.judge(complete.saturating_sub(SIZE_NEIGHBOURS), complete)
+    }
+
+    fn finish(&mut self, written: u32) -> Vec<u32> {
+        self.judge(written, written)
+    }
+}
+
+// Encoded from the finished delivery, so a resumed sequence still gets one
+// continuous file. A sequence takes the source's audio when it has any; a
+// movie brings its own.
+fn write_review_copy(
+    config: &EncodingConfig,
+    plan: &EncodePlan,
+    review: OutputFormat,
+) -> Result<PathBuf> {
+    let path = config.output_dir.join(format!(
+        "{}_review.{}",
+        config.base_name,
+        review.extension()
+    ));
+
+    let mut command = Command::new(&config.ffmpeg_path);
+    config.environment.apply(&mut command);
+    command.args(["-v", "error"]);
+    if !config.output_format.is_sequence() {
+        command
+            .arg("-i")
+            .arg(&plan.output_path)
+            .args(["-map", "0:a?"]);
+    } else {
+        command
+            .arg("-framerate")
+            .arg(format!("{:.3}", plan.frame_rate))
+            .args(["-start_number", "0", "-i"])
+            .arg(&plan.output_path);
+    }
+    if config.output_format.is_sequence()
+        && config.generator.is_none()
+        && config.still_hold.is_none()
+    {
+        if let Some(leader) = config.leader {
+            command.args(["-itsoffset", &leader.seconds.to_string()]);
+        }
+        command
+            .arg("-i")
+            .arg(&config.input_video)
+            .args(["-map", "1:a?", "-shortest"]);
+    }
+    command
+        .args(["-map", "0:v"])
+        .args(review.codec_args())
+        .args(config.format_options.args(review))
+        .arg("-y")
+        .arg(&path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    let output = {
+        #[cfg(windows)]
+        {
+            command.creation_flags(0x08000000).output()
+        }
+        #[cfg(not(windows))]
+        {
+            command.output()
+        }
+    }
+    .map_err(|e| EncoderError::from_io(&e, &config.output_dir, IoSide::Output))?;
+
+    if !output.status.success() {
+        return Err(EncoderError::EncoderFailed {
+            stderr: format!(
+                "review copy: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            resumes: false,
+        }
+        .into());
+    }
+    Ok(path)
+}
+
+// Only this run's files; other shots in the same folder keep their own ownership
+fn apply_output_permissions(config: &EncodingConfig) -> Result<()> {
+    if config.permissions.is_empty() {
+        return Ok(());
+    }
+    config.permissions.apply(&config.output_dir)?;
+    let extension = config.naming.extension(config.output_format);
+    for entry in std::fs::read_dir(&config.output_dir)?.flatten() {
+        if is_job_file(
+            &config.base_name,
+            &extension,
+            &entry.file_name().to_string_lossy(),
+        ) {
+            config.permissions.apply_tree(&entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+// Everything ffmpeg is started with, worked out from the config and whatever a
+// previous run left on disk. Script export builds from the same plan so the
+// commands match what the app would run.
+pub struct EncodePlan {
+    pub duration: f64,
+    pub frame_rate: f64,
+    pub source_size: (u32, u32),
+    pub fixed_size: Option<(u32, u32)>,
+    pub total_frames: u32,
+    pub start_frame: u32,
+    // A rerun picks up after the frames this one leaves on disk
+    pub resumable: bool,
+    pub seek: Option<FrameSeek>,
+    pub output_path: PathBuf,
+    pub filter_complex: String,
+    pub every_nth: Option<u32>,
+    // The graph writes the movie's audio to [aout], for leaders with a pop
+    pub graph_audio: bool,
+    // Written explicitly on movie outputs; None for image sequences
+    pub picture: Option<OutputPicture>,
+}
+
+// Field order and aspect a movie output is tagged with, and checked against afterwards
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutputPicture {
+    pub field_order: FieldOrder,
+    pub sample_aspect: (u32, u32),
+    pub display_aspect: (u32, u32),
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a.max(1)
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+fn reduced((num, den): (u64, u64)) -> (u32, u32) {
+    let divisor = gcd(num, den);
+    ((num / divisor) as u32, (den / divisor) as u32)
+}
+
+impl OutputPicture {
+    // Broadcast rasters are square-pixel by spec; everything else keeps the source's
+    // pixel aspect. Fields only survive when the source's frames go through
+    // untouched: scaling mixes them, and dropped, merged or leader frames break
+    // the cadence.
+    fn plan(
+        config: &EncodingConfig,
+        fixed_size: Option<(u32, u32)>,
+        source_size: (u32, u32),
+        leader: bool,
+    ) -> Result<Self> {
+        let source = match config.generator {
+            Some(_) => PictureTags::default(),
+            None => get_picture_tags(&config.input_video, &config.ffprobe_path)?,
+        };
+        let field_order = if config.inverse_telecine
+            || config.retime_fps.is_some()
+            || fixed_size.is_some()
+            || leader
+        {
+            FieldOrder::Progressive
+        } else {
+            source.field_order.unwrap_or_default()
+        };
+        let ((width, height), sample_aspect) = match fixed_size {
+            Some(size) => (size, (1, 1)),
+            None => (source_size, source.sample_aspect.unwrap_or((1, 1))),
+        };
+        let display_aspect = reduced((
+            width as u64 * sample_aspect.0 as u64,
+            height as u64 * sample_aspect.1 as u64,
+        ));
+        Ok(Self {
+            field_order,
+            sample_aspect: reduced((sample_aspect.0 as u64, sample_aspect.1 as u64)),
+            display_aspect,
+        })
+    }
+
+    fn args(&self) -> Vec<OsString> {
+        let mut args: Vec<OsString> =
+            vec!["-field_order".into(), self.field_order.as_ffmpeg().into()];
+        if self.field_order != FieldOrder::Progressive {
+            // Interlaced DCT and motion estimation; without them the encoder codes frames
+            args.extend(["-flags", "+ildct+ilme"].map(OsString::from));
+        }
+        args.push("-aspect".into());
+        args.push(format!("{}:{}", self.display_aspect.0, self.display_aspect.1).into());
+        args
+    }
+}
+
+// Reads the tags back from the finished file. Tags the container doesn't carry
+// are only logged; tags that came back different fail the job.
+fn verify_output_tags(
+    config: &EncodingConfig,
+    picture: &OutputPicture,
+    output: &Path,
+) -> Result<()> {
+    let tags = get_picture_tags(output, &config.ffprobe_path)?;
+    let format = config.output_format;
+    let mut problems = Vec::new();
+    let mut unreported = Vec::new();
+
+    match tags.field_order {
+        Some(order) if order != picture.field_order => problems.push(format!(
+            "field order {} (expected {})",
+            order.as_ffmpeg(),
+            picture.field_order.as_ffmpeg()
+        )),
+        Some(_) => {}
+        None => unreported.push("field order"),
+    }
+    match tags
+        .sample_aspect
+        .map(|sar| reduced((sar.0 as u64, sar.1 as u64)))
+    {
+        Some(sar) if sar != picture.sample_aspect => problems.push(format!(
+            "pixel aspect {}:{} (expected {}:{})",
+            sar.0, sar.1, picture.sample_aspect.0, picture.sample_aspect.1
+        )),
+        Some(_) => {}
+        None => unreported.push("pixel aspect"),
+    }
+    for (name, arg, value) in [
+        (
+            "colour primaries",
+            "-color_primaries",
+            &tags.color_primaries,
+        ),
+        ("transfer", "-color_trc", &tags.color_transfer),
+        ("matrix", "-colorspace", &tags.color_space),
+    ] {
+        // ffprobe names the RGB matrix after FFmpeg's planar order
+        let Some(expected) = format
+            .codec_arg(arg)
+            .map(|tag| if tag == "rgb" { "gbr" } else { tag })
+        else {
+            continue;
+        };
+        match value {
+            Some(value) if value != expected => {
+                problems.push(format!("{} {} (expected {})", name, value, expected))
+            }
+            Some(_) => {}
+            None => unreported.push(name),
+        }
+    }
+
+    if !unreported.is_empty() {
+        crash::log(format!(
+            "{} does not report {}; not checked",
+            output.display(),
+            unreported.join(", ")
+        ));
+    }
+    if !problems.is_empty() {
+        return Err(anyhow!(
+            "{} does not match its spec: {}",
+            output.display(),
+            problems.join("; ")
+        ));
+    }
+    Ok(())
+}
+
+// Frame numbers of this sequence already in the output folder, in no order
+fn written_frames(config: &EncodingConfig, extension: &str) -> Vec<u32> {
+    let Ok(entries) = std::fs::read_dir(&config.output_dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            output_frame_number(&config.base_name, extension, entry.file_name().to_str()?)
+        })
+        .collect()
+}
+
+// Highest frame number of an earlier partial run of this sequence, within the
+// run's frame window when it has one
+fn last_written_frame(config: &EncodingConfig, extension: &str) -> Option<u32> {
+    written_frames(config, extension)
+        .into_iter()
+        .filter(|frame| {
+            config
+                .frame_window
+                .is_none_or(|(first, end)| (first..end).contains(frame))
+        })
+        .max()
+}
+
+// Copies first, middle and last plus `count` random frames into <base>_qc, so a
+// supervisor can check a handful locally instead of scrubbing the SAN. The folder
+// is replaced each run so it never mixes samples of two encodes; the old one goes
+// to the trash like any other deleted output.
+fn write_qc_sample(config: &EncodingConfig, extension: &str, count: u32) -> Result<PathBuf> {
+    let mut frames = written_frames(config, extension);
+    frames.sort_unstable();
+    let qc_dir = config.output_dir.join(format!("{}_qc", config.base_name));
+    if qc_dir.exists() {
+        move_to_trash(std::slice::from_ref(&qc_dir))?;
+    }
+    std::fs::create_dir_all(&qc_dir)
+        .map_err(|e| EncoderError::from_io(&e, &qc_dir, IoSide::Output))?;
+    if frames.is_empty() {
+        return Ok(qc_dir);
+    }
+
+    let mut picked = vec![0, frames.len() / 2, frames.len() - 1];
+    let mut rest: Vec<usize> = (1..frames.len() - 1)
+        .filter(|&i| i != frames.len() / 2)
+        .collect();
+    for _ in 0..(count as usize).min(rest.len()) {
+        let mut bytes = [0u8; 8];
+        getrandom::getrandom(&mut bytes).map_err(|e| anyhow!("Failed to pick QC frames: {}", e))?;
+        let index = (u64::from_le_bytes(bytes) % rest.len() as u64) as usize;
+        picked.push(rest.swap_remove(index));
+    }
+    picked.sort_unstable();
+    picked.dedup();
+
+    for index in picked {
+        let name = format!("{}-{:06}.{}", config.base_name, frames[index], extension);
+        let target = qc_dir.join(&name);
+        std::fs::copy(config.output_dir.join(&name), &target)
+            .map_err(|e| EncoderError::from_io(&e, &target, IoSide::Output))?;
+    }
+    Ok(qc_dir)
+}
+
+/// Fails before anything is written when the build can't produce part of the job:
+/// the format, a companion review file, or a filter an option relies on.
+pub fn check_job_support(config: &EncodingConfig, capabilities: &FfmpegCapabilities) -> Result<()> {
+    let format = config.output_format;
+    capabilities.check(format)?;
+    if format.is_sequence() && config.format_options.review_mp4 {
+        capabilities.check(OutputFormat::Mp4H264)?;
+    }
+    if config.format_options.review_webm && format != OutputFormat::WebmVp9 {
+        capabilities.check(OutputFormat::WebmVp9)?;
+    }
+
+    let leader =
+        config.leader.is_some() && config.generator.is_none() && config.still_hold.is_none();
+    let filters = [
+        (leader, "drawtext", "The countdown leader"),
+        (
+            config.subtitles.is_some(),
+            "subtitles",
+            "Burnt-in subtitles",
+        ),
+    ];
+    for (_, filter, feature) in filters.iter().filter(|(used, ..)| *used) {
+        if !capabilities.has_filter(filter) {
+            return Err(EncoderError::UnsupportedFormat {
+                reason: format!("{} needs the {} filter", feature, filter),
+            }
+            .into());
+        }
+    }
+    Ok(())
+}
+
+// ffprobe rounds durations to the microsecond, which on a long source can leave
+// the product a hair past the last frame rather than on it
+fn frames_in(duration: f64, frame_rate: f64) -> u32 {
+    let frames = duration * frame_rate;
+    if (frames - frames.round()).abs() < 1e-3 {
+        frames.round() as u32
+    } else {
+        frames.ceil() as u32
+    }
+}
+
+// Kept in f64 until it is sent: an f32 percentage can't tell the last few
+// frames of a feature-length source apart
+fn percent_done(frame: u32, total_frames: u32) -> f64 {
+    if total_frames > 0 {
+        (frame as f64 / total_frames as f64 * 100.0).min(100.0)
+    } else {
+        0.0
+    }
+}
+
+fn eta_secs(elapsed: f64, percent: f64) -> Option<u64> {
+    (percent > 0.1).then(|| (elapsed * 100.0 / percent - elapsed).max(0.0) as u64)
+}
+
+// With `resume` set, a sequence carries on after the frames already on disk
+pub fn plan_encoding(config: &EncodingConfig, resume: bool) -> Result<EncodePlan> {
+    let duration = match (config.generator, config.still_hold) {
+        (Some(pattern), _) => pattern.seconds,
+        (None, Some(hold)) => hold.seconds,
+        (None, None) => get_duration(&config.input_video, &config.ffprobe_path)?,
+    };
+    let frame_rate = output_frame_rate(config)?;
+    let source_size = match config.generator {
+        Some(pattern) => pattern.size,
+        None => get_resolution(&config.input_video, &config.ffprobe_path)?,
+    };
+
+    let leader = config
+        .leader
+        .filter(|_| config.generator.is_none() && config.still_hold.is_none());
+    let leader_frames = leader_frames(config, frame_rate);
+    let every_nth = config
+        .every_nth
+        .filter(|n| *n > 1 && config.output_format.is_sequence());
+    let total_frames =
+        (frames_in(duration, frame_rate) + leader_frames).div_ceil(every_nth.unwrap_or(1));
+
+    let output_dir_str = config
+        .output_dir
+        .to_str()
+        .ok_or_else(|| anyhow!("Invalid output path"))?;
+    let format = config.output_format;
+    let extension = config.naming.extension(format);
+    let output_path = match format.kind() {
+        OutputKind::Sequence => {
+            let output_pattern = format!(
+                "{}-%06d.{}",
+                escape_image2_pattern(&config.base_name),
+                extension
+            );
+            PathBuf::from(escape_image2_pattern(output_dir_str)).join(&output_pattern)
+        }
+        OutputKind::Movie => config
+            .output_dir
+            .join(format!("{}.{}", config.base_name, extension)),
+    };
+
+    // Only image sequences on disk can resume; a movie file or pipe always starts over,
+    // and so do held stills and bars, which are a few seconds of one picture. A leader
+    // shifts every source frame, so those sequences are rewritten whole as well.
+    // One volume of a split starts at its own first frame.
+    let window_start = config.frame_window.map_or(0, |(first, _)| first);
+    let resumable = format.is_sequence()
+        && config.pipe_command.is_none()
+        && config.still_hold.is_none()
+        && config.generator.is_none()
+        && leader.is_none()
+        && every_nth.is_none();
+    let start_frame = if resume && resumable {
+        last_written_frame(config, &extension).map_or(window_start, |last| last.max(window_start))
+    } else {
+        window_start
+    };
+    let seek = if start_frame > 0 {
+        Some(FrameSeek {
+            frame: start_frame,
+            frame_rate,
+            stream_start: get_start_time(&config.input_video, &config.ffprobe_path)?,
+        })
+    } else {
+        None
+    };
+    let start_time_str = seek.map_or("0".to_string(), |seek| seek.start_time_str());
+
+    let fixed_size = format.frame_size().or(config.resolution.target_size());
+    let subtitle_filter = subtitle_filter(config.subtitles.as_ref(), &start_time_str)?;
+    let pix_fmt = config.format_options.pix_fmt(format);
+    let mut filter_complex = OverlayGraph {
+        fixed_size,
+        source_size,
+        fit: config.fit,
+        flags: config.resolution.filter_flags(),
+        overlays: &config.overlays,
+        matte: config.matte,
+        retime_fps: config.retime_fps,
+        inverse_telecine: config.inverse_telecine,
+        seek,
+        // Ranges are typed against the delivered numbering, which counts the leader
+        first_frame: start_frame + leader_frames,
+        subtitle_filter: &subtitle_filter,
+        output_filters: config.format_options.output_filters(format),
+        pix_fmt,
+    }
+    .build();
+    let mut graph_audio = false;
+    if let Some(leader) = leader {
+        let source_audio = if format.carries_audio() {
+            graph_audio = true;
+            Some(get_audio_layout(&config.input_video, &config.ffprobe_path)?.is_some())
+        } else {
+            None
+        };
+        filter_complex = prepend_leader(
+            &filter_complex,
+            leader,
+            fixed_size.unwrap_or(source_size),
+            frame_rate,
+            pix_fmt,
+            source_audio,
+        );
+    }
+
+    let picture = if format.is_sequence() {
+        None
+    } else {
+        Some(OutputPicture::plan(
+            config,
+            fixed_size,
+            source_size,
+            leader.is_some(),
+        )?)
+    };
+
+    // select drops the other frames; the kept ones get their delivery frame number as
+    // a timestamp in frame units, which -frame_pts then writes into the file name
+    if let Some(n) = every_nth {
+        let program = filter_complex
+            .strip_suffix("[out]")
+            .unwrap_or(&filter_complex);
+        filter_complex = format!(
+            "{},select=not(mod(n\\,{n})),settb=1/{fps:.3},setpts=N*{n}[out]",
+            program,
+            n = n,
+            fps = frame_rate
+        );
+    }
+
+    Ok(EncodePlan {
+        every_nth,
+        duration,
+        frame_rate,
+        source_size,
+        fixed_size,
+        total_frames,
+        start_frame,
+        resumable,
+        seek,
+        output_path,
+        filter_complex,
+        graph_audio,
+        picture,
+    })
+}
+
+impl EncodePlan {
+    // Arguments after the ffmpeg binary. Without a progress file ffmpeg prints its
+    // own stats line instead, which is what someone running a script wants to see.
+    pub fn args(&self, config: &EncodingConfig, progress_path: Option<&Path>) -> Vec<OsString> {
+        let format = config.output_format;
+        let mut args: Vec<OsString> = Vec::new();
+        if config.tolerate_errors {
+            // ffmpeg otherwise exits non-zero once two thirds of the packets failed
+            args.extend(["-err_detect", "ignore_err", "-max_error_rate", "1"].map(OsString::from));
+        }
+        if let Some(seek) = self.seek {
+            args.extend(seek.input_args().map(OsString::from));
+        }
+        if let Some(pattern) = config.generator {
+            args.extend(pattern.input_args().map(OsString::from));
+        } else {
+            if let Some(hold) = config.still_hold {
+                args.extend(hold.input_args().map(OsString::from));
+            }
+            args.push("-i".into());
+            args.push(config.input_video.clone().into());
+        }
+        for layer in &config.overlays {
+            args.push("-i".into());
+            args.push(layer.image.clone().into());
+        }
+        let tone = config.generator.and_then(|pattern| pattern.tone_args());
+        if let Some(tone) = &tone {
+            args.extend(tone.iter().map(OsString::from));
+        }
+        args.push("-filter_complex".into());
+        args.push(self.filter_complex.clone().into());
+        args.extend(["-map", "[out]"].map(OsString::from));
+        match format.kind() {
+            OutputKind::Sequence => {
+                args.extend(["-vsync", "0", "-start_number"].map(OsString::from));
+                args.push(self.start_frame.to_string().into());
+                if self.every_nth.is_some() {
+                    args.extend(["-frame_pts", "1"].map(OsString::from));
+                }
+                if let Some((_, end)) = config.frame_window {
+                    args.push("-frames:v".into());
+                    args.push(end.saturating_sub(self.start_frame).to_string().into());
+                }
+            }
+            OutputKind::Movie if format.carries_audio() => {
+                // The tone is the input after the overlays
+                let audio = if self.graph_audio {
+                    "[aout]".to_string()
+                } else if tone.is_some() {
+                    format!("{}:a", 1 + config.overlays.len())
+                } else {
+                    "0:a?".to_string()
+                };
+                args.push("-map".into());
+                args.push(audio.into());
+            }
+            OutputKind::Movie => {}
+        }
+        if let Some(progress_path) = progress_path {
+            args.push("-progress".into());
+            args.push(progress_path.into());
+        }
+        args.extend(format.codec_args().iter().map(OsString::from));
+        args.extend(
+            config
+                .format_options
+                .args(format)
+                .into_iter()
+                .map(OsString::from),
+        );
+        if let Some(picture) = &self.picture {
+            args.extend(picture.args());
+        }
+        if config.pipe_command.is_some() {
+            args.extend(["-f", "image2pipe", "-"].map(OsString::from));
+        } else {
+            args.push(self.output_path.clone().into());
+        }
+        args.push("-y".into());
+        if progress_path.is_some() {
+            args.push("-nostats".into());
+        }
+        args.extend(["-loglevel", "error"].map(OsString::from));
+        args
+    }
+}
+
+/// Runs the job to completion on the calling thread. Progress goes out as
+/// `(percent, frame, message)`: 100 when done, -1 on failure, -2 once a message on
+/// `cancel_receiver` has stopped ffmpeg (rerunning resumes from the last frame),
+/// -3 while output has stalled, and -4 with the frame number for each frame that
+/// came out far smaller than its neighbours.
 pub fn run_encoding(
     config: &EncodingConfig,
     progress_sender: Sender<(f32, u32, String)>,
     cancel_receiver: Receiver<()>,
 ) -> Result<()> {
-    let duration = get_duration(&config.input_video, &config.ffprobe_path)?;
-    let frame_rate = get_frame_rate(&config.input_video, &config.ffprobe_path)?;
-    let resolution = get_resolution(&config.input_video, &config.ffprobe_path)?;
-    let (width, height) = (resolution.0, resolution.1);
-
-    let total_frames = (duration * frame_rate).ceil() as u32;
-
-    let output_pattern = format!("{}-%06d.png", config.base_name);
-    let output_path = config.output_dir.join(&output_pattern);
-
-    let mut max_frame = 0;
-    let mut found_any = false;
-    if let Ok(entries) = std::fs::read_dir(&config.output_dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if let Some(file_name) = path.file_name().and_then(|s| s.to_str()) {
-                if file_name.starts_with(&config.base_name) && file_name.ends_with(".png") {
-                    let num_str = file_name
-                        .trim_start_matches(&config.base_name)
-                        .trim_start_matches('-')
-                        .trim_end_matches(".png");
-                    if let Ok(num) = num_str.parse::<u32>() {
-                        if num > max_frame {
-                            max_frame = num;
-                        }
-                        found_any = true;
+    if config.split.is_empty() || !config.output_format.is_sequence() {
+        return run_part(config, progress_sender, &cancel_receiver, false);
+    }
+    run_split(config, progress_sender, &cancel_receiver)
+}
+
+// One ffmpeg run per volume over its own frame range, recorded in a manifest in
+// output_dir first. Only the last run's 100 reaches the caller, and a pause or
+// failure stops the volumes after it; rerunning resumes each from its own frames.
+fn run_split(
+    config: &EncodingConfig,
+    progress_sender: Sender<(f32, u32, String)>,
+    cancel_receiver: &Receiver<()>,
+) -> Result<()> {
+    let mut parts = vec![SplitPart {
+        dir: config.output_dir.clone(),
+        first_frame: 0,
+    }];
+    parts.extend(config.split.iter().cloned());
+    let manifest = SplitManifest {
+        base_name: config.base_name.clone(),
+        extension: config.naming.extension(config.output_format),
+        total_frames: plan_encoding(config, false)?.total_frames,
+        parts,
+    };
+    manifest.write(&config.output_dir)?;
+    crash::log(format!(
+        "Splitting {} over {} volumes",
+        config.base_name,
+        manifest.parts.len()
+    ));
+
+    for (index, part) in manifest.parts.iter().enumerate() {
+        let last = index + 1 == manifest.parts.len();
+        let mut part_config = config.clone();
+        part_config.output_dir = part.dir.clone();
+        part_config.split = Vec::new();
+        part_config.frame_window = Some(manifest.frame_range(index));
+        // Steps that read the whole sequence back can't see it spread out
+        part_config.qc_sample = None;
+        part_config.format_options.review_mp4 = false;
+        part_config.format_options.review_webm = false;
+        if index > 0 {
+            part_config.audio_stems = None;
+        }
+        std::fs::create_dir_all(&part.dir)
+            .map_err(|e| EncoderError::from_io(&e, &part.dir, IoSide::Output))?;
+
+        let (sender, receiver) = mpsc::channel::<(f32, u32, String)>();
+        let forward = progress_sender.clone();
+        let volumes = manifest.parts.len();
+        let forwarder = thread::spawn(move || {
+            let mut finished = false;
+            for (progress, frame, message) in receiver {
+                if progress >= 100.0 {
+                    finished = true;
+                    if !last {
+                        continue;
                     }
+                    let message = format!("{} | Split over {} volumes", message, volumes);
+                    let _ = forward.send((progress, frame, message));
+                } else {
+                    let _ = forward.send((progress, frame, message));
                 }
             }
+            finished
+        });
+        let result = run_part(&part_config, sender, cancel_receiver, false);
+        let finished = forwarder.join().unwrap_or(false);
+        result?;
+        if !finished {
+            return Ok(());
         }
     }
+    Ok(())
+}
+
+// Rewrites one frame of a finished sequence in place, as a one-frame window of the
+// normal encode into whichever volume holds it. Used for frames the size watch flagged.
+pub fn rerender_frame(config: &EncodingConfig, frame: u32) -> Result<()> {
+    if !config.output_format.is_sequence()
+        || config.leader.is_some()
+        || config.still_hold.is_some()
+        || config.generator.is_some()
+        || config.pipe_command.is_some()
+        || config.every_nth.is_some_and(|n| n > 1)
+    {
+        return Err(anyhow!(
+            "Single frames can only be re-rendered into a plain image sequence"
+        ));
+    }
 
-    let start_frame = if found_any { max_frame } else { 0 };
-    let start_time_secs = start_frame as f32 / frame_rate;
-    let start_time_str = format!("{:.3}", start_time_secs);
+    let mut frame_config = config.clone();
+    if let Some(part) = config
+        .split
+        .iter()
+        .rev()
+        .find(|part| part.first_frame <= frame)
+    {
+        frame_config.output_dir = part.dir.clone();
+    }
+    frame_config.split = Vec::new();
+    frame_config.frame_window = Some((frame, frame + 1));
+    frame_config.qc_sample = None;
+    frame_config.format_options.review_mp4 = false;
+    frame_config.format_options.review_webm = false;
+    frame_config.audio_stems = None;
+
+    let (sender, _progress) = mpsc::channel();
+    let (_cancel, cancel_receiver) = mpsc::channel();
+    run_part(&frame_config, sender, &cancel_receiver, true)
+}
 
-    let temp_progress = tempfile::NamedTempFile::new()?;
+// A `patch` writes frames into an earlier run's output, so that run's provenance,
+// decode-error report, Finder tags and ledger entry are left as they were
+fn run_part(
+    config: &EncodingConfig,
+    progress_sender: Sender<(f32, u32, String)>,
+    cancel_receiver: &Receiver<()>,
+    patch: bool,
+) -> Result<()> {
+    let plan = plan_encoding(config, true)?;
+    let EncodePlan {
+        duration,
+        frame_rate,
+        total_frames,
+        start_frame,
+        fixed_size,
+        ..
+    } = plan;
+    let (width, height) = plan.source_size;
+    let format = config.output_format;
+    let output_path = plan.output_path.clone();
+
+    // Dropped on every return path, taking the progress file and log with it
+    let staging = JobStaging::create(&config.environment)?;
+    let temp_progress = tempfile::NamedTempFile::new_in(staging.dir())?;
     let progress_path = temp_progress.path().to_path_buf();
 
-    let (target_width, target_height) = match config.resolution.target_size() {
-        Some((w, h)) => (w, h),
-        None => (width, height),
-    };
+    // FFmpeg's own error output, kept so a failed run can report why
+    let stderr_log = tempfile::NamedTempFile::new_in(staging.dir())?;
 
-    let filter_complex = if config.resolution != Resolution::K6 {
-        format!(
-                "[0:v]scale={}:{}:flags=lanczos+full_chroma_inp+full_chroma_int:force_original_aspect_ratio=decrease,pad={}:{}:(ow-iw)/2:(oh-ih)/2:color=black[vid]; \
-                 [1:v]scale={}:{}:flags=lanczos+full_chroma_inp+full_chroma_int[ovr]; \
-                 [vid][ovr]overlay=0:0:format=rgb,format=rgb48le",
-                target_width, target_height, target_width, target_height, target_width, target_height
-            )
-    } else {
-        format!(
-            "[1:v]scale={}:{}:flags=lanczos+full_chroma_inp+full_chroma_int[ovr]; \
-                 [0:v][ovr]overlay=0:0:format=rgb,format=rgb48le",
-            width, height
-        )
+    let (target_width, target_height) = fixed_size.unwrap_or((width, height));
+
+    let mut downstream = match &config.pipe_command {
+        Some(command) => Some(spawn_downstream(command, config, staging.dir())?),
+        None => None,
+    };
+    let ffmpeg_stdout = match downstream.as_mut().and_then(|child| child.stdin.take()) {
+        Some(stdin) => Stdio::from(stdin),
+        None => Stdio::null(),
     };
 
     let mut cmd = Command::new(&config.ffmpeg_path);
-    cmd.arg("-ss")
-        .arg(&start_time_str)
-        .arg("-i")
-        .arg(&config.input_video)
-        .arg("-i")
-        .arg(&config.overlay_image)
-        .arg("-filter_complex")
-        .arg(&filter_complex)
-        .arg("-vsync")
-        .arg("0")
-        .arg("-start_number")
-        .arg(start_frame.to_string())
-        .arg("-progress")
-        .arg(&progress_path)
-        .arg("-color_trc")
-        .arg("linear")
-        .arg("-colorspace")
-        .arg("bt709")
-        .arg("-color_primaries")
-        .arg("bt709")
-        .arg("-pix_fmt")
-        .arg("rgb48le")
-        .arg("-compression_level")
-        .arg("1")
-        .arg("-pred")
-        .arg("none")
-        .arg(output_path)
-        .arg("-y")
-        .stdout(Stdio::null())
-        .stderr(Stdio::null());
+    config.environment.apply(&mut cmd);
+    cmd.args(plan.args(config, Some(&progress_path)))
+        .stdout(ffmpeg_stdout)
+        .stderr(Stdio::from(stderr_log.reopen()?));
+
+    crash::set_ffmpeg_command(format!("{:?}", cmd));
 
     let mut child = {
         #[cfg(windows)]
         {
-            cmd.creation_flags(0x08000000).spawn()?
+            cmd.creation_flags(0x08000000).spawn()
         }
         #[cfg(not(windows))]
         {
-            cmd.spawn()?
+            cmd.spawn()
         }
-    };
+    }
+    .map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => EncoderError::MissingBinary {
+            name: "FFmpeg",
+            path: config.ffmpeg_path.clone(),
+        },
+        _ => EncoderError::from_io(&e, &config.output_dir, IoSide::Output),
+    })?;
+
+    if !patch && config.pipe_command.is_none() && config.generator.is_none() {
+        provenance::record_source(&config.output_dir, &config.base_name, &config.input_video);
+    }
 
     let start_time = Instant::now();
+    let mut monitor = ResourceMonitor::new(child.id());
+    let meter = EnergyMeter::start(config.energy);
+    // Heartbeat: the last time the frame counter moved
+    let mut last_heartbeat = Instant::now();
+    let mut heartbeat_frame = start_frame;
 
-    let initial_progress = if total_frames > 0 {
-        (start_frame as f32 / total_frames as f32 * 100.0).min(100.0)
-    } else {
-        0.0
-    };
+    let initial_progress = percent_done(start_frame, total_frames);
 
     let _ = progress_sender.send((
-        initial_progress,
+        initial_progress as f32,
         start_frame,
         format!(
             "Processing | Res: {}x{} | Start: {:06} | ETA: --:--",
@@ -151,10 +1361,27 @@ pub fn run_encoding(
 
     let mut last_eta = "--:--".to_string();
     let mut last_frame = start_frame;
+    // Decoder errors paired with the frame being written when they appeared
+    let mut decode_errors: Vec<(u32, String)> = Vec::new();
+    let mut stderr_read = 0;
+    // Thinned-out frames are numbered by timestamp and a pipe writes no files
+    let mut size_watch =
+        (format.is_sequence() && config.pipe_command.is_none() && plan.every_nth.is_none())
+            .then(|| FrameSizeWatch::new(config, start_frame));
+    let suspect = |frame: u32| {
+        (
+            -4.0,
+            frame,
+            "Suspect frame: far smaller than its neighbours, added to re-render list".to_string(),
+        )
+    };
 
     while child.try_wait()?.is_none() {
         if cancel_receiver.try_recv().is_ok() {
             child.kill()?;
+            if let Some(downstream) = downstream.as_mut() {
+                let _ = downstream.kill();
+            }
             let _ = progress_sender.send((-2.0, last_frame, format!("Paused | ETA: {}", last_eta)));
             return Ok(());
         }
@@ -167,65 +1394,377 @@ pub fn run_encoding(
                     if let Some(frame_str) = line.split('=').nth(1) {
                         if let Ok(frame_index) = frame_str.trim().parse::<u32>() {
                             last_frame = start_frame + frame_index;
-
-                            if total_frames > 0 {
-                                progress_value =
-                                    (last_frame as f32 / total_frames as f32 * 100.0).min(100.0);
-                            }
+                            progress_value = percent_done(last_frame, total_frames);
                         }
                     }
                 } else if line.starts_with("out_time_ms") {
                     if let Some((_, time_str)) = line.split_once('=') {
                         if let Ok(_out_time_ms) = time_str.parse::<u64>() {
                             if duration > 0.0 {
-                                let elapsed = start_time.elapsed().as_secs_f32();
-                                if progress_value > 0.1 {
-                                    let total_estimated = (elapsed * 100.0) / progress_value;
-                                    let eta_secs = (total_estimated - elapsed) as u64;
-                                    last_eta = format!("{:02}:{:02}", eta_secs / 60, eta_secs % 60);
-                                } else {
-                                    last_eta = "--:--".to_string();
-                                }
+                                last_eta = match eta_secs(
+                                    start_time.elapsed().as_secs_f64(),
+                                    progress_value,
+                                ) {
+                                    Some(secs) => format!("{:02}:{:02}", secs / 60, secs % 60),
+                                    None => "--:--".to_string(),
+                                };
                             }
                         }
                     }
                 }
             }
 
-            let detailed_log = if config.resolution != Resolution::K6 {
+            if config.tolerate_errors {
+                collect_new_errors(
+                    stderr_log.path(),
+                    &mut stderr_read,
+                    last_frame,
+                    &mut decode_errors,
+                );
+            }
+
+            if let Some(watch) = size_watch.as_mut() {
+                for frame in watch.check(last_frame) {
+                    let _ = progress_sender.send(suspect(frame));
+                }
+            }
+
+            if last_frame != heartbeat_frame {
+                heartbeat_frame = last_frame;
+                last_heartbeat = Instant::now();
+            }
+
+            let resources = monitor
+                .sample()
+                .map(|usage| format!(" | {}", usage.describe()))
+                .unwrap_or_default();
+            let detailed_log = if fixed_size.is_some() {
                 format!(
-                    "Processing | Res: {}x{} | ETA: {}",
-                    target_width, target_height, last_eta
+                    "Processing | Res: {}x{}{} | ETA: {}",
+                    target_width, target_height, resources, last_eta
                 )
             } else {
-                format!("Processing | Res: {}x{} | ETA: {}", width, height, last_eta)
+                format!(
+                    "Processing | Res: {}x{}{} | ETA: {}",
+                    width, height, resources, last_eta
+                )
             };
 
-            let _ = progress_sender.send((progress_value, last_frame, detailed_log));
+            if last_heartbeat.elapsed() < config.stall_timeout {
+                let _ = progress_sender.send((progress_value as f32, last_frame, detailed_log));
+            }
+        }
+
+        // Seeking or opening the source never got going, which waiting won't fix
+        if heartbeat_frame == start_frame && start_time.elapsed() >= config.first_frame_timeout {
+            child.kill()?;
+            if let Some(downstream) = downstream.as_mut() {
+                let _ = downstream.kill();
+            }
+            return Err(EncoderError::Timeout {
+                phase: "First frame",
+                secs: config.first_frame_timeout.as_secs(),
+            }
+            .into());
+        }
+
+        // A child stuck on dead storage stays alive but stops writing progress
+        if last_heartbeat.elapsed() >= config.stall_timeout {
+            let _ = progress_sender.send((
+                -3.0,
+                last_frame,
+                format!(
+                    "Stalled: no progress for {}s | ETA: --:--",
+                    last_heartbeat.elapsed().as_secs()
+                ),
+            ));
         }
 
         thread::sleep(Duration::from_millis(200));
     }
 
     let status = child.wait()?;
+    if let Some(mut downstream) = downstream {
+        let downstream_status = downstream.wait()?;
+        if status.success() && !downstream_status.success() {
+            let log =
+                std::fs::read_to_string(staging.dir().join(DOWNSTREAM_LOG)).unwrap_or_default();
+            let last_line = log.lines().rev().find(|line| !line.trim().is_empty());
+            return Err(anyhow!(
+                "Pipe command failed ({}): {}",
+                downstream_status,
+                last_line.unwrap_or("no output")
+            ));
+        }
+    }
+    if config.tolerate_errors {
+        collect_new_errors(
+            stderr_log.path(),
+            &mut stderr_read,
+            last_frame,
+            &mut decode_errors,
+        );
+    }
     if status.success() {
-        let detailed_log = if config.resolution != Resolution::K6 {
+        if let Some(watch) = size_watch.as_mut() {
+            for frame in watch.finish(last_frame) {
+                let _ = progress_sender.send(suspect(frame));
+            }
+        }
+        if let Some(picture) = &plan.picture {
+            verify_output_tags(config, picture, &output_path)?;
+        }
+        if let Some(mode) = config.audio_stems.filter(|_| config.generator.is_none()) {
+            let _ = progress_sender.send((
+                99.9,
+                last_frame,
+                "Extracting audio stems | ETA: --:--".to_string(),
+            ));
+
+            if let Some(layout) = get_audio_layout(&config.input_video, &config.ffprobe_path)? {
+                export_stems(
+                    &config.ffmpeg_path,
+                    &config.environment,
+                    &config.input_video,
+                    &config.output_dir,
+                    &config.base_name,
+                    &layout,
+                    mode,
+                )?;
+            }
+        }
+
+        if format.is_imf() {
+            let _ = progress_sender.send((
+                99.9,
+                last_frame,
+                "Writing IMF package | ETA: --:--".to_string(),
+            ));
+
+            // The CPL duration has to match the essence exactly, so measure what was written
+            let written = get_duration(&output_path, &config.ffprobe_path)?;
+            write_imf_package(
+                &config.output_dir,
+                &ImfPackage {
+                    title: &config.base_name,
+                    essence: &output_path,
+                    frame_rate,
+                    duration_frames: TimecodeRate::from_fps(frame_rate).secs_to_frames(written),
+                },
+            )?;
+        }
+
+        // A thinned-out sequence has gaps the image2 reader would stop at
+        if format.is_sequence()
+            && config.format_options.review_mp4
+            && config.pipe_command.is_none()
+            && plan.every_nth.is_none()
+        {
+            let _ = progress_sender.send((
+                99.9,
+                last_frame,
+                "Encoding review copy | ETA: --:--".to_string(),
+            ));
+            write_review_copy(config, &plan, OutputFormat::Mp4H264)?;
+        }
+
+        if config.format_options.review_webm
+            && format != OutputFormat::WebmVp9
+            && config.pipe_command.is_none()
+            && plan.every_nth.is_none()
+        {
+            let _ = progress_sender.send((
+                99.9,
+                last_frame,
+                "Encoding WebM copy | ETA: --:--".to_string(),
+            ));
+            write_review_copy(config, &plan, OutputFormat::WebmVp9)?;
+        }
+
+        if let Some(count) = config
+            .qc_sample
+            .filter(|_| format.is_sequence() && config.pipe_command.is_none())
+        {
+            let _ = progress_sender.send((
+                99.9,
+                last_frame,
+                "Copying QC sample | ETA: --:--".to_string(),
+            ));
+            write_qc_sample(config, &config.naming.extension(format), count)?;
+        }
+
+        if !patch && !decode_errors.is_empty() {
+            write_error_report(config, &decode_errors)?;
+        }
+
+        apply_output_permissions(config)?;
+        let energy = meter.finish(start_time.elapsed(), monitor.cpu_time());
+        if !patch {
+            finder::mark_delivered(&config.output_dir, &config.base_name);
+            energy::record(
+                config.show.as_deref(),
+                &config.base_name,
+                &config.output_dir,
+                &config.notes,
+                &energy,
+            );
+        }
+        let mut errors_note = format!(" | {}", energy.describe());
+        if !decode_errors.is_empty() {
+            errors_note.push_str(&format!(" | {} decode errors logged", decode_errors.len()));
+        }
+        if let Some(flagged) = size_watch.map(|watch| watch.flagged).filter(|&n| n > 0) {
+            errors_note.push_str(&format!(" | {} suspect frames to re-render", flagged));
+        }
+        let detailed_log = if fixed_size.is_some() {
             format!(
-                "Processing | Res: {}x{} | ETA: 00:00",
-                target_width, target_height
+                "Processing | Res: {}x{}{} | ETA: 00:00",
+                target_width, target_height, errors_note
             )
         } else {
-            format!("Processing | Res: {}x{} | ETA: 00:00", width, height)
+            format!(
+                "Processing | Res: {}x{}{} | ETA: 00:00",
+                width, height, errors_note
+            )
         };
 
         let _ = progress_sender.send((100.0, last_frame, detailed_log));
         Ok(())
+    } else if !config.output_dir.is_dir() {
+        Err(EncoderError::OutputUnavailable {
+            path: config.output_dir.clone(),
+        }
+        .into())
     } else {
-        Err(anyhow!(
-            "FFmpeg exited with error at frame {} (ETA: {}): {}",
-            last_frame,
-            last_eta,
-            status
-        ))
+        let stderr = std::fs::read_to_string(stderr_log.path()).unwrap_or_default();
+        let last_lines: Vec<&str> = stderr.lines().rev().take(3).collect();
+        let details = if last_lines.is_empty() {
+            status.to_string()
+        } else {
+            last_lines.into_iter().rev().collect::<Vec<_>>().join(" / ")
+        };
+
+        Err(EncoderError::EncoderFailed {
+            stderr: format!("exited at frame {}: {}", last_frame, details),
+            resumes: plan.resumable,
+        }
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seek(frame: u32, frame_rate: f64, stream_start: f64) -> FrameSeek {
+        FrameSeek {
+            frame,
+            frame_rate,
+            stream_start,
+        }
+    }
+
+    #[test]
+    fn frame_seek_args_land_half_a_frame_early() {
+        // (frame, rate, stream start, coarse -ss, trim start, subtitle start)
+        for (frame, rate, start, coarse, trim, first) in [
+            (1000, 23.976, 0.0, "39.687479", "41.687479", "41.708333"),
+            (1000, 24.0, 0.0, "39.645833", "41.645833", "41.666667"),
+            (48, 25.0, 0.0, "0.000000", "1.900000", "1.920000"),
+            (90, 29.97, 10.0, "0.986317", "12.986317", "3.003000"),
+            (1800, 29.97, 10.0, "58.043317", "70.043317", "60.060000"),
+            (3600, 59.94, 10.0, "58.051658", "70.051658", "60.060000"),
+        ] {
+            let seek = seek(frame, rate, start);
+            assert_eq!(
+                seek.input_args(),
+                ["-copyts", "-noaccurate_seek", "-ss", coarse].map(str::to_string),
+                "frame {} at {}",
+                frame,
+                rate
+            );
+            assert_eq!(
+                seek.trim_filter(),
+                format!("trim=start={},setpts=PTS-STARTPTS", trim),
+                "frame {} at {}",
+                frame,
+                rate
+            );
+            assert_eq!(seek.start_time_str(), first, "frame {} at {}", frame, rate);
+        }
+    }
+
+    #[test]
+    fn six_hour_source_ends_on_its_last_frame() {
+        let frame_rate = 60000.0 / 1001.0;
+        for frames in [1_294_699, 1_294_700, 1_294_704, 1_294_705] {
+            // ffprobe's duration, rounded to the microsecond
+            let duration = (frames as f64 * 1001.0 / 60000.0 * 1e6).round() / 1e6;
+            assert_eq!(frames_in(duration, frame_rate), frames, "{}s", duration);
+
+            for frame in frames - 3..frames {
+                let percent = percent_done(frame, frames);
+                assert!(percent < 100.0, "frame {} of {}", frame, frames);
+                assert_eq!((percent / 100.0 * frames as f64).round() as u32, frame);
+            }
+            assert_eq!(percent_done(frames, frames), 100.0);
+        }
+    }
+
+    #[test]
+    fn eta_scales_elapsed_by_progress() {
+        assert_eq!(eta_secs(3600.0, 50.0), Some(3600));
+        assert_eq!(eta_secs(21_600.0, 75.0), Some(7200));
+        assert_eq!(eta_secs(10.0, 0.05), None);
+        assert_eq!(
+            eta_secs(21_600.0, percent_done(1_294_705, 1_294_705)),
+            Some(0)
+        );
+    }
+
+    // Decodes the first resumed frame of a source whose luma is its frame number.
+    // Skipped where ffmpeg isn't on the PATH.
+    #[test]
+    fn frame_seek_decodes_the_resume_frame() {
+        if Command::new("ffmpeg").arg("-version").output().is_err() {
+            eprintln!("ffmpeg not found, skipping");
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        for (rate, fps) in [("24000/1001", 23.976), ("30000/1001", 29.97), ("25", 25.0)] {
+            let source = dir.path().join("numbered.mkv");
+            let made = Command::new("ffmpeg")
+                .args(["-v", "error", "-y", "-f", "lavfi", "-i"])
+                .arg(format!(
+                    "nullsrc=s=16x16:r={}:d=9,format=gray,geq=lum=N",
+                    rate
+                ))
+                .args(["-c:v", "ffv1"])
+                .arg(&source)
+                .status()
+                .unwrap();
+            assert!(made.success());
+
+            for frame in [1, 61, 200] {
+                let output = Command::new("ffmpeg")
+                    .args(["-v", "error"])
+                    .args(seek(frame, fps, 0.0).input_args())
+                    .arg("-i")
+                    .arg(&source)
+                    .arg("-vf")
+                    .arg(seek(frame, fps, 0.0).trim_filter())
+                    .args(["-frames:v", "1", "-f", "rawvideo", "-pix_fmt", "gray", "-"])
+                    .output()
+                    .unwrap();
+                assert!(output.status.success());
+                assert_eq!(
+                    output.stdout.first().copied(),
+                    Some(frame as u8),
+                    "frame {} at {}",
+                    frame,
+                    rate
+                );
+            }
+        }
     }
 }
@@ -0,0 +1,205 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{crash, utils::app_data_dir};
+
+const LEDGER_HEADER: &str =
+    "timestamp,show,job,output_dir,wall_secs,cpu_secs,watt_hours,method,cost,notes\n";
+
+// Station figures for turning CPU time into watt-hours and money
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EnergyRates {
+    // Package power of the delivery machine's CPU at full load
+    pub cpu_tdp_watts: f64,
+    // 0 leaves the cost column empty
+    pub price_per_kwh: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnergyMethod {
+    // Measured by the CPU's energy counters for the whole package
+    Rapl,
+    // ffmpeg's CPU time spread over the TDP
+    TdpEstimate,
+}
+
+impl EnergyMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EnergyMethod::Rapl => "rapl",
+            EnergyMethod::TdpEstimate => "tdp-estimate",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct JobEnergy {
+    pub wall: Duration,
+    pub cpu: Duration,
+    pub watt_hours: f64,
+    pub method: EnergyMethod,
+    pub cost: Option<f64>,
+}
+
+impl JobEnergy {
+    pub fn describe(&self) -> String {
+        let estimate = if self.method == EnergyMethod::TdpEstimate {
+            "~"
+        } else {
+            ""
+        };
+        let cost = self
+            .cost
+            .map(|cost| format!(" ({:.2})", cost))
+            .unwrap_or_default();
+        format!("Energy: {}{:.0} Wh{}", estimate, self.watt_hours, cost)
+    }
+}
+
+// RAPL counters of every CPU package in microjoules, with each counter's wrap point.
+// Only readable by root on most distributions, in which case the estimate is used.
+#[cfg(target_os = "linux")]
+fn read_rapl() -> Option<Vec<(u64, u64)>> {
+    let mut counters = Vec::new();
+    for entry in fs::read_dir("/sys/class/powercap").ok()?.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        // Top-level zones are packages; intel-rapl:0:0 and the like are parts of one
+        if !name.starts_with("intel-rapl:") || name.matches(':').count() != 1 {
+            continue;
+        }
+        let read = |file: &str| -> Option<u64> {
+            fs::read_to_string(entry.path().join(file))
+                .ok()?
+                .trim()
+                .parse()
+                .ok()
+        };
+        counters.push((read("energy_uj")?, read("max_energy_range_uj")?));
+    }
+    (!counters.is_empty()).then_some(counters)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_rapl() -> Option<Vec<(u64, u64)>> {
+    None
+}
+
+// Taken when ffmpeg starts; the RAPL reading covers the whole machine, which
+// is fair on a dedicated delivery station
+pub struct EnergyMeter {
+    rates: EnergyRates,
+    rapl_start: Option<Vec<(u64, u64)>>,
+}
+
+impl EnergyMeter {
+    pub fn start(rates: EnergyRates) -> Self {
+        Self {
+            rates,
+            rapl_start: read_rapl(),
+        }
+    }
+
+    pub fn finish(&self, wall: Duration, cpu: Duration) -> JobEnergy {
+        let measured = self
+            .rapl_start
+            .as_ref()
+            .zip(read_rapl())
+            .and_then(|(start, end)| {
+                (start.len() == end.len()).then(|| {
+                    start
+                        .iter()
+                        .zip(&end)
+                        .map(|(&(before, range), &(after, _))| {
+                            if after >= before {
+                                after - before
+                            } else {
+                                range - before + after
+                            }
+                        })
+                        .sum::<u64>()
+                })
+            });
+
+        let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get()) as f64;
+        let (watt_hours, method) = match measured {
+            Some(microjoules) => (microjoules as f64 / 3.6e9, EnergyMethod::Rapl),
+            None => (
+                cpu.as_secs_f64() / 3600.0 * self.rates.cpu_tdp_watts / cores,
+                EnergyMethod::TdpEstimate,
+            ),
+        };
+        let cost = (self.rates.price_per_kwh > 0.0)
+            .then(|| watt_hours / 1000.0 * self.rates.price_per_kwh);
+
+        JobEnergy {
+            wall,
+            cpu,
+            watt_hours,
+            method,
+            cost,
+        }
+    }
+}
+
+pub fn ledger_path() -> PathBuf {
+    app_data_dir().join("job_costs.csv")
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// One row per finished job, so production can total a show in a spreadsheet
+// and the station keeps a history of what was delivered and why
+pub fn record(show: Option<&str>, job: &str, output_dir: &Path, notes: &str, energy: &JobEnergy) {
+    let path = ledger_path();
+    let row = format!(
+        "{},{},{},{},{:.0},{:.0},{:.1},{},{},{}\n",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        csv_field(show.unwrap_or("")),
+        csv_field(job),
+        csv_field(&output_dir.display().to_string()),
+        energy.wall.as_secs_f64(),
+        energy.cpu.as_secs_f64(),
+        energy.watt_hours,
+        energy.method.as_str(),
+        energy
+            .cost
+            .map(|cost| format!("{:.2}", cost))
+            .unwrap_or_default(),
+        csv_field(notes),
+    );
+
+    let written = (|| -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let is_new = !path.exists();
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        if is_new {
+            file.write_all(LEDGER_HEADER.as_bytes())?;
+        }
+        file.write_all(row.as_bytes())
+    })();
+    if let Err(e) = written {
+        crash::log(format!(
+            "Could not record job cost in {}: {}",
+            path.display(),
+            e
+        ));
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+// Which end of a job an I/O error hit; a missing file is a broken source on one
+// side and a dropped share on the other
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoSide {
+    Source,
+    Output,
+}
+
+#[derive(Debug)]
+pub enum EncoderError {
+    MissingBinary { name: &'static str, path: PathBuf },
+    SourceUnreadable { path: PathBuf, reason: String },
+    SourceUnavailable { path: PathBuf, reason: String },
+    DiskFull { required_gb: f64, available_gb: f64 },
+    PermissionDenied { path: PathBuf },
+    OutputUnavailable { path: PathBuf },
+    // `resumes` when the frames already written are kept and picked up on the next run
+    EncoderFailed { stderr: String, resumes: bool },
+    UnsupportedFormat { reason: String },
+    // Audio-only files, or containers too damaged for ffprobe to find the picture
+    NoVideoStream { path: PathBuf },
+    // A phase that stopped responding, e.g. a probe or copy on a dead mount
+    Timeout { phase: &'static str, secs: u64 },
+    // Any other file system failure, e.g. while archiving, exporting or trashing
+    Io { path: PathBuf, reason: String },
+}
+
+impl EncoderError {
+    pub fn source_unreadable(path: &Path, reason: impl fmt::Display) -> Self {
+        EncoderError::SourceUnreadable {
+            path: path.to_path_buf(),
+            reason: reason.to_string(),
+        }
+    }
+
+    // Storage-side failure that may succeed on retry, as opposed to an invalid file
+    pub fn source_unavailable(path: &Path, reason: impl fmt::Display) -> Self {
+        EncoderError::SourceUnavailable {
+            path: path.to_path_buf(),
+            reason: reason.to_string(),
+        }
+    }
+
+    // Maps an I/O failure on `path` to the closest user-facing category
+    pub fn from_io(error: &io::Error, path: &Path, side: IoSide) -> Self {
+        match error.kind() {
+            io::ErrorKind::NotFound | io::ErrorKind::PermissionDenied if side == IoSide::Source => {
+                EncoderError::source_unreadable(path, error)
+            }
+            io::ErrorKind::PermissionDenied | io::ErrorKind::ReadOnlyFilesystem => {
+                EncoderError::PermissionDenied {
+                    path: path.to_path_buf(),
+                }
+            }
+            io::ErrorKind::StorageFull => EncoderError::DiskFull {
+                required_gb: 0.0,
+                available_gb: 0.0,
+            },
+            io::ErrorKind::NotFound => EncoderError::OutputUnavailable {
+                path: path.to_path_buf(),
+            },
+            _ => EncoderError::Io {
+                path: path.to_path_buf(),
+                reason: error.to_string(),
+            },
+        }
+    }
+
+    pub fn guidance(&self) -> &'static str {
+        match self {
+            EncoderError::MissingBinary { .. } => {
+                "Install FFmpeg or place ffmpeg and ffprobe in assets/ffmpeg, then restart the app."
+            }
+            EncoderError::SourceUnreadable { .. } => {
+                "Check that the source file is reachable and fully copied, then try again."
+            }
+            EncoderError::SourceUnavailable { .. } => {
+                "The source storage did not respond after several attempts. Check the network share, then try again."
+            }
+            EncoderError::DiskFull { .. } => {
+                "Free up space on the output drive or choose another output directory."
+            }
+            EncoderError::PermissionDenied { .. } => {
+                "Check write permissions on the output folder or choose another one."
+            }
+            EncoderError::OutputUnavailable { .. } => {
+                "Output share disconnected - reconnect it and press Start Encoding to resume."
+            }
+            EncoderError::EncoderFailed { resumes: true, .. } => {
+                "FFmpeg stopped with an error. Frames already written are kept, so starting again resumes."
+            }
+            EncoderError::EncoderFailed { resumes: false, .. } => {
+                "FFmpeg stopped with an error. Check the details above; starting again encodes the output from the beginning."
+            }
+            EncoderError::UnsupportedFormat { .. } => {
+                "Install a full FFmpeg build in assets/ffmpeg or choose another output format."
+            }
+            EncoderError::NoVideoStream { .. } => {
+                "Choose the picture master; audio-only and damaged files cannot be delivered."
+            }
+            EncoderError::Timeout { .. } => {
+                "Storage or a device stopped responding. Check the mounts, then start again or retry the job from the queue."
+            }
+            EncoderError::Io { .. } => {
+                "Check that the location is reachable and not in use by another program, then try again."
+            }
+        }
+    }
+}
+
+impl fmt::Display for EncoderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncoderError::MissingBinary { name, path } => {
+                write!(f, "{} not found at {}", name, path.display())
+            }
+            EncoderError::SourceUnreadable { path, reason } => {
+                write!(f, "Cannot read source {}: {}", path.display(), reason)
+            }
+            EncoderError::SourceUnavailable { path, reason } => {
+                write!(
+                    f,
+                    "Source {} is temporarily unreachable: {}",
+                    path.display(),
+                    reason
+                )
+            }
+            EncoderError::DiskFull {
+                required_gb,
+                available_gb,
+            } => {
+                if *required_gb > 0.0 {
+                    write!(
+                        f,
+                        "Insufficient storage: {:.2}GB required, {:.2}GB available",
+                        required_gb, available_gb
+                    )
+                } else {
+                    write!(f, "Output drive is full")
+                }
+            }
+            EncoderError::PermissionDenied { path } => {
+                write!(f, "Permission denied writing to {}", path.display())
+            }
+            EncoderError::OutputUnavailable { path } => {
+                write!(f, "Output location {} is unavailable", path.display())
+            }
+            EncoderError::EncoderFailed { stderr, .. } => write!(f, "FFmpeg failed: {}", stderr),
+            EncoderError::UnsupportedFormat { reason } => {
+                write!(f, "Unsupported output format: {}", reason)
+            }
+            EncoderError::NoVideoStream { path } => {
+                write!(f, "{} has no video stream", path.display())
+            }
+            EncoderError::Timeout { phase, secs } => {
+                write!(f, "{} timed out after {}s", phase, secs)
+            }
+            EncoderError::Io { path, reason } => {
+                write!(f, "Could not access {}: {}", path.display(), reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EncoderError {}
+
+// Renders any error for the status line, appending guidance for known categories
+pub fn user_message(error: &anyhow::Error) -> String {
+    match error.downcast_ref::<EncoderError>() {
+        Some(classified) => format!("{} | {}", classified, classified.guidance()),
+        None => error.to_string(),
+    }
+}
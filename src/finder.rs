@@ -0,0 +1,45 @@
+use std::path::Path;
+
+// Extended attribute carrying the job that produced a delivery folder
+#[cfg(target_os = "macos")]
+const JOB_ATTR: &str = "com.krutartstudio.delivery_encoder.job";
+
+// Finder reads tags from this attribute as a plist of "name\ncolour" strings; 2 is green
+#[cfg(target_os = "macos")]
+const TAGS_ATTR: &str = "com.apple.metadata:_kMDItemUserTags";
+#[cfg(target_os = "macos")]
+const DELIVERED_TAG: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0"><array><string>Delivered
+2</string></array></plist>"#;
+
+// Tags a finished output folder so its state shows when browsing the SAN.
+// Best effort: a volume without xattr support only loses the tag.
+#[cfg(target_os = "macos")]
+pub fn mark_delivered(dir: &Path, job: &str) {
+    use std::process::{Command, Stdio};
+
+    use crate::crash;
+
+    for (name, value) in [(TAGS_ATTR, DELIVERED_TAG), (JOB_ATTR, job)] {
+        let result = Command::new("xattr")
+            .args(["-w", name, value])
+            .arg(dir)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .output();
+        match result {
+            Ok(output) if output.status.success() => {}
+            Ok(output) => crash::log(format!(
+                "Could not set {} on {}: {}",
+                name,
+                dir.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+            Err(e) => crash::log(format!("Could not run xattr: {}", e)),
+        }
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn mark_delivered(_dir: &Path, _job: &str) {}
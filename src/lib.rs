@@ -0,0 +1,28 @@
+//! Delivery Encoder's pipeline without the gui: probing a source, planning the
+//! ffmpeg job and running it to a frame sequence or package.
+//!
+//! Build an [`encoding::EncodingConfig`] and hand it to [`encoding::run_encoding`],
+//! which reports `(percent, frame, message)` on the progress channel until it
+//! sends 100 (done), -1 (failed) or -2 (stopped through the cancel channel).
+//! Two codes come mid-run without ending it: -3 while ffmpeg has stopped writing
+//! frames (stalled; the caller decides whether to cancel), and -4 with a suspect
+//! frame's number, one far smaller than its neighbours, to re-render afterwards.
+//! The probing helpers ([`utils::get_resolution`], [`utils::get_frame_rate`],
+//! [`utils::get_picture_tags`] and friends) work on their own for quick checks.
+
+pub mod audio;
+pub mod crash;
+pub mod encoding;
+pub mod energy;
+pub mod errors;
+pub mod finder;
+pub mod models;
+pub mod packaging;
+pub mod permissions;
+pub mod provenance;
+pub mod resources;
+pub mod split;
+pub mod staging;
+pub mod timecode;
+pub mod trash;
+pub mod utils;
@@ -6,13 +6,45 @@ use eframe::egui;
 use egui::IconData;
 
 mod app;
-mod encoding;
-mod models;
-mod utils;
+mod archive;
+mod cli;
+mod player;
+mod profiles;
+mod proxy;
+mod purge;
+mod queue;
+mod renumber;
+mod replication;
+mod rerender;
+mod schedule;
+mod scopes;
+mod script;
+mod settings;
+mod share;
+mod spool;
+mod stills;
+mod summary;
+mod telemetry;
+mod update;
 
 use app::DeliveryEncoderApp;
+use delivery_encoder_core::{
+    audio, crash, encoding, energy, errors, models, permissions, provenance, split, staging,
+    timecode, trash, utils,
+};
 
 fn main() -> Result<()> {
+    crash::install_hook();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--cli") {
+        if let Err(e) = cli::run(&args) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     let icon_bytes = include_bytes!("../assets/krutart.rgba");
 
     let (icon_width, icon_height) = (256, 256);
@@ -37,4 +69,4 @@ fn main() -> Result<()> {
         Box::new(|_| Box::new(DeliveryEncoderApp::new())),
     )
     .map_err(|e| anyhow!("Application error: {}", e))
-}
\ No newline at end of file
+}
@@ -1,24 +1,49 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::utils::sanitize_base_name;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Resolution {
     K2,
     K4,
     K6,
+    Hd,
+    Uhd,
+    Dci4k,
 }
 
 impl Resolution {
+    pub const ALL: [Resolution; 6] = [
+        Resolution::K2,
+        Resolution::K4,
+        Resolution::K6,
+        Resolution::Hd,
+        Resolution::Uhd,
+        Resolution::Dci4k,
+    ];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             Resolution::K2 => "2K (2048x2048)",
             Resolution::K4 => "4K (4096x4096)",
             Resolution::K6 => "6K (Original)",
+            Resolution::Hd => "HD (1920x1080)",
+            Resolution::Uhd => "UHD (3840x2160)",
+            Resolution::Dci4k => "DCI 4K (4096x2160)",
         }
     }
 
+    // The source is fitted inside and padded with black, so a square master
+    // lands pillarboxed on a 16:9 raster
     pub fn target_size(&self) -> Option<(u32, u32)> {
         match self {
             Resolution::K2 => Some((2048, 2048)),
             Resolution::K4 => Some((4096, 4096)),
             Resolution::K6 => None,
+            Resolution::Hd => Some((1920, 1080)),
+            Resolution::Uhd => Some((3840, 2160)),
+            Resolution::Dci4k => Some((4096, 2160)),
         }
     }
 
@@ -31,6 +56,1542 @@ impl Resolution {
             Resolution::K2 => "2k",
             Resolution::K4 => "4k",
             Resolution::K6 => "6k",
+            Resolution::Hd => "hd",
+            Resolution::Uhd => "uhd",
+            Resolution::Dci4k => "dci4k",
+        }
+    }
+
+    // overlay_<tag>.png in `dir`. The wide presets fall back to the square
+    // overlay of the same height class, fitted like the picture.
+    pub fn overlay_file(&self, dir: &Path) -> PathBuf {
+        let own = dir.join(format!("overlay_{}.png", self.as_file_tag()));
+        let fallback = match self {
+            Resolution::Hd => Resolution::K2,
+            Resolution::Uhd | Resolution::Dci4k => Resolution::K4,
+            _ => return own,
+        };
+        if own.exists() {
+            own
+        } else {
+            dir.join(format!("overlay_{}.png", fallback.as_file_tag()))
+        }
+    }
+
+    // Preset nearest the source's long edge on a log scale, so 4096 picks 4K
+    // and a 6K-class master keeps its original size. A source already at one of
+    // the wide rasters keeps it.
+    pub fn closest((width, height): (u32, u32)) -> Self {
+        if let Some(exact) = Self::ALL
+            .into_iter()
+            .find(|preset| preset.target_size() == Some((width, height)))
+        {
+            return exact;
+        }
+        let edge = width.max(height).max(1) as f64;
+        [
+            (Resolution::K2, 2048.0),
+            (Resolution::K4, 4096.0),
+            (Resolution::K6, 6144.0),
+        ]
+        .into_iter()
+        .min_by(|(_, a), (_, b)| (edge / a).ln().abs().total_cmp(&(edge / b).ln().abs()))
+        .map(|(resolution, _)| resolution)
+        .unwrap_or(Resolution::K6)
+    }
+}
+
+// What the operator should know about a freshly loaded source before picking settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceCheck {
+    pub size: (u32, u32),
+    pub suggested: Resolution,
+    pub warnings: Vec<String>,
+}
+
+impl SourceCheck {
+    pub fn of(size: (u32, u32), tags: &PictureTags) -> Self {
+        let (width, height) = size;
+        let mut warnings = Vec::new();
+        if width % 2 == 1 || height % 2 == 1 {
+            warnings.push(format!(
+                "Odd dimensions ({}x{}); 4:2:2 outputs lose the last row or column",
+                width, height
+            ));
+        }
+        if let Some(rotation) = tags.rotation.filter(|rotation| rotation % 360 != 0) {
+            warnings.push(format!(
+                "Rotated {} degrees in its metadata; outputs use the stored orientation",
+                rotation
+            ));
+        }
+        if let Some((num, den)) = tags.sample_aspect.filter(|(num, den)| num != den) {
+            warnings.push(format!(
+                "Non-square pixels ({}:{}); overlays are placed on the stored raster",
+                num, den
+            ));
+        }
+        if tags
+            .field_order
+            .is_some_and(|order| order != FieldOrder::Progressive)
+        {
+            warnings.push("Interlaced source".to_string());
+        }
+        Self {
+            size,
+            suggested: Resolution::closest(size),
+            warnings,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SubtitleSpec {
+    pub path: PathBuf,
+    pub font: String,
+    pub font_size: u32,
+    pub margin: u32,
+}
+
+impl SubtitleSpec {
+    // libass style overrides; unset fields keep the subtitle file's own styling
+    pub fn force_style(&self) -> String {
+        let mut styles = Vec::new();
+        if !self.font.trim().is_empty() {
+            styles.push(format!("FontName={}", self.font.trim()));
+        }
+        if self.font_size > 0 {
+            styles.push(format!("FontSize={}", self.font_size));
+        }
+        if self.margin > 0 {
+            styles.push(format!("MarginV={}", self.margin));
+        }
+        styles.join(",")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StemMode {
+    PerChannel,
+    StereoPairs,
+}
+
+impl StemMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StemMode::PerChannel => "Per Channel",
+            StemMode::StereoPairs => "Stereo Pairs",
+        }
+    }
+}
+
+// What one job writes: numbered frames, or a single file with the whole shot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputKind {
+    Sequence,
+    Movie,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutputFormat {
+    PngSequence,
+    ExrSequence,
+    DpxSequence,
+    JpegSequence,
+    MovProRes422,
+    MovProRes4444,
+    MovDnxhr,
+    MxfDnxhr,
+    Mp4H264,
+    Mp4Hevc,
+    Mp4Av1Svt,
+    Mp4Av1Aom,
+    WebmVp9,
+    MxfXdcamHd50,
+    MxfAvcIntra100,
+    ImfJpeg2000,
+    ImfProRes,
+}
+
+impl OutputFormat {
+    pub const ALL: [OutputFormat; 17] = [
+        OutputFormat::PngSequence,
+        OutputFormat::ExrSequence,
+        OutputFormat::DpxSequence,
+        OutputFormat::JpegSequence,
+        OutputFormat::MovProRes422,
+        OutputFormat::MovProRes4444,
+        OutputFormat::MovDnxhr,
+        OutputFormat::MxfDnxhr,
+        OutputFormat::Mp4H264,
+        OutputFormat::Mp4Hevc,
+        OutputFormat::Mp4Av1Svt,
+        OutputFormat::Mp4Av1Aom,
+        OutputFormat::WebmVp9,
+        OutputFormat::MxfXdcamHd50,
+        OutputFormat::MxfAvcIntra100,
+        OutputFormat::ImfJpeg2000,
+        OutputFormat::ImfProRes,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputFormat::PngSequence => "PNG Sequence (16-bit)",
+            OutputFormat::ExrSequence => "OpenEXR Sequence",
+            OutputFormat::DpxSequence => "DPX Sequence (10-bit)",
+            OutputFormat::JpegSequence => "JPEG Sequence (review)",
+            OutputFormat::MovProRes422 => "QuickTime - ProRes 422 HQ",
+            OutputFormat::MovProRes4444 => "QuickTime - ProRes 4444",
+            OutputFormat::MovDnxhr => "QuickTime - DNxHR",
+            OutputFormat::MxfDnxhr => "MXF OP1a - DNxHR (Avid)",
+            OutputFormat::Mp4H264 => "MP4 - H.264 (review)",
+            OutputFormat::Mp4Hevc => "MP4 - HEVC (archive)",
+            OutputFormat::Mp4Av1Svt => "MP4 - AV1 (SVT-AV1)",
+            OutputFormat::Mp4Av1Aom => "MP4 - AV1 (libaom)",
+            OutputFormat::WebmVp9 => "WebM - VP9 (web)",
+            OutputFormat::MxfXdcamHd50 => "MXF OP1a - XDCAM HD 50",
+            OutputFormat::MxfAvcIntra100 => "MXF OP1a - AVC-Intra 100",
+            OutputFormat::ImfJpeg2000 => "IMF Package - JPEG 2000",
+            OutputFormat::ImfProRes => "IMF Package - ProRes 4444",
+        }
+    }
+
+    pub fn kind(&self) -> OutputKind {
+        match self {
+            OutputFormat::PngSequence
+            | OutputFormat::ExrSequence
+            | OutputFormat::DpxSequence
+            | OutputFormat::JpegSequence => OutputKind::Sequence,
+            OutputFormat::MovProRes422
+            | OutputFormat::MovProRes4444
+            | OutputFormat::MovDnxhr
+            | OutputFormat::MxfDnxhr
+            | OutputFormat::Mp4H264
+            | OutputFormat::Mp4Hevc
+            | OutputFormat::Mp4Av1Svt
+            | OutputFormat::Mp4Av1Aom
+            | OutputFormat::WebmVp9
+            | OutputFormat::MxfXdcamHd50
+            | OutputFormat::MxfAvcIntra100
+            | OutputFormat::ImfJpeg2000
+            | OutputFormat::ImfProRes => OutputKind::Movie,
+        }
+    }
+
+    pub fn is_sequence(&self) -> bool {
+        self.kind() == OutputKind::Sequence
+    }
+
+    pub fn is_imf(&self) -> bool {
+        matches!(self, OutputFormat::ImfJpeg2000 | OutputFormat::ImfProRes)
+    }
+
+    // IMF audio lives in separate track files, which the package stub does not write
+    pub fn carries_audio(&self) -> bool {
+        matches!(
+            self,
+            OutputFormat::MovProRes422
+                | OutputFormat::MovProRes4444
+                | OutputFormat::MovDnxhr
+                | OutputFormat::MxfDnxhr
+                | OutputFormat::Mp4H264
+                | OutputFormat::Mp4Hevc
+                | OutputFormat::Mp4Av1Svt
+                | OutputFormat::Mp4Av1Aom
+                | OutputFormat::WebmVp9
+                | OutputFormat::MxfXdcamHd50
+                | OutputFormat::MxfAvcIntra100
+        )
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::PngSequence => "png",
+            OutputFormat::ExrSequence => "exr",
+            OutputFormat::DpxSequence => "dpx",
+            OutputFormat::JpegSequence => "jpg",
+            OutputFormat::MovProRes422 | OutputFormat::MovProRes4444 | OutputFormat::MovDnxhr => {
+                "mov"
+            }
+            OutputFormat::Mp4H264
+            | OutputFormat::Mp4Hevc
+            | OutputFormat::Mp4Av1Svt
+            | OutputFormat::Mp4Av1Aom => "mp4",
+            OutputFormat::WebmVp9 => "webm",
+            OutputFormat::MxfDnxhr
+            | OutputFormat::MxfXdcamHd50
+            | OutputFormat::MxfAvcIntra100
+            | OutputFormat::ImfJpeg2000
+            | OutputFormat::ImfProRes => "mxf",
+        }
+    }
+
+    // Broadcast profiles are fixed HD rasters regardless of the chosen resolution
+    pub fn frame_size(&self) -> Option<(u32, u32)> {
+        match self {
+            OutputFormat::PngSequence
+            | OutputFormat::ExrSequence
+            | OutputFormat::DpxSequence
+            | OutputFormat::JpegSequence
+            | OutputFormat::MovProRes422
+            | OutputFormat::MovProRes4444
+            | OutputFormat::MovDnxhr
+            | OutputFormat::MxfDnxhr
+            | OutputFormat::Mp4H264
+            | OutputFormat::Mp4Hevc
+            | OutputFormat::Mp4Av1Svt
+            | OutputFormat::Mp4Av1Aom
+            | OutputFormat::WebmVp9
+            | OutputFormat::ImfJpeg2000
+            | OutputFormat::ImfProRes => None,
+            OutputFormat::MxfXdcamHd50 | OutputFormat::MxfAvcIntra100 => Some((1920, 1080)),
+        }
+    }
+
+    pub fn pix_fmt(&self) -> &'static str {
+        match self {
+            OutputFormat::PngSequence => "rgb48le",
+            // The EXR encoder takes planar float and writes half or full precision from it
+            OutputFormat::ExrSequence => "gbrpf32le",
+            OutputFormat::DpxSequence => "gbrp10le",
+            // Full-range 4:4:4 keeps overlay text and burn-ins crisp
+            OutputFormat::JpegSequence => "yuvj444p",
+            OutputFormat::MovProRes422 => "yuv422p10le",
+            OutputFormat::MovProRes4444 => "yuv444p10le",
+            // For the default HQ profile; FormatOptions::pix_fmt follows the chosen one
+            OutputFormat::MovDnxhr | OutputFormat::MxfDnxhr => "yuv422p",
+            // What browsers and phones play
+            OutputFormat::Mp4H264 => "yuv420p",
+            // For the default Main 10; FormatOptions::pix_fmt follows the chosen depth
+            OutputFormat::Mp4Hevc => "yuv420p10le",
+            // AV1's Main profile is 10-bit, which also avoids banding in streamed gradients
+            OutputFormat::Mp4Av1Svt | OutputFormat::Mp4Av1Aom => "yuv420p10le",
+            // 8-bit profile 0, the only one every browser decodes
+            OutputFormat::WebmVp9 => "yuv420p",
+            OutputFormat::MxfXdcamHd50 => "yuv422p",
+            OutputFormat::MxfAvcIntra100 => "yuv422p10le",
+            OutputFormat::ImfJpeg2000 => "rgb48le",
+            OutputFormat::ImfProRes => "yuv444p10le",
+        }
+    }
+
+    pub fn bitrate_mbps(&self) -> Option<u32> {
+        match self {
+            OutputFormat::PngSequence
+            | OutputFormat::ExrSequence
+            | OutputFormat::DpxSequence
+            | OutputFormat::JpegSequence => None,
+            // 1080p figures; larger rasters scale up, which the 20% buffer only partly covers
+            OutputFormat::MovProRes422 => Some(220),
+            OutputFormat::MovProRes4444 => Some(330),
+            OutputFormat::MovDnxhr | OutputFormat::MxfDnxhr => {
+                Some(DnxhrProfile::Hq.bitrate_mbps())
+            }
+            OutputFormat::Mp4H264 => Some(RateControl::default().estimated_mbps()),
+            OutputFormat::Mp4Hevc => Some(RateControl::default().estimated_mbps() / 2),
+            OutputFormat::Mp4Av1Svt | OutputFormat::Mp4Av1Aom => Some(8),
+            OutputFormat::WebmVp9 => Some(5),
+            OutputFormat::MxfXdcamHd50 => Some(50),
+            OutputFormat::MxfAvcIntra100 => Some(100),
+            // Upper bounds of the IMF application profiles, used for storage estimates
+            OutputFormat::ImfJpeg2000 => Some(250),
+            OutputFormat::ImfProRes => Some(330),
+        }
+    }
+
+    // Colour tag the profile writes, e.g. "-color_primaries" -> "bt709"
+    pub fn codec_arg(&self, name: &str) -> Option<&'static str> {
+        let args = self.codec_args();
+        args.iter()
+            .position(|arg| *arg == name)
+            .and_then(|index| args.get(index + 1).copied())
+    }
+
+    // (muxer, encoder) the FFmpeg build has to provide
+    pub fn requirements(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            OutputFormat::PngSequence => None,
+            OutputFormat::ExrSequence => Some(("image2", "exr")),
+            OutputFormat::DpxSequence => Some(("image2", "dpx")),
+            OutputFormat::JpegSequence => Some(("image2", "mjpeg")),
+            OutputFormat::MovProRes422 | OutputFormat::MovProRes4444 => Some(("mov", "prores_ks")),
+            OutputFormat::MovDnxhr => Some(("mov", "dnxhd")),
+            OutputFormat::MxfDnxhr => Some(("mxf", "dnxhd")),
+            OutputFormat::Mp4H264 => Some(("mp4", "libx264")),
+            OutputFormat::Mp4Hevc => Some(("mp4", "libx265")),
+            OutputFormat::Mp4Av1Svt => Some(("mp4", "libsvtav1")),
+            OutputFormat::Mp4Av1Aom => Some(("mp4", "libaom-av1")),
+            OutputFormat::WebmVp9 => Some(("webm", "libvpx-vp9")),
+            OutputFormat::MxfXdcamHd50 => Some(("mxf", "mpeg2video")),
+            OutputFormat::MxfAvcIntra100 => Some(("mxf", "libx264")),
+            OutputFormat::ImfJpeg2000 => Some(("mxf", "libopenjpeg")),
+            OutputFormat::ImfProRes => Some(("mxf", "prores_ks")),
+        }
+    }
+
+    pub fn codec_args(&self) -> &'static [&'static str] {
+        match self {
+            OutputFormat::PngSequence => &[
+                "-color_trc",
+                "linear",
+                "-colorspace",
+                "bt709",
+                "-color_primaries",
+                "bt709",
+                "-pix_fmt",
+                "rgb48le",
+                "-compression_level",
+                "1",
+                "-pred",
+                "none",
+            ],
+            // Precision and compression come from FormatOptions
+            OutputFormat::ExrSequence => &[
+                "-c:v",
+                "exr",
+                "-color_trc",
+                "linear",
+                "-colorspace",
+                "bt709",
+                "-color_primaries",
+                "bt709",
+                "-pix_fmt",
+                "gbrpf32le",
+            ],
+            // Transfer tag comes from FormatOptions, which knows whether the frames are log
+            OutputFormat::DpxSequence => &[
+                "-c:v",
+                "dpx",
+                "-colorspace",
+                "bt709",
+                "-color_primaries",
+                "bt709",
+                "-pix_fmt",
+                "gbrp10le",
+            ],
+            // Quality comes from FormatOptions
+            OutputFormat::JpegSequence => &[
+                "-c:v",
+                "mjpeg",
+                "-pix_fmt",
+                "yuvj444p",
+                "-color_primaries",
+                "bt709",
+                "-color_trc",
+                "bt709",
+                "-colorspace",
+                "bt709",
+            ],
+            OutputFormat::MovProRes422 => &[
+                "-c:v",
+                "prores_ks",
+                "-profile:v",
+                "3",
+                "-vendor",
+                "apl0",
+                "-pix_fmt",
+                "yuv422p10le",
+                "-color_primaries",
+                "bt709",
+                "-color_trc",
+                "bt709",
+                "-colorspace",
+                "bt709",
+                "-c:a",
+                "pcm_s24le",
+                "-ar",
+                "48000",
+                "-f",
+                "mov",
+            ],
+            OutputFormat::MovProRes4444 => &[
+                "-c:v",
+                "prores_ks",
+                "-profile:v",
+                "4444",
+                "-vendor",
+                "apl0",
+                "-pix_fmt",
+                "yuv444p10le",
+                "-color_primaries",
+                "bt709",
+                "-color_trc",
+                "bt709",
+                "-colorspace",
+                "bt709",
+                "-c:a",
+                "pcm_s24le",
+                "-ar",
+                "48000",
+                "-f",
+                "mov",
+            ],
+            // Profile and pixel format come from FormatOptions::args
+            OutputFormat::MovDnxhr => &[
+                "-c:v",
+                "dnxhd",
+                "-color_primaries",
+                "bt709",
+                "-color_trc",
+                "bt709",
+                "-colorspace",
+                "bt709",
+                "-c:a",
+                "pcm_s24le",
+                "-ar",
+                "48000",
+                "-f",
+                "mov",
+            ],
+            OutputFormat::MxfDnxhr => &[
+                "-c:v",
+                "dnxhd",
+                "-color_primaries",
+                "bt709",
+                "-color_trc",
+                "bt709",
+                "-colorspace",
+                "bt709",
+                "-c:a",
+                "pcm_s24le",
+                "-ar",
+                "48000",
+                "-f",
+                "mxf",
+            ],
+            // Rate control comes from FormatOptions::args
+            OutputFormat::Mp4H264 => &[
+                "-c:v",
+                "libx264",
+                "-preset",
+                "medium",
+                "-pix_fmt",
+                "yuv420p",
+                "-color_primaries",
+                "bt709",
+                "-color_trc",
+                "bt709",
+                "-colorspace",
+                "bt709",
+                "-movflags",
+                "+faststart",
+                "-c:a",
+                "aac",
+                "-b:a",
+                "192k",
+                "-f",
+                "mp4",
+            ],
+            // hvc1 is the tag QuickTime and Apple devices insist on; profile, pixel
+            // format and rate control come from FormatOptions::args
+            OutputFormat::Mp4Hevc => &[
+                "-c:v",
+                "libx265",
+                "-preset",
+                "slow",
+                "-tag:v",
+                "hvc1",
+                "-color_primaries",
+                "bt709",
+                "-color_trc",
+                "bt709",
+                "-colorspace",
+                "bt709",
+                "-movflags",
+                "+faststart",
+                "-c:a",
+                "aac",
+                "-b:a",
+                "256k",
+                "-f",
+                "mp4",
+            ],
+            // Speed and quality come from FormatOptions::args
+            OutputFormat::Mp4Av1Svt => &[
+                "-c:v",
+                "libsvtav1",
+                "-pix_fmt",
+                "yuv420p10le",
+                "-color_primaries",
+                "bt709",
+                "-color_trc",
+                "bt709",
+                "-colorspace",
+                "bt709",
+                "-movflags",
+                "+faststart",
+                "-c:a",
+                "aac",
+                "-b:a",
+                "192k",
+                "-f",
+                "mp4",
+            ],
+            // -b:v 0 makes libaom honour the CRF alone rather than as a cap
+            OutputFormat::Mp4Av1Aom => &[
+                "-c:v",
+                "libaom-av1",
+                "-b:v",
+                "0",
+                "-row-mt",
+                "1",
+                "-pix_fmt",
+                "yuv420p10le",
+                "-color_primaries",
+                "bt709",
+                "-color_trc",
+                "bt709",
+                "-colorspace",
+                "bt709",
+                "-movflags",
+                "+faststart",
+                "-c:a",
+                "aac",
+                "-b:a",
+                "192k",
+                "-f",
+                "mp4",
+            ],
+            // Constant quality like libaom; the CRF comes from FormatOptions::args
+            OutputFormat::WebmVp9 => &[
+                "-c:v",
+                "libvpx-vp9",
+                "-b:v",
+                "0",
+                "-row-mt",
+                "1",
+                "-deadline",
+                "good",
+                "-cpu-used",
+                "2",
+                "-pix_fmt",
+                "yuv420p",
+                "-color_primaries",
+                "bt709",
+                "-color_trc",
+                "bt709",
+                "-colorspace",
+                "bt709",
+                "-c:a",
+                "libopus",
+                "-b:a",
+                "128k",
+                "-f",
+                "webm",
+            ],
+            // Sony XDCAM HD422 constant 50 Mb/s long-GOP profile
+            OutputFormat::MxfXdcamHd50 => &[
+                "-c:v",
+                "mpeg2video",
+                "-pix_fmt",
+                "yuv422p",
+                "-b:v",
+                "50M",
+                "-minrate",
+                "50M",
+                "-maxrate",
+                "50M",
+                "-bufsize",
+                "17825792",
+                "-rc_init_occupancy",
+                "17825792",
+                "-g",
+                "12",
+                "-bf",
+                "2",
+                "-intra_vlc",
+                "1",
+                "-non_linear_quant",
+                "1",
+                "-dc",
+                "10",
+                "-color_primaries",
+                "bt709",
+                "-color_trc",
+                "bt709",
+                "-colorspace",
+                "bt709",
+                "-signal_standard",
+                "smpte274m",
+                "-c:a",
+                "pcm_s24le",
+                "-ar",
+                "48000",
+                "-f",
+                "mxf",
+            ],
+            OutputFormat::MxfAvcIntra100 => &[
+                "-c:v",
+                "libx264",
+                "-pix_fmt",
+                "yuv422p10le",
+                "-avcintra-class",
+                "100",
+                "-color_primaries",
+                "bt709",
+                "-color_trc",
+                "bt709",
+                "-colorspace",
+                "bt709",
+                "-signal_standard",
+                "smpte274m",
+                "-c:a",
+                "pcm_s24le",
+                "-ar",
+                "48000",
+                "-f",
+                "mxf",
+            ],
+            OutputFormat::ImfJpeg2000 => &[
+                "-c:v",
+                "libopenjpeg",
+                "-pix_fmt",
+                "rgb48le",
+                "-color_primaries",
+                "bt709",
+                "-color_trc",
+                "bt709",
+                "-colorspace",
+                "rgb",
+                "-an",
+                "-f",
+                "mxf",
+            ],
+            OutputFormat::ImfProRes => &[
+                "-c:v",
+                "prores_ks",
+                "-profile:v",
+                "4444",
+                "-vendor",
+                "apl0",
+                "-pix_fmt",
+                "yuv444p10le",
+                "-color_primaries",
+                "bt709",
+                "-color_trc",
+                "bt709",
+                "-colorspace",
+                "bt709",
+                "-an",
+                "-f",
+                "mxf",
+            ],
+        }
+    }
+}
+
+// Display field order; coded-first order only matters to the encoder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FieldOrder {
+    #[default]
+    Progressive,
+    TopFirst,
+    BottomFirst,
+}
+
+impl FieldOrder {
+    // ffprobe's field_order; "bt" is bottom coded first but top displayed first
+    pub fn from_probe(value: &str) -> Option<Self> {
+        match value {
+            "progressive" => Some(FieldOrder::Progressive),
+            "tt" | "bt" => Some(FieldOrder::TopFirst),
+            "bb" | "tb" => Some(FieldOrder::BottomFirst),
+            _ => None,
+        }
+    }
+
+    pub fn as_ffmpeg(&self) -> &'static str {
+        match self {
+            FieldOrder::Progressive => "progressive",
+            FieldOrder::TopFirst => "tt",
+            FieldOrder::BottomFirst => "bb",
+        }
+    }
+}
+
+// Picture metadata of a video stream; None where the container doesn't say
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PictureTags {
+    pub field_order: Option<FieldOrder>,
+    pub sample_aspect: Option<(u32, u32)>,
+    pub color_primaries: Option<String>,
+    pub color_transfer: Option<String>,
+    pub color_space: Option<String>,
+    // Display rotation in degrees from side data or the legacy rotate tag
+    pub rotation: Option<i32>,
+}
+
+// Fixed name parts and extension casing some ingest systems insist on
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NamingRules {
+    pub prefix: String,
+    pub suffix: String,
+    pub uppercase_extension: bool,
+}
+
+impl NamingRules {
+    pub fn apply(&self, base_name: &str) -> String {
+        sanitize_base_name(&format!("{}{}{}", self.prefix, base_name, self.suffix))
+    }
+
+    pub fn extension(&self, format: OutputFormat) -> String {
+        if self.uppercase_extension {
+            format.extension().to_uppercase()
+        } else {
+            format.extension().to_string()
+        }
+    }
+
+    // Characters a filesystem would reject are replaced, which the operator should know about
+    pub fn validate(&self) -> Option<String> {
+        [("Prefix", &self.prefix), ("Suffix", &self.suffix)]
+            .into_iter()
+            .find(|(_, part)| sanitize_base_name(part) != part.trim() && !part.trim().is_empty())
+            .map(|(label, _)| format!("{} contains characters not allowed in file names", label))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Addition,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 4] = [
+        BlendMode::Normal,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Addition,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlendMode::Normal => "Normal",
+            BlendMode::Multiply => "Multiply",
+            BlendMode::Screen => "Screen",
+            BlendMode::Addition => "Addition",
+        }
+    }
+
+    // all_mode of FFmpeg's blend filter; Normal goes through overlay instead
+    pub fn blend_filter_mode(&self) -> Option<&'static str> {
+        match self {
+            BlendMode::Normal => None,
+            BlendMode::Multiply => Some("multiply"),
+            BlendMode::Screen => Some("screen"),
+            BlendMode::Addition => Some("addition"),
+        }
+    }
+
+    // Colour that leaves the picture unchanged, used behind transparent overlay areas
+    pub fn neutral_color(&self) -> &'static str {
+        match self {
+            BlendMode::Multiply => "white",
+            _ => "black",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExrCompression {
+    None,
+    Rle,
+    // Scanline ZIP, which compositing apps read fastest
+    Zip1,
+    Zip16,
+}
+
+impl ExrCompression {
+    pub const ALL: [ExrCompression; 4] = [
+        ExrCompression::None,
+        ExrCompression::Rle,
+        ExrCompression::Zip1,
+        ExrCompression::Zip16,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExrCompression::None => "None",
+            ExrCompression::Rle => "RLE",
+            ExrCompression::Zip1 => "ZIP (1 line)",
+            ExrCompression::Zip16 => "ZIP (16 lines)",
+        }
+    }
+
+    pub fn ffmpeg_name(&self) -> &'static str {
+        match self {
+            ExrCompression::None => "none",
+            ExrCompression::Rle => "rle",
+            ExrCompression::Zip1 => "zip1",
+            ExrCompression::Zip16 => "zip16",
+        }
+    }
+
+    // Rough output/raw size on graded plates, for storage estimates only
+    fn size_ratio(&self) -> f64 {
+        match self {
+            ExrCompression::None => 1.0,
+            ExrCompression::Rle => 0.9,
+            ExrCompression::Zip1 => 0.65,
+            ExrCompression::Zip16 => 0.6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DnxhrProfile {
+    Lb,
+    Sq,
+    Hq,
+    Dnx444,
+}
+
+impl DnxhrProfile {
+    pub const ALL: [DnxhrProfile; 4] = [
+        DnxhrProfile::Lb,
+        DnxhrProfile::Sq,
+        DnxhrProfile::Hq,
+        DnxhrProfile::Dnx444,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DnxhrProfile::Lb => "LB (offline)",
+            DnxhrProfile::Sq => "SQ",
+            DnxhrProfile::Hq => "HQ",
+            DnxhrProfile::Dnx444 => "444 (10-bit)",
+        }
+    }
+
+    pub fn ffmpeg_name(&self) -> &'static str {
+        match self {
+            DnxhrProfile::Lb => "dnxhr_lb",
+            DnxhrProfile::Sq => "dnxhr_sq",
+            DnxhrProfile::Hq => "dnxhr_hq",
+            DnxhrProfile::Dnx444 => "dnxhr_444",
+        }
+    }
+
+    pub fn pix_fmt(&self) -> &'static str {
+        match self {
+            DnxhrProfile::Dnx444 => "yuv444p10le",
+            _ => "yuv422p",
+        }
+    }
+
+    // Avid's 1080p 24 fps figures
+    pub fn bitrate_mbps(&self) -> u32 {
+        match self {
+            DnxhrProfile::Lb => 36,
+            DnxhrProfile::Sq => 116,
+            DnxhrProfile::Hq => 176,
+            DnxhrProfile::Dnx444 => 352,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HevcProfile {
+    Main,
+    Main10,
+}
+
+impl HevcProfile {
+    pub const ALL: [HevcProfile; 2] = [HevcProfile::Main, HevcProfile::Main10];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HevcProfile::Main => "Main (8-bit)",
+            HevcProfile::Main10 => "Main 10 (10-bit)",
+        }
+    }
+
+    pub fn ffmpeg_name(&self) -> &'static str {
+        match self {
+            HevcProfile::Main => "main",
+            HevcProfile::Main10 => "main10",
+        }
+    }
+
+    pub fn pix_fmt(&self) -> &'static str {
+        match self {
+            HevcProfile::Main => "yuv420p",
+            HevcProfile::Main10 => "yuv420p10le",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Av1Speed {
+    Quality,
+    Balanced,
+    Fast,
+}
+
+impl Av1Speed {
+    pub const ALL: [Av1Speed; 3] = [Av1Speed::Quality, Av1Speed::Balanced, Av1Speed::Fast];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Av1Speed::Quality => "Quality (slow)",
+            Av1Speed::Balanced => "Balanced",
+            Av1Speed::Fast => "Fast",
+        }
+    }
+
+    // (option, value) for the encoder; SVT-AV1 presets run 0-13, libaom's cpu-used 0-8
+    pub fn encoder_arg(&self, format: OutputFormat) -> (&'static str, &'static str) {
+        match (format, self) {
+            (OutputFormat::Mp4Av1Aom, Av1Speed::Quality) => ("-cpu-used", "2"),
+            (OutputFormat::Mp4Av1Aom, Av1Speed::Balanced) => ("-cpu-used", "4"),
+            (OutputFormat::Mp4Av1Aom, Av1Speed::Fast) => ("-cpu-used", "6"),
+            (_, Av1Speed::Quality) => ("-preset", "4"),
+            (_, Av1Speed::Balanced) => ("-preset", "8"),
+            (_, Av1Speed::Fast) => ("-preset", "12"),
+        }
+    }
+}
+
+// Quality target or fixed rate, for the codecs that offer both
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RateControl {
+    // 0 is lossless; around 18 looks the same as the source
+    Crf(u8),
+    BitrateMbps(u32),
+}
+
+impl Default for RateControl {
+    fn default() -> Self {
+        RateControl::Crf(18)
+    }
+}
+
+impl RateControl {
+    // x265 reaches the same look a couple of steps higher than x264
+    pub fn default_for(format: OutputFormat) -> Self {
+        match format {
+            OutputFormat::Mp4Hevc => RateControl::Crf(20),
+            _ => RateControl::default(),
+        }
+    }
+
+    pub fn args(&self) -> Vec<String> {
+        match *self {
+            RateControl::Crf(crf) => vec!["-crf".to_string(), crf.min(51).to_string()],
+            // Capped so players with small buffers get a steady rate
+            RateControl::BitrateMbps(mbps) => vec![
+                "-b:v".to_string(),
+                format!("{}M", mbps),
+                "-maxrate".to_string(),
+                format!("{}M", mbps),
+                "-bufsize".to_string(),
+                format!("{}M", mbps * 2),
+            ],
+        }
+    }
+
+    // CRF sizes depend on the picture; this is a 1080p guess that halves every 6 steps
+    pub fn estimated_mbps(&self) -> u32 {
+        match *self {
+            RateControl::Crf(crf) => {
+                (40.0 * 0.5f64.powf((crf as f64 - 12.0) / 6.0)).max(1.0) as u32
+            }
+            RateControl::BitrateMbps(mbps) => mbps,
+        }
+    }
+}
+
+// Choices within a format that don't warrant a variant of their own
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatOptions {
+    pub exr_half_float: bool,
+    pub exr_compression: ExrCompression,
+    // Cineon-style printing density code values instead of video levels
+    pub dpx_log: bool,
+    // ffmpeg's -q:v scale, 2 (best) to 31
+    pub jpeg_quality: u8,
+    pub dnxhr_profile: DnxhrProfile,
+    pub h264_rate: RateControl,
+    pub hevc_profile: HevcProfile,
+    pub hevc_rate: RateControl,
+    pub av1_speed: Av1Speed,
+    // 0-63 for both AV1 encoders
+    pub av1_crf: u8,
+    // With a sequence, also encode an H.264 review file from the finished frames
+    pub review_mp4: bool,
+    // 0-63, lower is better
+    pub vp9_crf: u8,
+    // Also encode a small VP9 WebM from the finished delivery, for web embedding
+    pub review_webm: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            exr_half_float: true,
+            exr_compression: ExrCompression::Zip1,
+            dpx_log: false,
+            jpeg_quality: 3,
+            dnxhr_profile: DnxhrProfile::Hq,
+            h264_rate: RateControl::default(),
+            hevc_profile: HevcProfile::Main10,
+            // x265 at 20 looks about like x264 at 18, at roughly half the size
+            hevc_rate: RateControl::default_for(OutputFormat::Mp4Hevc),
+            av1_speed: Av1Speed::Balanced,
+            av1_crf: 30,
+            review_mp4: false,
+            vp9_crf: 32,
+            review_webm: false,
+        }
+    }
+}
+
+impl FormatOptions {
+    // Go after the format's codec_args
+    pub fn args(&self, format: OutputFormat) -> Vec<String> {
+        let args = match format {
+            OutputFormat::ExrSequence => vec![
+                "-format",
+                if self.exr_half_float { "half" } else { "float" },
+                "-compression",
+                self.exr_compression.ffmpeg_name(),
+            ],
+            OutputFormat::DpxSequence => {
+                vec!["-color_trc", if self.dpx_log { "log" } else { "bt709" }]
+            }
+            OutputFormat::JpegSequence => {
+                return vec![
+                    "-q:v".to_string(),
+                    self.jpeg_quality.clamp(2, 31).to_string(),
+                ];
+            }
+            OutputFormat::Mp4H264 => return self.h264_rate.args(),
+            OutputFormat::Mp4Av1Svt | OutputFormat::Mp4Av1Aom => {
+                let (option, value) = self.av1_speed.encoder_arg(format);
+                return vec![
+                    option.to_string(),
+                    value.to_string(),
+                    "-crf".to_string(),
+                    self.av1_crf.min(63).to_string(),
+                ];
+            }
+            OutputFormat::WebmVp9 => {
+                return vec!["-crf".to_string(), self.vp9_crf.min(63).to_string()];
+            }
+            OutputFormat::Mp4Hevc => {
+                let mut args: Vec<String> = [
+                    "-profile:v",
+                    self.hevc_profile.ffmpeg_name(),
+                    "-pix_fmt",
+                    self.hevc_profile.pix_fmt(),
+                ]
+                .map(str::to_string)
+                .into();
+                args.extend(self.hevc_rate.args());
+                return args;
+            }
+            OutputFormat::MovDnxhr | OutputFormat::MxfDnxhr => vec![
+                "-profile:v",
+                self.dnxhr_profile.ffmpeg_name(),
+                "-pix_fmt",
+                self.dnxhr_profile.pix_fmt(),
+            ],
+            _ => Vec::new(),
+        };
+        args.into_iter().map(str::to_string).collect()
+    }
+
+    // Pixel format the filter graph ends in
+    pub fn pix_fmt(&self, format: OutputFormat) -> &'static str {
+        match format {
+            OutputFormat::MovDnxhr | OutputFormat::MxfDnxhr => self.dnxhr_profile.pix_fmt(),
+            OutputFormat::Mp4Hevc => self.hevc_profile.pix_fmt(),
+            _ => format.pix_fmt(),
+        }
+    }
+
+    pub fn bitrate_mbps(&self, format: OutputFormat) -> Option<u32> {
+        match format {
+            OutputFormat::MovDnxhr | OutputFormat::MxfDnxhr => {
+                Some(self.dnxhr_profile.bitrate_mbps())
+            }
+            OutputFormat::Mp4H264 => Some(self.h264_rate.estimated_mbps()),
+            OutputFormat::Mp4Hevc => Some(match self.hevc_rate {
+                RateControl::Crf(_) => (self.hevc_rate.estimated_mbps() / 2).max(1),
+                RateControl::BitrateMbps(mbps) => mbps,
+            }),
+            _ => format.bitrate_mbps(),
+        }
+    }
+
+    // Appended to the graph after the overlay, before the final pixel format.
+    // The log curve linearises with a 2.4 display gamma, then maps 0..1 to the
+    // Cineon 95..685 code range (10-bit, 0.002 density per code, 0.6 gamma).
+    pub fn output_filters(&self, format: OutputFormat) -> &'static str {
+        match format {
+            OutputFormat::DpxSequence if self.dpx_log => {
+                ",lutrgb=\
+                 r='maxval*(685+130.288*log(pow(val/maxval,2.4)*0.9892+0.0108))/1023':\
+                 g='maxval*(685+130.288*log(pow(val/maxval,2.4)*0.9892+0.0108))/1023':\
+                 b='maxval*(685+130.288*log(pow(val/maxval,2.4)*0.9892+0.0108))/1023'"
+            }
+            _ => "",
+        }
+    }
+
+    // Estimated size of one frame of an image sequence
+    pub fn sequence_frame_bytes(&self, format: OutputFormat, width: u32, height: u32) -> u64 {
+        let pixels = width as u64 * height as u64;
+        match format {
+            OutputFormat::ExrSequence => {
+                let bytes_per_pixel = if self.exr_half_float { 6.0 } else { 12.0 };
+                (pixels as f64 * bytes_per_pixel * self.exr_compression.size_ratio()) as u64
+            }
+            // Three 10-bit samples packed per 32-bit word, plus the 2 KB header
+            OutputFormat::DpxSequence => pixels * 4 + 2048,
+            // About 0.6 bytes per pixel at q 2, falling off roughly with the quantiser
+            OutputFormat::JpegSequence => {
+                (pixels as f64 * 1.2 / self.jpeg_quality.clamp(2, 31) as f64) as u64
+            }
+            // Updated for 16-bit RGB (6 bytes per pixel instead of 4)
+            _ => pixels * 6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AlphaMode {
+    #[default]
+    Straight,
+    Premultiplied,
+}
+
+impl AlphaMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlphaMode::Straight => "Straight alpha",
+            AlphaMode::Premultiplied => "Premultiplied alpha",
+        }
+    }
+}
+
+// How a source of another aspect goes onto a preset's raster
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum FitMode {
+    // Fitted inside with bars
+    #[default]
+    Pad,
+    // Fills the raster, cutting the overhang off both sides
+    Crop,
+    // Fills the raster, distorting the picture
+    Stretch,
+}
+
+impl FitMode {
+    pub const ALL: [FitMode; 3] = [FitMode::Pad, FitMode::Crop, FitMode::Stretch];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FitMode::Pad => "Pad (letterbox)",
+            FitMode::Crop => "Center crop",
+            FitMode::Stretch => "Stretch",
+        }
+    }
+
+    // Scale chain onto `width`x`height`; bars are filled with `fill`
+    pub fn filter(&self, (width, height): (u32, u32), flags: &str, fill: &str) -> String {
+        match self {
+            FitMode::Pad => format!(
+                "scale={w}:{h}:flags={flags}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2:color={fill}",
+                w = width,
+                h = height,
+                flags = flags,
+                fill = fill
+            ),
+            FitMode::Crop => format!(
+                "scale={w}:{h}:flags={flags}:force_original_aspect_ratio=increase,crop={w}:{h}",
+                w = width,
+                h = height,
+                flags = flags
+            ),
+            // scale keeps the display aspect by tagging non-square pixels otherwise
+            FitMode::Stretch => format!(
+                "scale={}:{}:flags={},setsar=1",
+                width, height, flags
+            ),
+        }
+    }
+}
+
+// Inclusive span of delivery frames, counted from 0 like the sequence numbering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameRange {
+    pub first: u32,
+    pub last: u32,
+}
+
+impl FrameRange {
+    // Timeline option for filters that support it. `n` restarts at 0 in a seeked
+    // graph, so `offset` is the delivery frame the graph starts on.
+    pub fn enable_option(&self, offset: u32) -> String {
+        format!(
+            ":enable='between(n,{},{})'",
+            self.first as i64 - offset as i64,
+            self.last as i64 - offset as i64
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlayStyle {
+    pub blend: BlendMode,
+    // How the overlay asset was authored
+    pub alpha: AlphaMode,
+    // Burn the overlay only on these frames, e.g. a slate on the first 48
+    pub frames: Option<FrameRange>,
+}
+
+// One burn-in layer: a PNG and how it goes over what's beneath
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OverlaySpec {
+    pub image: PathBuf,
+    #[serde(default)]
+    pub style: OverlayStyle,
+}
+
+// A single still looped into a clip, for cards and test patterns
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StillHold {
+    pub seconds: f64,
+    pub fps: f64,
+}
+
+impl Default for StillHold {
+    fn default() -> Self {
+        Self {
+            seconds: 10.0,
+            fps: 24.0,
+        }
+    }
+}
+
+impl StillHold {
+    // Go before the still's -i; image2 repeats the picture at `fps` until `seconds` run out
+    pub fn input_args(&self) -> [String; 6] {
+        [
+            "-loop".to_string(),
+            "1".to_string(),
+            "-framerate".to_string(),
+            format!("{:.3}", self.fps),
+            "-t".to_string(),
+            format!("{:.6}", self.seconds),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PatternKind {
+    SmpteHdBars,
+    TestSrc2,
+}
+
+impl PatternKind {
+    pub const ALL: [PatternKind; 2] = [PatternKind::SmpteHdBars, PatternKind::TestSrc2];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PatternKind::SmpteHdBars => "SMPTE HD Bars",
+            PatternKind::TestSrc2 => "Test Pattern",
+        }
+    }
+
+    pub fn lavfi_name(&self) -> &'static str {
+        match self {
+            PatternKind::SmpteHdBars => "smptehdbars",
+            PatternKind::TestSrc2 => "testsrc2",
+        }
+    }
+
+    pub fn file_tag(&self) -> &'static str {
+        match self {
+            PatternKind::SmpteHdBars => "bars",
+            PatternKind::TestSrc2 => "testpattern",
+        }
+    }
+}
+
+// Calibration leader rendered by ffmpeg in place of a source file
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestPattern {
+    pub kind: PatternKind,
+    pub size: (u32, u32),
+    pub fps: f64,
+    pub seconds: f64,
+    // 1 kHz reference tone at -20 dBFS, for formats that carry audio
+    pub tone: bool,
+}
+
+impl Default for TestPattern {
+    fn default() -> Self {
+        Self {
+            kind: PatternKind::SmpteHdBars,
+            size: (1920, 1080),
+            fps: 24.0,
+            seconds: 30.0,
+            tone: true,
+        }
+    }
+}
+
+impl TestPattern {
+    // Replaces the source's -i
+    pub fn input_args(&self) -> [String; 4] {
+        [
+            "-f".to_string(),
+            "lavfi".to_string(),
+            "-i".to_string(),
+            format!(
+                "{}=size={}x{}:rate={:.3}:duration={:.6}",
+                self.kind.lavfi_name(),
+                self.size.0,
+                self.size.1,
+                self.fps,
+                self.seconds
+            ),
+        ]
+    }
+
+    // Goes after the overlay's -i, making the tone input 2
+    pub fn tone_args(&self) -> Option<[String; 4]> {
+        self.tone.then(|| {
+            [
+                "-f".to_string(),
+                "lavfi".to_string(),
+                "-i".to_string(),
+                format!(
+                    "aevalsrc=0.1*sin(2*PI*1000*t):s=48000:d={:.6}",
+                    self.seconds
+                ),
+            ]
+        })
+    }
+}
+
+// Countdown in front of the program, counting the seconds down to a 2-pop two seconds
+// before the first frame, as the universal leader does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeaderSpec {
+    pub seconds: u32,
+}
+
+impl Default for LeaderSpec {
+    fn default() -> Self {
+        Self { seconds: 8 }
+    }
+}
+
+impl LeaderSpec {
+    pub fn frames(&self, fps: f64) -> u32 {
+        (self.seconds as f64 * fps).round() as u32
+    }
+
+    // Numerals from `seconds` down to 3, then the "2" for a single frame and black
+    pub fn video_filter(&self, (width, height): (u32, u32), fps: f64, pix_fmt: &str) -> String {
+        let pop = self.seconds.saturating_sub(2);
+        format!(
+            "color=c=black:s={w}x{h}:r={fps:.3}:d={secs},\
+             drawtext=text='%{{eif\\:{secs}-trunc(t)\\:d}}':fontcolor=white:fontsize={size}:\
+             x=(w-text_w)/2:y=(h-text_h)/2:enable='lt(t,{pop}+1/{fps:.3})',\
+             format={pix_fmt},setsar=1",
+            w = width,
+            h = height,
+            fps = fps,
+            secs = self.seconds,
+            size = height / 2,
+            pop = pop,
+            pix_fmt = pix_fmt
+        )
+    }
+
+    // One frame of 1 kHz at -20 dBFS on the "2", silence otherwise
+    pub fn pop_filter(&self, fps: f64) -> String {
+        let pop = self.seconds.saturating_sub(2);
+        format!(
+            "aevalsrc='0.1*sin(2*PI*1000*t)*between(t,{pop},{pop}+1/{fps:.3})':s=48000:d={secs}",
+            pop = pop,
+            fps = fps,
+            secs = self.seconds
+        )
+    }
+}
+
+// Framing matte burnt over the source picture, e.g. 2.39:1 bars for review copies
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MatteSpec {
+    pub aspect: f32,
+    pub opacity: f32,
+    #[serde(default)]
+    pub frames: Option<FrameRange>,
+}
+
+impl MatteSpec {
+    pub const PRESETS: [(f32, &'static str); 5] = [
+        (2.39, "2.39:1 Scope"),
+        (2.35, "2.35:1"),
+        (1.85, "1.85:1 Flat"),
+        (1.78, "16:9"),
+        (1.33, "4:3"),
+    ];
+
+    pub fn label(&self) -> String {
+        Self::PRESETS
+            .iter()
+            .find(|(aspect, _)| (aspect - self.aspect).abs() < 0.005)
+            .map(|(_, label)| label.to_string())
+            .unwrap_or_else(|| format!("{:.2}:1", self.aspect))
+    }
+
+    // drawbox chain for a picture of `size`; None when the picture already has that aspect.
+    // Box sizes are computed here because drawbox treats a size of 0 as the full frame.
+    pub fn drawbox_filter(&self, (width, height): (u32, u32), frame_offset: u32) -> Option<String> {
+        let color = format!("black@{:.2}", self.opacity.clamp(0.0, 1.0));
+        let enable = self
+            .frames
+            .map(|frames| frames.enable_option(frame_offset))
+            .unwrap_or_default();
+        let picture_aspect = width as f32 / height.max(1) as f32;
+
+        if self.aspect > picture_aspect {
+            let bar = ((height as f32 - width as f32 / self.aspect) / 2.0).round() as u32;
+            (bar > 0).then(|| {
+                format!(
+                    "drawbox=x=0:y=0:w={w}:h={bar}:color={c}:t=fill{e},drawbox=x=0:y={y}:w={w}:h={bar}:color={c}:t=fill{e}",
+                    w = width,
+                    bar = bar,
+                    y = height - bar,
+                    c = color,
+                    e = enable
+                )
+            })
+        } else {
+            let bar = ((width as f32 - height as f32 * self.aspect) / 2.0).round() as u32;
+            (bar > 0).then(|| {
+                format!(
+                    "drawbox=x=0:y=0:w={bar}:h={h}:color={c}:t=fill{e},drawbox=x={x}:y=0:w={bar}:h={h}:color={c}:t=fill{e}",
+                    h = height,
+                    bar = bar,
+                    x = width - bar,
+                    c = color,
+                    e = enable
+                )
+            })
         }
     }
 }
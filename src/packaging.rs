@@ -0,0 +1,381 @@
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sha1::{Digest, Sha1};
+use std::{
+    fs,
+    io::{self, BufReader, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    errors::{EncoderError, IoSide},
+    timecode::TimecodeRate,
+    utils::civil_date,
+};
+
+// Constrained IMF-like package: one image track file, no audio or subtitle
+// tracks, and a single-segment CPL. Good enough for ingest systems that only
+// check the ASSETMAP/PKL/CPL structure and hashes.
+pub struct ImfPackage<'a> {
+    pub title: &'a str,
+    pub essence: &'a Path,
+    pub frame_rate: f64,
+    pub duration_frames: u32,
+}
+
+fn urn_uuid() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes)
+        .map_err(|e| anyhow::anyhow!("Failed to generate package id: {}", e))?;
+    // RFC 4122 version 4, variant 1
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    Ok(format_urn(&bytes))
+}
+
+fn format_urn(bytes: &[u8; 16]) -> String {
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!(
+        "urn:uuid:{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+// Source Package set, ignoring the registry version byte
+const SOURCE_PACKAGE_KEY: ([u8; 6], [u8; 8]) = (
+    [0x06, 0x0e, 0x2b, 0x34, 0x02, 0x53],
+    [0x0d, 0x01, 0x01, 0x01, 0x01, 0x01, 0x37, 0x00],
+);
+const PACKAGE_UID_TAG: u16 = 0x4401;
+
+fn read_ber_length(reader: &mut impl Read) -> io::Result<u64> {
+    let mut first = [0u8; 1];
+    reader.read_exact(&mut first)?;
+    if first[0] < 0x80 {
+        return Ok(first[0] as u64);
+    }
+    let mut length = 0u64;
+    for _ in 0..(first[0] & 0x7f) {
+        let mut byte = [0u8; 1];
+        reader.read_exact(&mut byte)?;
+        length = length << 8 | byte[0] as u64;
+    }
+    Ok(length)
+}
+
+// The track file id ingest checks against is the material number of the MXF's
+// file package UMID, so it is read back from what ffmpeg wrote rather than made up
+fn mxf_file_package_id(path: &Path) -> Result<String> {
+    let file = fs::File::open(path).map_err(|e| EncoderError::from_io(&e, path, IoSide::Output))?;
+    let mut reader = BufReader::new(file);
+    let mut key = [0u8; 16];
+    while reader.read_exact(&mut key).is_ok() {
+        let length = read_ber_length(&mut reader)?;
+        if key[..6] != SOURCE_PACKAGE_KEY.0 || key[8..] != SOURCE_PACKAGE_KEY.1 {
+            reader.seek(SeekFrom::Current(length as i64))?;
+            continue;
+        }
+
+        let mut set = vec![0u8; length as usize];
+        reader.read_exact(&mut set)?;
+        let mut rest = &set[..];
+        while rest.len() >= 4 {
+            let tag = u16::from_be_bytes([rest[0], rest[1]]);
+            let size = u16::from_be_bytes([rest[2], rest[3]]) as usize;
+            let Some(value) = rest.get(4..4 + size) else {
+                break;
+            };
+            if tag == PACKAGE_UID_TAG && size == 32 {
+                let mut material = [0u8; 16];
+                material.copy_from_slice(&value[16..]);
+                return Ok(format_urn(&material));
+            }
+            rest = &rest[4 + size..];
+        }
+    }
+    Err(EncoderError::source_unreadable(path, "no file package in the MXF header").into())
+}
+
+// ISO 8601 UTC timestamp for IssueDate
+fn issue_date() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let (year, month, day) = civil_date(secs);
+    let rem = secs.rem_euclid(86_400);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+// NTSC rates are carried as N*1000/1001, everything else as a whole number
+fn edit_rate(frame_rate: f64) -> String {
+    let rate = TimecodeRate::from_fps(frame_rate);
+    if rate.is_ntsc() {
+        format!("{} 1001", rate.nominal * 1000)
+    } else {
+        format!("{} 1", rate.nominal)
+    }
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn sha1_base64(path: &Path) -> Result<String> {
+    let mut file =
+        fs::File::open(path).map_err(|e| EncoderError::from_io(&e, path, IoSide::Output))?;
+    let mut hasher = Sha1::new();
+    io::copy(&mut file, &mut hasher)?;
+    Ok(STANDARD.encode(hasher.finalize()))
+}
+
+struct Asset {
+    id: String,
+    file_name: String,
+    size: u64,
+    hash: String,
+    mime: &'static str,
+}
+
+impl Asset {
+    fn from_file(id: String, path: &Path, mime: &'static str) -> Result<Self> {
+        Ok(Self {
+            id,
+            file_name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            size: fs::metadata(path)?.len(),
+            hash: sha1_base64(path)?,
+            mime,
+        })
+    }
+}
+
+fn cpl_xml(
+    package: &ImfPackage,
+    cpl_id: &str,
+    track_file_id: &str,
+    issue_date: &str,
+) -> Result<String> {
+    let title = escape_xml(package.title);
+    let rate = edit_rate(package.frame_rate);
+    Ok(format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<CompositionPlaylist xmlns="http://www.smpte-ra.org/schemas/2067-3/2016" xmlns:cc="http://www.smpte-ra.org/schemas/2067-2/2016" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <Id>{cpl_id}</Id>
+  <Annotation>{title}</Annotation>
+  <IssueDate>{issue_date}</IssueDate>
+  <Creator>Delivery Encoder {version}</Creator>
+  <ContentTitle>{title}</ContentTitle>
+  <EditRate>{rate}</EditRate>
+  <SegmentList>
+    <Segment>
+      <Id>{segment_id}</Id>
+      <SequenceList>
+        <cc:MainImageSequence>
+          <Id>{sequence_id}</Id>
+          <TrackId>{track_id}</TrackId>
+          <ResourceList>
+            <Resource xsi:type="TrackFileResourceType">
+              <Id>{resource_id}</Id>
+              <EditRate>{rate}</EditRate>
+              <IntrinsicDuration>{duration}</IntrinsicDuration>
+              <TrackFileId>{track_file_id}</TrackFileId>
+            </Resource>
+          </ResourceList>
+        </cc:MainImageSequence>
+      </SequenceList>
+    </Segment>
+  </SegmentList>
+</CompositionPlaylist>
+"#,
+        version = env!("CARGO_PKG_VERSION"),
+        segment_id = urn_uuid()?,
+        sequence_id = urn_uuid()?,
+        track_id = urn_uuid()?,
+        resource_id = urn_uuid()?,
+        duration = package.duration_frames,
+    ))
+}
+
+fn pkl_xml(pkl_id: &str, title: &str, issue_date: &str, assets: &[Asset]) -> String {
+    let entries: String = assets
+        .iter()
+        .map(|asset| {
+            format!(
+                "    <Asset>\n      <Id>{}</Id>\n      <Hash>{}</Hash>\n      <Size>{}</Size>\n      <Type>{}</Type>\n      <OriginalFileName>{}</OriginalFileName>\n    </Asset>\n",
+                asset.id,
+                asset.hash,
+                asset.size,
+                asset.mime,
+                escape_xml(&asset.file_name)
+            )
+        })
+        .collect();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<PackingList xmlns="http://www.smpte-ra.org/schemas/2067-2/2016/PKL">
+  <Id>{pkl_id}</Id>
+  <Annotation>{title}</Annotation>
+  <IssueDate>{issue_date}</IssueDate>
+  <Issuer>Delivery Encoder</Issuer>
+  <Creator>Delivery Encoder {version}</Creator>
+  <AssetList>
+{entries}  </AssetList>
+</PackingList>
+"#,
+        title = escape_xml(title),
+        version = env!("CARGO_PKG_VERSION"),
+    )
+}
+
+fn assetmap_xml(title: &str, issue_date: &str, pkl: &Asset, assets: &[Asset]) -> Result<String> {
+    let entry = |asset: &Asset, packing_list: bool| {
+        format!(
+            "    <Asset>\n      <Id>{}</Id>\n{}      <ChunkList>\n        <Chunk>\n          <Path>{}</Path>\n        </Chunk>\n      </ChunkList>\n    </Asset>\n",
+            asset.id,
+            if packing_list { "      <PackingList>true</PackingList>\n" } else { "" },
+            escape_xml(&asset.file_name)
+        )
+    };
+    let entries: String = std::iter::once(entry(pkl, true))
+        .chain(assets.iter().map(|asset| entry(asset, false)))
+        .collect();
+
+    Ok(format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<AssetMap xmlns="http://www.smpte-ra.org/schemas/429-9/2007/AM">
+  <Id>{id}</Id>
+  <Annotation>{title}</Annotation>
+  <Creator>Delivery Encoder {version}</Creator>
+  <VolumeCount>1</VolumeCount>
+  <IssueDate>{issue_date}</IssueDate>
+  <Issuer>Delivery Encoder</Issuer>
+  <AssetList>
+{entries}  </AssetList>
+</AssetMap>
+"#,
+        id = urn_uuid()?,
+        title = escape_xml(title),
+        version = env!("CARGO_PKG_VERSION"),
+    ))
+}
+
+// Moves the essence into `<output_dir>/<title>_IMF` and writes CPL, PKL and ASSETMAP next to it
+pub fn write_imf_package(output_dir: &Path, package: &ImfPackage) -> Result<PathBuf> {
+    let package_dir = output_dir.join(format!("{}_IMF", package.title));
+    fs::create_dir_all(&package_dir)
+        .map_err(|e| EncoderError::from_io(&e, &package_dir, IoSide::Output))?;
+
+    let essence_name = format!("{}_video.mxf", package.title);
+    let essence = package_dir.join(&essence_name);
+    fs::rename(package.essence, &essence)
+        .map_err(|e| EncoderError::from_io(&e, &package_dir, IoSide::Output))?;
+    // The same id goes in the CPL, PKL and ASSETMAP
+    let track_file_id = mxf_file_package_id(&essence)?;
+
+    let issue_date = issue_date();
+    let cpl_id = urn_uuid()?;
+    let cpl_path = package_dir.join(format!("CPL_{}.xml", &cpl_id[9..]));
+    fs::write(
+        &cpl_path,
+        cpl_xml(package, &cpl_id, &track_file_id, &issue_date)?,
+    )?;
+
+    let assets = vec![
+        Asset::from_file(cpl_id, &cpl_path, "text/xml")?,
+        Asset::from_file(track_file_id, &essence, "application/mxf")?,
+    ];
+
+    let pkl_id = urn_uuid()?;
+    let pkl_path = package_dir.join(format!("PKL_{}.xml", &pkl_id[9..]));
+    fs::write(
+        &pkl_path,
+        pkl_xml(&pkl_id, package.title, &issue_date, &assets),
+    )?;
+    let pkl = Asset::from_file(pkl_id, &pkl_path, "text/xml")?;
+
+    fs::write(
+        package_dir.join("ASSETMAP.xml"),
+        assetmap_xml(package.title, &issue_date, &pkl, &assets)?,
+    )?;
+
+    Ok(package_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn klv(key: [u8; 16], value: &[u8]) -> Vec<u8> {
+        let mut bytes = key.to_vec();
+        bytes.extend([0x83, 0, (value.len() >> 8) as u8, value.len() as u8]);
+        bytes.extend(value);
+        bytes
+    }
+
+    #[test]
+    fn reads_the_file_package_material_number() {
+        let (prefix, suffix) = SOURCE_PACKAGE_KEY;
+        let mut source_package = [0u8; 16];
+        source_package[..6].copy_from_slice(&prefix);
+        source_package[6..8].copy_from_slice(&[0x01, 0x01]);
+        source_package[8..].copy_from_slice(&suffix);
+        let mut material_package = source_package;
+        material_package[14] = 0x36;
+
+        let mut umid = vec![0x06, 0x0a, 0x2b, 0x34, 0x01, 0x01, 0x01, 0x05];
+        umid.extend([0x01, 0x01, 0x0d, 0x20, 0x13, 0x00, 0x00, 0x00]);
+        umid.extend(1..=16u8);
+        let package_set = |umid: &[u8]| {
+            let mut set = vec![0x3c, 0x0a, 0x00, 0x02, 0xaa, 0xbb];
+            set.extend(PACKAGE_UID_TAG.to_be_bytes());
+            set.extend(32u16.to_be_bytes());
+            set.extend(umid);
+            set
+        };
+        let mut material_umid = umid.clone();
+        material_umid[31] = 0xff;
+
+        let mut file = klv(
+            [
+                0x06, 0x0e, 0x2b, 0x34, 0x02, 0x05, 0x01, 0x01, 0x0d, 0x01, 0x02, 0x01, 0x01, 0x02,
+                0x04, 0x00,
+            ],
+            &[0; 88],
+        );
+        file.extend(klv(material_package, &package_set(&material_umid)));
+        file.extend(klv(source_package, &package_set(&umid)));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("video.mxf");
+        fs::write(&path, file).unwrap();
+        assert_eq!(
+            mxf_file_package_id(&path).unwrap(),
+            "urn:uuid:01020304-0506-0708-090a-0b0c0d0e0f10"
+        );
+
+        fs::write(&path, klv(material_package, &package_set(&umid))).unwrap();
+        assert!(mxf_file_package_id(&path).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use anyhow::Result;
+use std::path::Path;
+
+// Ownership applied to delivered files so the rest of the pipeline can read them
+// without a manual chmod pass. Only meaningful on Unix; a no-op elsewhere.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutputPermissions {
+    // File mode such as 0o664; directories also get execute wherever read is set
+    pub mode: Option<u32>,
+    // Group name or numeric gid
+    pub group: Option<String>,
+}
+
+impl OutputPermissions {
+    pub fn parse_mode(text: &str) -> Option<u32> {
+        u32::from_str_radix(text.trim(), 8)
+            .ok()
+            .filter(|mode| *mode <= 0o7777)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mode.is_none() && self.group.is_none()
+    }
+
+    // Applies to `path` and, for directories, everything below it
+    pub fn apply_tree(&self, path: &Path) -> Result<()> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        self.apply(path)?;
+        if path.is_dir() {
+            for entry in std::fs::read_dir(path)?.flatten() {
+                self.apply_tree(&entry.path())?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    pub fn apply(&self, path: &Path) -> Result<()> {
+        use std::os::unix::fs::{chown, PermissionsExt};
+
+        use crate::errors::{EncoderError, IoSide};
+
+        if let Some(group) = &self.group {
+            let gid =
+                group_id(group).ok_or_else(|| anyhow::anyhow!("Unknown group '{}'", group))?;
+            chown(path, None, Some(gid))
+                .map_err(|e| EncoderError::from_io(&e, path, IoSide::Output))?;
+        }
+        if let Some(mode) = self.mode {
+            let mode = if path.is_dir() {
+                mode | ((mode & 0o444) >> 2)
+            } else {
+                mode
+            };
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+                .map_err(|e| EncoderError::from_io(&e, path, IoSide::Output))?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn apply(&self, _path: &Path) -> Result<()> {
+        Ok(())
+    }
+}
+
+// Looks the group up through NSS, so groups served by LDAP, SSSD or NIS resolve
+// the same as ones in /etc/group
+#[cfg(unix)]
+fn group_id(group: &str) -> Option<u32> {
+    if let Ok(gid) = group.parse() {
+        return Some(gid);
+    }
+    let name = std::ffi::CString::new(group).ok()?;
+    let mut buffer = vec![0 as libc::c_char; 1024];
+    loop {
+        let mut entry: libc::group = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+        let code = unsafe {
+            libc::getgrnam_r(
+                name.as_ptr(),
+                &mut entry,
+                buffer.as_mut_ptr(),
+                buffer.len(),
+                &mut result,
+            )
+        };
+        // Large directory groups list every member in the buffer
+        if code == libc::ERANGE && buffer.len() < 1 << 20 {
+            buffer.resize(buffer.len() * 2, 0);
+            continue;
+        }
+        return (code == 0 && !result.is_null()).then_some(entry.gr_gid);
+    }
+}
@@ -0,0 +1,840 @@
+use eframe::egui;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicU32, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+use crate::{
+    encoding::FrameSeek,
+    models::FitMode,
+    scopes::Scopes,
+    timecode::{Timecode, TimecodeRate},
+    utils::JobEnvironment,
+};
+
+// Frames kept decoded around the playhead
+const WINDOW_AHEAD: usize = 24;
+const WINDOW_BEHIND: usize = 4;
+const PREVIEW_MAX_SIDE: u32 = 1280;
+
+// Texture memory the preview window may occupy; older review machines
+// run out of VRAM long before they run out of RAM
+const TEXTURE_BUDGET_BYTES: usize = 128 * 1024 * 1024;
+
+type DecodedFrame = (usize, Result<egui::ColorImage, String>);
+type DecodedPreview = (usize, Result<PreviewFrame, String>);
+type DecodedFull = (usize, Result<image::DynamicImage, String>);
+
+const MIN_ZOOM: f32 = 0.05;
+const MAX_ZOOM: f32 = 16.0;
+
+// Source media used for the A side of the compare view; the low-res proxy is
+// decoded instead of the master when one is available
+pub struct SourceClip {
+    pub ffmpeg_path: PathBuf,
+    pub input_video: PathBuf,
+    pub proxy: Option<PathBuf>,
+    // The job's fit, so the source lines up with the output it's compared against
+    pub fit: FitMode,
+    // Output frames before the program starts, which have no source frame
+    pub leader_frames: u32,
+    // First timestamp of whichever of the proxy or master is decoded
+    pub stream_start: f64,
+    pub environment: JobEnvironment,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareMode {
+    Off,
+    Source,
+    Output,
+    Split,
+}
+
+struct PreviewFrame {
+    image: egui::ColorImage,
+    full_size: [u32; 2],
+    scopes: Scopes,
+}
+
+// Full-resolution copy of the current frame used for 1:1 inspection
+struct FullFrame {
+    index: usize,
+    // True when the GPU limit forced the display texture below full resolution
+    downscaled: bool,
+    image: image::DynamicImage,
+    texture: egui::TextureHandle,
+}
+
+struct SourceRequest {
+    index: usize,
+    frame_number: u32,
+    size: [usize; 2],
+}
+
+// Derived from the GPU limits reported by eframe on the first repaint
+struct PreviewLimits {
+    max_texture_side: AtomicUsize,
+    preview_side: AtomicU32,
+    window_ahead: AtomicUsize,
+}
+
+impl PreviewLimits {
+    fn update(&self, max_texture_side: usize) {
+        let preview_side = PREVIEW_MAX_SIDE.min(max_texture_side as u32);
+        let frame_bytes = (preview_side as usize).pow(2) * 4;
+        let window_ahead = (TEXTURE_BUDGET_BYTES / frame_bytes.max(1)).clamp(2, WINDOW_AHEAD);
+
+        self.max_texture_side
+            .store(max_texture_side, Ordering::Relaxed);
+        self.preview_side.store(preview_side, Ordering::Relaxed);
+        self.window_ahead.store(window_ahead, Ordering::Relaxed);
+    }
+
+    fn in_window(&self, index: usize, current: usize) -> bool {
+        index + WINDOW_BEHIND >= current
+            && index < current + self.window_ahead.load(Ordering::Relaxed)
+    }
+}
+
+pub struct SequencePlayer {
+    pub open: bool,
+    frames: Vec<PathBuf>,
+    fps: f64,
+    current: usize,
+    playing: bool,
+    last_advance: Instant,
+    textures: HashMap<usize, egui::TextureHandle>,
+    requested: HashSet<usize>,
+    position: Arc<AtomicUsize>,
+    limits: Arc<PreviewLimits>,
+    request_sender: Sender<(usize, PathBuf)>,
+    frame_receiver: Receiver<DecodedPreview>,
+    frame_sizes: HashMap<usize, [u32; 2]>,
+    scopes: HashMap<usize, Scopes>,
+    show_scopes: bool,
+    scope_texture: Option<(usize, egui::TextureHandle)>,
+    error: Option<String>,
+    compare_mode: CompareMode,
+    split: f32,
+    source_sender: Option<Sender<SourceRequest>>,
+    source_receiver: Receiver<DecodedFrame>,
+    source_texture: Option<(usize, egui::TextureHandle)>,
+    source_pending: Option<usize>,
+    zoom: Option<f32>,
+    view_center: egui::Pos2,
+    full_frame: Option<FullFrame>,
+    full_pending: Option<usize>,
+    full_sender: Sender<DecodedFull>,
+    full_receiver: Receiver<DecodedFull>,
+    marks: Vec<u32>,
+    timecode: TimecodeRate,
+    goto_timecode: String,
+}
+
+impl SequencePlayer {
+    pub fn new(frames: Vec<PathBuf>, fps: f64, source: Option<SourceClip>) -> Self {
+        let (request_sender, request_receiver) = mpsc::channel::<(usize, PathBuf)>();
+        let (frame_sender, frame_receiver) = mpsc::channel();
+        let position = Arc::new(AtomicUsize::new(0));
+        let limits = Arc::new(PreviewLimits {
+            max_texture_side: AtomicUsize::new(2048),
+            preview_side: AtomicU32::new(PREVIEW_MAX_SIDE),
+            window_ahead: AtomicUsize::new(WINDOW_AHEAD),
+        });
+        let fps = if fps > 0.0 { fps } else { 24.0 };
+
+        let request_receiver = Arc::new(Mutex::new(request_receiver));
+        let workers = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(2)
+            .min(4);
+
+        for _ in 0..workers {
+            let request_receiver = Arc::clone(&request_receiver);
+            let frame_sender = frame_sender.clone();
+            let position = Arc::clone(&position);
+            let limits = Arc::clone(&limits);
+            thread::spawn(move || loop {
+                let request = request_receiver.lock().unwrap().recv();
+                let Ok((index, path)) = request else {
+                    break;
+                };
+
+                // Skip frames the playhead has already moved away from
+                if !limits.in_window(index, position.load(Ordering::Relaxed)) {
+                    let _ = frame_sender.send((index, Err(String::new())));
+                    continue;
+                }
+
+                let preview_side = limits.preview_side.load(Ordering::Relaxed);
+                if frame_sender
+                    .send((index, decode_frame(&path, preview_side)))
+                    .is_err()
+                {
+                    break;
+                }
+            });
+        }
+
+        let (source_result_sender, source_receiver) = mpsc::channel();
+        let (full_sender, full_receiver) = mpsc::channel();
+        let source_sender = source.map(|clip| {
+            let (sender, receiver) = mpsc::channel::<SourceRequest>();
+            thread::spawn(move || {
+                while let Ok(mut request) = receiver.recv() {
+                    // Only the most recent request matters while scrubbing
+                    while let Ok(newer) = receiver.try_recv() {
+                        request = newer;
+                    }
+
+                    let result =
+                        decode_source_frame(&clip, request.frame_number, fps, request.size);
+                    if source_result_sender.send((request.index, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+            sender
+        });
+
+        Self {
+            open: true,
+            frames,
+            fps,
+            current: 0,
+            playing: false,
+            last_advance: Instant::now(),
+            textures: HashMap::new(),
+            requested: HashSet::new(),
+            position,
+            limits,
+            request_sender,
+            frame_receiver,
+            frame_sizes: HashMap::new(),
+            scopes: HashMap::new(),
+            show_scopes: false,
+            scope_texture: None,
+            error: None,
+            timecode: TimecodeRate::from_fps(fps),
+            goto_timecode: String::new(),
+            compare_mode: CompareMode::Off,
+            split: 0.5,
+            source_sender,
+            source_receiver,
+            source_texture: None,
+            source_pending: None,
+            zoom: None,
+            view_center: egui::pos2(0.5, 0.5),
+            full_frame: None,
+            full_pending: None,
+            full_sender,
+            full_receiver,
+            marks: Vec::new(),
+        }
+    }
+
+    fn seek(&mut self, index: usize) {
+        self.current = index.min(self.frames.len().saturating_sub(1));
+        self.position.store(self.current, Ordering::Relaxed);
+    }
+
+    fn receive_frames(&mut self, ctx: &egui::Context) {
+        while let Ok((index, result)) = self.frame_receiver.try_recv() {
+            self.requested.remove(&index);
+            match result {
+                Ok(frame) => {
+                    let texture = ctx.load_texture(
+                        format!("player_frame_{}", index),
+                        frame.image,
+                        egui::TextureOptions::LINEAR,
+                    );
+                    self.textures.insert(index, texture);
+                    self.frame_sizes.insert(index, frame.full_size);
+                    self.scopes.insert(index, frame.scopes);
+                }
+                Err(e) if !e.is_empty() => self.error = Some(e),
+                Err(_) => {}
+            }
+        }
+
+        while let Ok((index, result)) = self.source_receiver.try_recv() {
+            // A failed decode stays pending so it is not retried every repaint
+            match result {
+                Ok(image) => {
+                    if self.source_pending == Some(index) {
+                        self.source_pending = None;
+                    }
+                    let texture =
+                        ctx.load_texture("player_source", image, egui::TextureOptions::LINEAR);
+                    self.source_texture = Some((index, texture));
+                }
+                Err(e) => self.error = Some(e),
+            }
+        }
+
+        while let Ok((index, result)) = self.full_receiver.try_recv() {
+            match result {
+                Ok(image) => {
+                    if self.full_pending == Some(index) {
+                        self.full_pending = None;
+                    }
+                    // Full frames larger than the GPU allows are shown downscaled;
+                    // the readout still samples the decoded image
+                    let max_side = self.limits.max_texture_side.load(Ordering::Relaxed) as u32;
+                    let downscaled = image.width() > max_side || image.height() > max_side;
+                    let rgba = if downscaled {
+                        image
+                            .resize(max_side, max_side, image::imageops::FilterType::Triangle)
+                            .to_rgba8()
+                    } else {
+                        image.to_rgba8()
+                    };
+                    let size = [rgba.width() as usize, rgba.height() as usize];
+                    let color_image = egui::ColorImage::from_rgba_unmultiplied(
+                        size,
+                        rgba.as_flat_samples().as_slice(),
+                    );
+                    // Nearest filtering keeps individual pixels visible when zoomed in
+                    let texture =
+                        ctx.load_texture("player_full", color_image, egui::TextureOptions::NEAREST);
+                    self.full_frame = Some(FullFrame {
+                        index,
+                        downscaled,
+                        image,
+                        texture,
+                    });
+                }
+                Err(e) => self.error = Some(e),
+            }
+        }
+    }
+
+    fn request_window(&mut self) {
+        let current = self.current;
+        let limits = Arc::clone(&self.limits);
+        self.textures
+            .retain(|index, _| limits.in_window(*index, current));
+        self.frame_sizes
+            .retain(|index, _| limits.in_window(*index, current));
+        self.scopes
+            .retain(|index, _| limits.in_window(*index, current));
+
+        let window_ahead = limits.window_ahead.load(Ordering::Relaxed);
+        let end = (current + window_ahead).min(self.frames.len());
+        for index in current..end {
+            if !self.textures.contains_key(&index) && self.requested.insert(index) {
+                let _ = self
+                    .request_sender
+                    .send((index, self.frames[index].clone()));
+            }
+        }
+    }
+
+    fn request_source(&mut self) {
+        if self.compare_mode == CompareMode::Off || self.playing {
+            return;
+        }
+
+        let Some(sender) = &self.source_sender else {
+            return;
+        };
+
+        let up_to_date = self
+            .source_texture
+            .as_ref()
+            .is_some_and(|(index, _)| *index == self.current);
+        if up_to_date || self.source_pending == Some(self.current) {
+            return;
+        }
+
+        // The source is scaled to match the decoded output frame exactly
+        let Some(output) = self.textures.get(&self.current) else {
+            return;
+        };
+        let Some(frame_number) = frame_number(&self.frames[self.current]) else {
+            return;
+        };
+
+        let _ = sender.send(SourceRequest {
+            index: self.current,
+            frame_number,
+            size: output.size(),
+        });
+        self.source_pending = Some(self.current);
+    }
+
+    fn request_full_frame(&mut self) {
+        if self.zoom.is_none() || self.playing {
+            return;
+        }
+
+        let up_to_date = self
+            .full_frame
+            .as_ref()
+            .is_some_and(|frame| frame.index == self.current);
+        if up_to_date || self.full_pending == Some(self.current) {
+            return;
+        }
+
+        let index = self.current;
+        let path = self.frames[index].clone();
+        let sender = self.full_sender.clone();
+        thread::spawn(move || {
+            let result = image::open(&path)
+                .map_err(|e| format!("Failed to decode {}: {}", path.display(), e));
+            let _ = sender.send((index, result));
+        });
+        self.full_pending = Some(index);
+    }
+
+    fn advance(&mut self) {
+        if !self.playing {
+            self.last_advance = Instant::now();
+            return;
+        }
+
+        let frame_duration = Duration::from_secs_f64(1.0 / self.fps);
+        if self.last_advance.elapsed() < frame_duration {
+            return;
+        }
+
+        // Hold on the current frame until the next one is decoded
+        let next = self.current + 1;
+        if next >= self.frames.len() {
+            self.playing = false;
+        } else if self.textures.contains_key(&next) {
+            self.seek(next);
+            self.last_advance = Instant::now();
+        }
+    }
+
+    fn seek_timecode(&mut self) {
+        let target = self
+            .goto_timecode
+            .parse::<Timecode>()
+            .and_then(|timecode| self.timecode.frame_at(&timecode));
+        match target {
+            Ok(target) => {
+                self.error = None;
+                self.playing = false;
+                // Resumed or partial sequences don't start at frame zero
+                let index = self
+                    .frames
+                    .iter()
+                    .position(|path| frame_number(path) == Some(target))
+                    .unwrap_or(target as usize);
+                self.seek(index.min(self.frames.len() - 1));
+            }
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+
+    // Frames marked since the last call, handed to the still pull list
+    pub fn take_marks(&mut self) -> Vec<u32> {
+        std::mem::take(&mut self.marks)
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        if self.frames.is_empty() {
+            self.open = false;
+            return;
+        }
+
+        self.limits.update(ctx.input(|i| i.max_texture_side));
+        self.receive_frames(ctx);
+        self.advance();
+        self.request_window();
+        self.request_source();
+        self.request_full_frame();
+
+        let mut open = self.open;
+        egui::Window::new("Sequence Player")
+            .open(&mut open)
+            .default_size([720.0, 560.0])
+            .show(ctx, |ui| {
+                let file_name = self.frames[self.current]
+                    .file_name()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let frame = frame_number(&self.frames[self.current]).unwrap_or(self.current as u32);
+                ui.label(format!(
+                    "File: {} | Frame {}/{} | TC {} | {:.3} fps",
+                    file_name,
+                    self.current + 1,
+                    self.frames.len(),
+                    self.timecode.timecode_at(frame),
+                    self.fps
+                ));
+
+                if let Some(err) = &self.error {
+                    ui.colored_label(egui::Color32::LIGHT_RED, err);
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("⏮").clicked() {
+                        self.seek(0);
+                    }
+                    if ui.button("◀").clicked() {
+                        self.seek(self.current.saturating_sub(1));
+                    }
+                    let play_label = if self.playing { "⏸" } else { "▶" };
+                    if ui.button(play_label).clicked() {
+                        if !self.playing && self.current + 1 >= self.frames.len() {
+                            self.seek(0);
+                        }
+                        self.playing = !self.playing;
+                    }
+                    if ui.button("▶|").clicked() {
+                        self.seek(self.current + 1);
+                    }
+                    if ui.button("⏭").clicked() {
+                        self.seek(self.frames.len() - 1);
+                    }
+                    if ui
+                        .button("📌")
+                        .on_hover_text("Mark frame for still pulls")
+                        .clicked()
+                    {
+                        if let Some(frame) = frame_number(&self.frames[self.current]) {
+                            self.marks.push(frame);
+                        }
+                    }
+
+                    let goto = ui.add(
+                        egui::TextEdit::singleline(&mut self.goto_timecode)
+                            .hint_text("Go to TC")
+                            .desired_width(90.0),
+                    );
+                    if goto.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        self.seek_timecode();
+                    }
+
+                    if self.source_sender.is_some() {
+                        ui.separator();
+                        ui.label("Compare:");
+                        ui.selectable_value(&mut self.compare_mode, CompareMode::Off, "Off");
+                        ui.selectable_value(&mut self.compare_mode, CompareMode::Source, "A");
+                        ui.selectable_value(&mut self.compare_mode, CompareMode::Output, "B");
+                        ui.selectable_value(&mut self.compare_mode, CompareMode::Split, "Split");
+                    }
+                });
+
+                let mut position = self.current;
+                let last = self.frames.len() - 1;
+                ui.spacing_mut().slider_width = ui.available_width() - 80.0;
+                if ui
+                    .add(egui::Slider::new(&mut position, 0..=last).show_value(true))
+                    .changed()
+                {
+                    self.playing = false;
+                    self.seek(position);
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label("Zoom:");
+                    if ui.selectable_label(self.zoom.is_none(), "Fit").clicked() {
+                        self.zoom = None;
+                    }
+                    for level in [1.0, 2.0, 4.0] {
+                        let label = format!("{}:1", level);
+                        if ui
+                            .selectable_label(self.zoom == Some(level), label)
+                            .clicked()
+                        {
+                            self.zoom = Some(level);
+                        }
+                    }
+                    if let Some(zoom) = self.zoom {
+                        ui.label(format!("{:.0}%", zoom * 100.0));
+                    }
+                    let limited = self
+                        .full_frame
+                        .as_ref()
+                        .is_some_and(|frame| frame.index == self.current && frame.downscaled);
+                    if limited && self.zoom.is_some() {
+                        ui.colored_label(
+                            egui::Color32::LIGHT_YELLOW,
+                            format!(
+                                "Display limited to {}px by the GPU",
+                                self.limits.max_texture_side.load(Ordering::Relaxed)
+                            ),
+                        );
+                    }
+                });
+
+                ui.checkbox(
+                    &mut self.show_scopes,
+                    "Scopes (RGB histogram / luma waveform)",
+                );
+                if self.show_scopes {
+                    self.show_scopes_panel(ui);
+                }
+
+                if self.compare_mode == CompareMode::Split {
+                    ui.add(
+                        egui::Slider::new(&mut self.split, 0.0..=1.0)
+                            .show_value(false)
+                            .text("A | B"),
+                    );
+                }
+
+                ui.add_space(5.0);
+                self.show_frame(ui);
+            });
+        self.open = open;
+
+        if self.open {
+            ctx.request_repaint();
+        }
+    }
+
+    fn show_scopes_panel(&mut self, ui: &mut egui::Ui) {
+        let Some(scopes) = self.scopes.get(&self.current) else {
+            ui.label("Decoding...");
+            return;
+        };
+
+        let up_to_date = self
+            .scope_texture
+            .as_ref()
+            .is_some_and(|(index, _)| *index == self.current);
+        if !up_to_date {
+            let texture = ui.ctx().load_texture(
+                "player_waveform",
+                scopes.waveform_image(),
+                egui::TextureOptions::LINEAR,
+            );
+            self.scope_texture = Some((self.current, texture));
+        }
+
+        if let Some((_, texture)) = &self.scope_texture {
+            scopes.show(ui, texture);
+        }
+    }
+
+    fn show_frame(&mut self, ui: &mut egui::Ui) {
+        let Some(preview) = self.textures.get(&self.current) else {
+            ui.label("Decoding...");
+            return;
+        };
+
+        let source = self
+            .source_texture
+            .as_ref()
+            .filter(|(index, _)| *index == self.current)
+            .map(|(_, texture)| texture);
+
+        let wants_source = matches!(self.compare_mode, CompareMode::Source | CompareMode::Split);
+        if wants_source && source.is_none() {
+            let message = if self.playing {
+                "Pause playback to decode the source frame"
+            } else {
+                "Decoding source..."
+            };
+            ui.label(message);
+        }
+
+        let full_frame = self
+            .full_frame
+            .as_ref()
+            .filter(|frame| frame.index == self.current && self.zoom.is_some());
+        let output = full_frame.map_or(preview, |frame| &frame.texture);
+
+        let full_size = self
+            .frame_sizes
+            .get(&self.current)
+            .map_or(preview.size_vec2(), |[w, h]| {
+                egui::vec2(*w as f32, *h as f32)
+            });
+
+        // Leave room for the pixel readout underneath
+        let available = ui.available_size() - egui::vec2(0.0, 24.0);
+        let fit_scale = (available.x / full_size.x)
+            .min(available.y / full_size.y)
+            .min(1.0);
+        let scale = self.zoom.unwrap_or(fit_scale);
+
+        let view_size = (full_size * scale).min(available.max(egui::Vec2::ZERO));
+        let extent = view_size / (full_size * scale);
+        let half = extent / 2.0;
+        self.view_center.x = self.view_center.x.clamp(half.x, 1.0 - half.x);
+        self.view_center.y = self.view_center.y.clamp(half.y, 1.0 - half.y);
+        let uv = egui::Rect::from_center_size(self.view_center, extent);
+
+        let (rect, response) = ui.allocate_exact_size(view_size, egui::Sense::drag());
+
+        if response.dragged() {
+            self.view_center -= response.drag_delta() / (full_size * scale);
+        }
+
+        if response.hovered() {
+            let scroll = ui.input(|i| i.raw_scroll_delta.y);
+            if scroll != 0.0 {
+                let zoomed = scale * (1.0 + scroll * 0.002);
+                self.zoom = Some(zoomed.clamp(MIN_ZOOM, MAX_ZOOM));
+            }
+        }
+
+        let painter = ui.painter_at(rect);
+        match (self.compare_mode, source) {
+            (CompareMode::Source, Some(source)) => {
+                painter.image(source.id(), rect, uv, egui::Color32::WHITE);
+            }
+            (CompareMode::Split, Some(source)) => {
+                painter.image(output.id(), rect, uv, egui::Color32::WHITE);
+
+                let split_x = rect.left() + rect.width() * self.split;
+                let source_rect =
+                    egui::Rect::from_min_max(rect.min, egui::pos2(split_x, rect.bottom()));
+                let source_uv = egui::Rect::from_min_max(
+                    uv.min,
+                    egui::pos2(uv.min.x + uv.width() * self.split, uv.max.y),
+                );
+                painter.image(source.id(), source_rect, source_uv, egui::Color32::WHITE);
+                painter.vline(
+                    split_x,
+                    rect.y_range(),
+                    egui::Stroke::new(1.0, egui::Color32::YELLOW),
+                );
+            }
+            _ => {
+                painter.image(output.id(), rect, uv, egui::Color32::WHITE);
+            }
+        }
+
+        let readout = response.hover_pos().map(|pos| {
+            let relative = (pos - rect.min) / rect.size();
+            let x = ((uv.min.x + relative.x * uv.width()) * full_size.x) as u32;
+            let y = ((uv.min.y + relative.y * uv.height()) * full_size.y) as u32;
+            let x = x.min(full_size.x as u32 - 1);
+            let y = y.min(full_size.y as u32 - 1);
+
+            match full_frame {
+                Some(frame) => format!("X: {} Y: {} | {}", x, y, pixel_readout(&frame.image, x, y)),
+                None => format!("X: {} Y: {} | Zoom in to read pixel values", x, y),
+            }
+        });
+        ui.label(readout.unwrap_or_else(|| "Hover the frame to inspect pixels".to_string()));
+    }
+}
+
+// Output frames are named `<base>-<number>.<ext>`
+fn frame_number(path: &Path) -> Option<u32> {
+    let stem = path.file_stem()?.to_str()?;
+    stem.rsplit('-').next()?.parse().ok()
+}
+
+fn decode_frame(path: &Path, preview_side: u32) -> Result<PreviewFrame, String> {
+    let image =
+        image::open(path).map_err(|e| format!("Failed to decode {}: {}", path.display(), e))?;
+    let full_size = [image.width(), image.height()];
+    let preview = image.thumbnail(preview_side, preview_side).to_rgba8();
+    let size = [preview.width() as usize, preview.height() as usize];
+
+    Ok(PreviewFrame {
+        image: egui::ColorImage::from_rgba_unmultiplied(size, preview.as_flat_samples().as_slice()),
+        full_size,
+        scopes: Scopes::compute(&preview),
+    })
+}
+
+fn pixel_readout(image: &image::DynamicImage, x: u32, y: u32) -> String {
+    use image::GenericImageView;
+
+    match image {
+        image::DynamicImage::ImageRgb16(buffer) => {
+            let pixel = buffer.get_pixel(x, y);
+            format!("R {} G {} B {} (16-bit)", pixel[0], pixel[1], pixel[2])
+        }
+        image::DynamicImage::ImageRgba16(buffer) => {
+            let pixel = buffer.get_pixel(x, y);
+            format!(
+                "R {} G {} B {} A {} (16-bit)",
+                pixel[0], pixel[1], pixel[2], pixel[3]
+            )
+        }
+        other => {
+            let pixel = other.get_pixel(x, y);
+            format!(
+                "R {} G {} B {} A {}",
+                pixel[0], pixel[1], pixel[2], pixel[3]
+            )
+        }
+    }
+}
+
+fn decode_source_frame(
+    clip: &SourceClip,
+    frame_number: u32,
+    fps: f64,
+    size: [usize; 2],
+) -> Result<egui::ColorImage, String> {
+    let [width, height] = size;
+    let Some(source_frame) = frame_number.checked_sub(clip.leader_frames) else {
+        return Err(format!("Frame {} is part of the leader", frame_number));
+    };
+    // Same seek as the encoder, so the compare shows exactly the frame it encoded
+    let seek = FrameSeek {
+        frame: source_frame,
+        frame_rate: fps,
+        stream_start: clip.stream_start,
+    };
+    let filter = format!(
+        "{},{},format=rgba",
+        seek.trim_filter(),
+        clip.fit
+            .filter((width as u32, height as u32), "bicubic", "black")
+    );
+
+    let mut command = Command::new(&clip.ffmpeg_path);
+    clip.environment.apply(&mut command);
+    command
+        .args(["-v", "error"])
+        .args(seek.input_args())
+        .arg("-i")
+        .arg(clip.proxy.as_ref().unwrap_or(&clip.input_video))
+        .args(["-frames:v", "1", "-vf", &filter])
+        .args(["-f", "rawvideo", "pipe:1"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let output = {
+        #[cfg(windows)]
+        {
+            command.creation_flags(0x08000000).output()
+        }
+        #[cfg(not(windows))]
+        {
+            command.output()
+        }
+    }
+    .map_err(|e| format!("Failed to run FFmpeg: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "Source decode failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    if output.stdout.len() != width * height * 4 {
+        return Err(format!("No source frame at {}", frame_number));
+    }
+
+    Ok(egui::ColorImage::from_rgba_unmultiplied(
+        size,
+        &output.stdout,
+    ))
+}
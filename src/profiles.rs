@@ -0,0 +1,80 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf};
+
+use crate::{
+    models::{LeaderSpec, MatteSpec, NamingRules, OverlaySpec, OverlayStyle, Resolution},
+    utils::{app_data_dir, sanitize_base_name},
+};
+
+// Everything scoped to one production; each show is stored in its own file
+// so stations serving several shows never mix their settings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShowProfile {
+    pub name: String,
+    pub resolution: Resolution,
+    pub output_dir: Option<PathBuf>,
+    // Folder holding an overlay_<resolution>.png per preset, e.g. overlay_2k.png
+    pub overlay_dir: Option<PathBuf>,
+    pub destinations: Vec<PathBuf>,
+    pub naming: NamingRules,
+    pub overlay_style: OverlayStyle,
+    // Layers over the show overlay, such as a client logo or legal text
+    pub extra_overlays: Vec<OverlaySpec>,
+    pub matte: Option<MatteSpec>,
+    // Frame rate the show's delivery spec requires
+    pub delivery_fps: Option<f64>,
+    // Countdown the spec wants in front of every delivery
+    pub leader: Option<LeaderSpec>,
+}
+
+impl Default for ShowProfile {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            resolution: Resolution::K6,
+            output_dir: None,
+            overlay_dir: None,
+            destinations: Vec::new(),
+            naming: NamingRules::default(),
+            overlay_style: OverlayStyle::default(),
+            extra_overlays: Vec::new(),
+            matte: None,
+            delivery_fps: None,
+            leader: None,
+        }
+    }
+}
+
+impl ShowProfile {
+    pub fn profiles_dir() -> PathBuf {
+        app_data_dir().join("shows")
+    }
+
+    fn path(&self) -> PathBuf {
+        Self::profiles_dir().join(format!("{}.json", sanitize_base_name(&self.name)))
+    }
+
+    pub fn load_all() -> Vec<ShowProfile> {
+        let mut profiles: Vec<ShowProfile> = fs::read_dir(Self::profiles_dir())
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                    .filter_map(|path| fs::read_to_string(path).ok())
+                    .filter_map(|content| serde_json::from_str(&content).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        profiles.sort_by_key(|show| show.name.to_lowercase());
+        profiles
+    }
+
+    pub fn save(&self) -> Result<()> {
+        fs::create_dir_all(Self::profiles_dir())?;
+        fs::write(self.path(), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
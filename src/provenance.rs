@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::crash;
+
+// Hidden so replication and players, which only pick up the base name's frames, skip it
+const SOURCES_FILE: &str = ".delivery_encoder_sources.json";
+// Hashing whole masters would take minutes; the head and the size tell shots apart
+const SAMPLE_BYTES: u64 = 1024 * 1024;
+
+// Which source a base name's frames in an output folder came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceFingerprint {
+    // For the operator; masters get moved to the archive, so it isn't compared
+    pub path: PathBuf,
+    pub size: u64,
+    pub sample_sha1: String,
+}
+
+impl SourceFingerprint {
+    pub fn of(source: &Path) -> io::Result<Self> {
+        let size = fs::metadata(source)?.len();
+        let mut hasher = Sha1::new();
+        io::copy(&mut fs::File::open(source)?.take(SAMPLE_BYTES), &mut hasher)?;
+        Ok(Self {
+            path: source.to_path_buf(),
+            size,
+            sample_sha1: hasher
+                .finalize()
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect(),
+        })
+    }
+
+    pub fn same_source(&self, other: &SourceFingerprint) -> bool {
+        self.size == other.size && self.sample_sha1 == other.sample_sha1
+    }
+}
+
+fn read_sources(output_dir: &Path) -> BTreeMap<String, SourceFingerprint> {
+    fs::read_to_string(output_dir.join(SOURCES_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn recorded_source(output_dir: &Path, base_name: &str) -> Option<SourceFingerprint> {
+    read_sources(output_dir).remove(base_name)
+}
+
+// Failing to record only loses the check for the next run, so it is logged, not raised
+pub fn record_source(output_dir: &Path, base_name: &str, source: &Path) {
+    let recorded = SourceFingerprint::of(source).and_then(|fingerprint| {
+        let mut sources = read_sources(output_dir);
+        sources.insert(base_name.to_string(), fingerprint);
+        let json = serde_json::to_string_pretty(&sources).map_err(io::Error::other)?;
+        fs::write(output_dir.join(SOURCES_FILE), json)
+    });
+    if let Err(e) = recorded {
+        crash::log(format!(
+            "Could not record source of {} in {}: {}",
+            base_name,
+            output_dir.display(),
+            e
+        ));
+    }
+}
+
+// The recorded source when the folder's frames for `base_name` came from a
+// different file than `source`; None when they match or nothing was recorded
+pub fn conflicting_source(
+    output_dir: &Path,
+    base_name: &str,
+    source: &Path,
+) -> Option<SourceFingerprint> {
+    let recorded = recorded_source(output_dir, base_name)?;
+    let current = SourceFingerprint::of(source).ok()?;
+    (!recorded.same_source(&current)).then_some(recorded)
+}
+
+// First sibling folder, `<dir>_2` and up, that is new or already holds this
+// source's frames for `base_name`
+pub fn disambiguated_dir(output_dir: &Path, base_name: &str, source: &Path) -> PathBuf {
+    let name = output_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "output".to_string());
+    let current = SourceFingerprint::of(source).ok();
+    let mut n = 2;
+    loop {
+        let dir = output_dir.with_file_name(format!("{}_{}", name, n));
+        let same_source = current.as_ref().is_some_and(|current| {
+            recorded_source(&dir, base_name).is_some_and(|recorded| recorded.same_source(current))
+        });
+        if !dir.exists() || same_source {
+            return dir;
+        }
+        n += 1;
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::{anyhow, Result};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::UNIX_EPOCH,
+};
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+use crate::utils::JobEnvironment;
+
+const PROXY_MAX_SIDE: u32 = 1280;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProxyStatus {
+    Generating,
+    Ready(PathBuf),
+    Failed(String),
+}
+
+impl ProxyStatus {
+    pub fn describe(&self) -> String {
+        match self {
+            ProxyStatus::Generating => "Generating...".to_string(),
+            ProxyStatus::Ready(_) => "Ready".to_string(),
+            ProxyStatus::Failed(e) => format!("Unavailable ({})", e),
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            ProxyStatus::Ready(path) => Some(path),
+            _ => None,
+        }
+    }
+}
+
+// Proxies are keyed by path, size and modification time so a replaced master
+// gets a fresh proxy without hashing the whole file.
+fn proxy_path(input: &Path, environment: &JobEnvironment) -> Result<PathBuf> {
+    let metadata = fs::metadata(input)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let mut hasher = DefaultHasher::new();
+    fs::canonicalize(input)?.hash(&mut hasher);
+    metadata.len().hash(&mut hasher);
+    modified.hash(&mut hasher);
+
+    let cache_dir = environment.temp_dir().join("delivery_encoder_proxies");
+    fs::create_dir_all(&cache_dir)?;
+    Ok(cache_dir.join(format!("{:016x}.mp4", hasher.finish())))
+}
+
+pub fn spawn_proxy(
+    ffmpeg_path: PathBuf,
+    input: PathBuf,
+    environment: JobEnvironment,
+) -> Receiver<ProxyStatus> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let status = match generate_proxy(&ffmpeg_path, &input, &environment, || {
+            let _ = sender.send(ProxyStatus::Generating);
+        }) {
+            Ok(path) => ProxyStatus::Ready(path),
+            Err(e) => ProxyStatus::Failed(e.to_string()),
+        };
+        let _ = sender.send(status);
+    });
+
+    receiver
+}
+
+fn generate_proxy(
+    ffmpeg_path: &Path,
+    input: &Path,
+    environment: &JobEnvironment,
+    on_start: impl FnOnce(),
+) -> Result<PathBuf> {
+    let proxy = proxy_path(input, environment)?;
+    if proxy.exists() {
+        return Ok(proxy);
+    }
+    on_start();
+
+    // Written under a temporary name so an interrupted run is never picked up as a cache hit
+    let partial = proxy.with_extension("partial.mp4");
+    let filter = format!(
+        "scale={size}:{size}:force_original_aspect_ratio=decrease:force_divisible_by=2",
+        size = PROXY_MAX_SIDE
+    );
+
+    let mut command = Command::new(ffmpeg_path);
+    environment.apply(&mut command);
+    command
+        .args(["-v", "error", "-i"])
+        .arg(input)
+        .args(["-an", "-vf", &filter])
+        // Intra-only so the player can seek to any frame quickly
+        .args([
+            "-c:v", "libx264", "-preset", "veryfast", "-crf", "18", "-g", "1",
+        ])
+        .args(["-pix_fmt", "yuv420p", "-y"])
+        .arg(&partial)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    let output = {
+        #[cfg(windows)]
+        {
+            command.creation_flags(0x08000000).output()?
+        }
+        #[cfg(not(windows))]
+        {
+            command.output()?
+        }
+    };
+
+    if !output.status.success() {
+        let _ = fs::remove_file(&partial);
+        return Err(anyhow!(
+            "FFmpeg failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    fs::rename(&partial, &proxy)?;
+    Ok(proxy)
+}
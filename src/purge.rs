@@ -0,0 +1,180 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use crate::trash::{move_to_trash, TrashSummary};
+
+// How many versions of each delivery to keep around, newest first
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetentionPolicy {
+    pub keep_versions: usize,
+    // Older versions past this age go even inside the keep count; the newest never does
+    pub max_age_days: Option<u32>,
+}
+
+// Every output of one version of a delivery, e.g. all files named `Show_ep01_v003*`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveryVersion {
+    pub delivery: String,
+    pub version: u32,
+    pub files: Vec<PathBuf>,
+    pub bytes: u64,
+    pub modified: SystemTime,
+    pub purge: bool,
+}
+
+impl DeliveryVersion {
+    pub fn age_days(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(self.modified)
+            .unwrap_or_default()
+            .as_secs()
+            / 86_400
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PurgeStatus {
+    Scanning,
+    Listed(Vec<DeliveryVersion>),
+    Purging,
+    Done(TrashSummary),
+    Failed(String),
+}
+
+impl PurgeStatus {
+    pub fn describe(&self) -> String {
+        match self {
+            PurgeStatus::Scanning => "Scanning...".to_string(),
+            PurgeStatus::Listed(versions) if versions.is_empty() => {
+                "No versioned deliveries found".to_string()
+            }
+            PurgeStatus::Listed(versions) => format!("{} versions found", versions.len()),
+            PurgeStatus::Purging => "Moving to trash...".to_string(),
+            PurgeStatus::Done(summary) => format!("Moved {} to the trash", summary.describe()),
+            PurgeStatus::Failed(e) => format!("Failed ({})", e),
+        }
+    }
+}
+
+// Splits `Show_ep01_v003_4k-000001.png` into ("Show_ep01", 3)
+fn parse_version(name: &str) -> Option<(String, u32)> {
+    let mut offset = 0;
+    for token in name.split(['_', '-', '.']) {
+        let digits = token.strip_prefix(['v', 'V']).unwrap_or("");
+        if offset > 0 && !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+            return Some((name[..offset - 1].to_string(), digits.parse().ok()?));
+        }
+        offset += token.len() + 1;
+    }
+    None
+}
+
+fn size_and_modified(path: &Path) -> (u64, SystemTime) {
+    let Ok(metadata) = fs::metadata(path) else {
+        return (0, SystemTime::UNIX_EPOCH);
+    };
+    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    if !metadata.is_dir() {
+        return (metadata.len(), modified);
+    }
+
+    fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| size_and_modified(&entry.path()))
+                .fold((0, modified), |(bytes, newest), (size, time)| {
+                    (bytes + size, newest.max(time))
+                })
+        })
+        .unwrap_or((0, modified))
+}
+
+pub fn scan_versions(
+    output_dir: &Path,
+    policy: RetentionPolicy,
+) -> std::io::Result<Vec<DeliveryVersion>> {
+    let mut grouped: BTreeMap<(String, u32), DeliveryVersion> = BTreeMap::new();
+    for entry in fs::read_dir(output_dir)?.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some((delivery, version)) = parse_version(&name) else {
+            continue;
+        };
+
+        let path = entry.path();
+        let (bytes, modified) = size_and_modified(&path);
+        let group = grouped
+            .entry((delivery.clone(), version))
+            .or_insert_with(|| DeliveryVersion {
+                delivery,
+                version,
+                files: Vec::new(),
+                bytes: 0,
+                modified: SystemTime::UNIX_EPOCH,
+                purge: false,
+            });
+        group.files.push(path);
+        group.bytes += bytes;
+        group.modified = group.modified.max(modified);
+    }
+
+    // Newest version first within each delivery
+    let mut versions: Vec<DeliveryVersion> = grouped.into_values().collect();
+    versions.sort_by(|a, b| a.delivery.cmp(&b.delivery).then(b.version.cmp(&a.version)));
+
+    let max_age = policy
+        .max_age_days
+        .map(|days| Duration::from_secs(days as u64 * 86_400));
+    let mut rank = 0;
+    for index in 0..versions.len() {
+        if index > 0 && versions[index].delivery != versions[index - 1].delivery {
+            rank = 0;
+        }
+        let too_old = max_age.is_some_and(|max_age| {
+            SystemTime::now()
+                .duration_since(versions[index].modified)
+                .is_ok_and(|age| age > max_age)
+        });
+        versions[index].purge = rank > 0 && (rank >= policy.keep_versions.max(1) || too_old);
+        rank += 1;
+    }
+
+    Ok(versions)
+}
+
+pub fn spawn_scan(output_dir: PathBuf, policy: RetentionPolicy) -> Receiver<PurgeStatus> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let _ = sender.send(PurgeStatus::Scanning);
+        let status = match scan_versions(&output_dir, policy) {
+            Ok(versions) => PurgeStatus::Listed(versions),
+            Err(e) => PurgeStatus::Failed(e.to_string()),
+        };
+        let _ = sender.send(status);
+    });
+
+    receiver
+}
+
+pub fn spawn_purge(files: Vec<PathBuf>) -> Receiver<PurgeStatus> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let _ = sender.send(PurgeStatus::Purging);
+        let summary = TrashSummary::of(&files);
+        let status = match move_to_trash(&files) {
+            Ok(()) => PurgeStatus::Done(summary),
+            Err(e) => PurgeStatus::Failed(e.to_string()),
+        };
+        let _ = sender.send(status);
+    });
+
+    receiver
+}
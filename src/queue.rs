@@ -0,0 +1,364 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    models::{OutputFormat, Resolution},
+    utils::civil_date,
+};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JobState {
+    Pending,
+    Running,
+    Paused,
+    // Running, but ffmpeg stopped writing frames
+    Stalled,
+    // Encoded; the source master is being archived and checked
+    Verifying,
+    // Encoded; frames are being copied to the replication destinations
+    Uploading,
+    Done,
+    Failed(String),
+}
+
+impl JobState {
+    pub fn describe(&self) -> String {
+        match self {
+            JobState::Pending => "Pending".to_string(),
+            JobState::Running => "Running".to_string(),
+            JobState::Paused => "Paused".to_string(),
+            JobState::Stalled => "Stalled".to_string(),
+            JobState::Verifying => "Verifying".to_string(),
+            JobState::Uploading => "Uploading".to_string(),
+            JobState::Done => "Done".to_string(),
+            JobState::Failed(e) => format!("Failed: {}", e),
+        }
+    }
+
+    pub const LABELS: [&'static str; 8] = [
+        "Pending",
+        "Running",
+        "Paused",
+        "Stalled",
+        "Verifying",
+        "Uploading",
+        "Done",
+        "Failed",
+    ];
+
+    // The state without its details, for filtering
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobState::Pending => "Pending",
+            JobState::Running => "Running",
+            JobState::Paused => "Paused",
+            JobState::Stalled => "Stalled",
+            JobState::Verifying => "Verifying",
+            JobState::Uploading => "Uploading",
+            JobState::Done => "Done",
+            JobState::Failed(_) => "Failed",
+        }
+    }
+
+    // Still tied to the running encode or its copies, so not to be moved or removed
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            JobState::Running | JobState::Stalled | JobState::Verifying | JobState::Uploading
+        )
+    }
+}
+
+// What differs between queued jobs; everything else comes from the show and
+// station settings at the time the job starts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Job {
+    pub id: u64,
+    pub input_video: PathBuf,
+    pub resolution: Resolution,
+    pub output_dir: PathBuf,
+    pub output_format: OutputFormat,
+    // Free text for whoever picks the job up, e.g. "client requested darker slate"
+    #[serde(default)]
+    pub notes: String,
+    // Unix seconds when the job was queued
+    #[serde(default)]
+    pub added_at: u64,
+    pub state: JobState,
+    // Jobs that have to be Done first, e.g. a 2K down-res after the 6K master
+    #[serde(default)]
+    pub depends_on: Vec<u64>,
+}
+
+impl Job {
+    pub fn name(&self) -> String {
+        self.input_video
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    pub fn added_date(&self) -> String {
+        let (year, month, day) = civil_date(self.added_at as i64);
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+
+    // Case-insensitive match on the shot name or the YYYY-MM-DD it was queued
+    pub fn matches(&self, search: &str) -> bool {
+        let search = search.trim().to_lowercase();
+        search.is_empty()
+            || self.name().to_lowercase().contains(&search)
+            || self.added_date().contains(&search)
+    }
+}
+
+// Jobs run top to bottom, one at a time
+#[derive(Debug, Default)]
+pub struct JobQueue {
+    pub jobs: Vec<Job>,
+    pub running: bool,
+    next_id: u64,
+}
+
+impl JobQueue {
+    pub fn add(
+        &mut self,
+        input_video: PathBuf,
+        resolution: Resolution,
+        output_dir: PathBuf,
+        output_format: OutputFormat,
+        notes: String,
+    ) -> u64 {
+        self.next_id += 1;
+        self.jobs.push(Job {
+            id: self.next_id,
+            input_video,
+            resolution,
+            output_dir,
+            output_format,
+            notes,
+            added_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            state: JobState::Pending,
+            depends_on: Vec::new(),
+        });
+        self.next_id
+    }
+
+    pub fn remove(&mut self, id: u64) {
+        self.jobs.retain(|job| job.id != id);
+    }
+
+    // Swaps a job with its neighbour; `up` moves it towards the front
+    pub fn shift(&mut self, id: u64, up: bool) {
+        let Some(index) = self.jobs.iter().position(|job| job.id == id) else {
+            return;
+        };
+        let other = if up {
+            index.checked_sub(1)
+        } else {
+            Some(index + 1).filter(|&other| other < self.jobs.len())
+        };
+        if let Some(other) = other {
+            self.jobs.swap(index, other);
+        }
+    }
+
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut Job> {
+        self.jobs.iter_mut().find(|job| job.id == id)
+    }
+
+    fn get(&self, id: u64) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+
+    // Dependencies not yet Done. One that was removed or cleared counts as done.
+    pub fn waiting_on(&self, job: &Job) -> Vec<u64> {
+        job.depends_on
+            .iter()
+            .copied()
+            .filter(|&id| self.get(id).is_some_and(|dep| dep.state != JobState::Done))
+            .collect()
+    }
+
+    // Whether `id` waits on `on`, directly or through other jobs
+    fn waits_on(&self, id: u64, on: u64) -> bool {
+        let mut stack = vec![id];
+        let mut seen = Vec::new();
+        while let Some(current) = stack.pop() {
+            if current == on {
+                return true;
+            }
+            if seen.contains(&current) {
+                continue;
+            }
+            seen.push(current);
+            if let Some(job) = self.get(current) {
+                stack.extend(&job.depends_on);
+            }
+        }
+        false
+    }
+
+    // False when the dependency would make two jobs wait on each other
+    pub fn set_dependency(&mut self, id: u64, on: u64, wanted: bool) -> bool {
+        if wanted && (id == on || self.waits_on(on, id)) {
+            return false;
+        }
+        if let Some(job) = self.get_mut(id) {
+            job.depends_on.retain(|&dep| dep != on);
+            if wanted {
+                job.depends_on.push(on);
+            }
+        }
+        true
+    }
+
+    // Paused jobs go first so resuming the queue carries on where it stopped;
+    // a pending one waits until everything it depends on is Done
+    pub fn next_runnable(&self) -> Option<&Job> {
+        self.jobs
+            .iter()
+            .find(|job| job.state == JobState::Paused)
+            .or_else(|| {
+                self.jobs
+                    .iter()
+                    .find(|job| job.state == JobState::Pending && self.waiting_on(job).is_empty())
+            })
+    }
+
+    // Pending jobs that can never run because something they wait on failed, directly
+    // or through another job, are failed in turn. Returns the ones failed this call.
+    pub fn fail_blocked(&mut self) -> Vec<u64> {
+        let mut failed = Vec::new();
+        loop {
+            let blocked = self.jobs.iter().find_map(|job| {
+                let dep = self.waiting_on(job).into_iter().find(|&id| {
+                    self.get(id)
+                        .is_some_and(|dep| matches!(dep.state, JobState::Failed(_)))
+                })?;
+                (job.state == JobState::Pending).then_some((job.id, dep))
+            });
+            let Some((id, dep)) = blocked else {
+                return failed;
+            };
+            if let Some(job) = self.get_mut(id) {
+                job.state = JobState::Failed(format!("dependency #{} failed", dep));
+            }
+            failed.push(id);
+        }
+    }
+
+    // Failed jobs that never ran because a dependency failed first
+    pub fn blocked_count(&self) -> usize {
+        self.jobs
+            .iter()
+            .filter(|job| {
+                matches!(job.state, JobState::Failed(_))
+                    && job.depends_on.iter().any(|&id| {
+                        self.get(id)
+                            .is_some_and(|dep| matches!(dep.state, JobState::Failed(_)))
+                    })
+            })
+            .count()
+    }
+
+    // Nothing can start yet, but a pending job will once a running or
+    // delivering one finishes
+    pub fn is_held(&self) -> bool {
+        self.jobs.iter().any(|job| {
+            job.state == JobState::Pending
+                && self
+                    .waiting_on(job)
+                    .into_iter()
+                    .any(|id| self.get(id).is_some_and(|dep| dep.state.is_active()))
+        })
+    }
+
+    pub fn clear_finished(&mut self) {
+        self.jobs.retain(|job| job.state != JobState::Done);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue_of(count: usize) -> (JobQueue, Vec<u64>) {
+        let mut queue = JobQueue::default();
+        let ids = (0..count)
+            .map(|index| {
+                queue.add(
+                    PathBuf::from(format!("shot_{}.mov", index)),
+                    Resolution::K2,
+                    PathBuf::from("out"),
+                    OutputFormat::PngSequence,
+                    String::new(),
+                )
+            })
+            .collect();
+        (queue, ids)
+    }
+
+    fn set_state(queue: &mut JobQueue, id: u64, state: JobState) {
+        queue.get_mut(id).unwrap().state = state;
+    }
+
+    #[test]
+    fn dependency_cycles_are_rejected() {
+        let (mut queue, ids) = queue_of(3);
+        assert!(!queue.set_dependency(ids[0], ids[0], true));
+        assert!(queue.set_dependency(ids[1], ids[0], true));
+        assert!(queue.set_dependency(ids[2], ids[1], true));
+        assert!(!queue.set_dependency(ids[0], ids[1], true));
+        assert!(!queue.set_dependency(ids[0], ids[2], true));
+        assert!(queue.jobs[0].depends_on.is_empty());
+
+        // Dropping a link lets the reverse one in
+        assert!(queue.set_dependency(ids[2], ids[1], false));
+        assert!(queue.set_dependency(ids[1], ids[0], false));
+        assert!(queue.set_dependency(ids[0], ids[2], true));
+    }
+
+    #[test]
+    fn dependent_waits_until_its_parent_is_done() {
+        let (mut queue, ids) = queue_of(2);
+        // The dependent is first in line but has to wait for the job below it
+        queue.shift(ids[1], true);
+        assert!(queue.set_dependency(ids[0], ids[1], true));
+        assert_eq!(queue.next_runnable().map(|job| job.id), Some(ids[1]));
+
+        for state in [JobState::Running, JobState::Uploading, JobState::Verifying] {
+            set_state(&mut queue, ids[1], state);
+            assert_eq!(queue.next_runnable().map(|job| job.id), None);
+            assert!(queue.is_held());
+        }
+
+        set_state(&mut queue, ids[1], JobState::Done);
+        assert_eq!(queue.next_runnable().map(|job| job.id), Some(ids[0]));
+        assert!(!queue.is_held());
+    }
+
+    #[test]
+    fn failed_parent_blocks_its_dependents() {
+        let (mut queue, ids) = queue_of(4);
+        assert!(queue.set_dependency(ids[1], ids[0], true));
+        assert!(queue.set_dependency(ids[2], ids[1], true));
+        set_state(&mut queue, ids[0], JobState::Failed("ffmpeg".to_string()));
+
+        assert_eq!(queue.next_runnable().map(|job| job.id), Some(ids[3]));
+        assert_eq!(queue.fail_blocked(), vec![ids[1], ids[2]]);
+        assert_eq!(
+            queue.jobs[2].state,
+            JobState::Failed(format!("dependency #{} failed", ids[1]))
+        );
+        assert_eq!(queue.jobs[3].state, JobState::Pending);
+        assert_eq!(queue.blocked_count(), 2);
+        assert!(queue.fail_blocked().is_empty());
+    }
+}
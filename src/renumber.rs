@@ -0,0 +1,283 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use crate::utils::sanitize_base_name;
+
+const DELIMITERS: [char; 3] = ['-', '_', '.'];
+
+// An existing sequence, recognised from any one of its frames
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequenceScan {
+    pub dir: PathBuf,
+    pub base: String,
+    pub delimiter: String,
+    pub padding: usize,
+    pub extension: String,
+    // (frame number, path), lowest first
+    pub frames: Vec<(u32, PathBuf)>,
+}
+
+// Splits `Show_ep01-000123.png` into ("Show_ep01", "-", "000123", "png")
+fn split_frame_name(name: &str) -> Option<(&str, &str, &str, &str)> {
+    let (stem, extension) = name.rsplit_once('.')?;
+    let digits_start = stem
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_ascii_digit())
+        .last()?
+        .0;
+    let (head, digits) = stem.split_at(digits_start);
+    let delimiter_len = if head.ends_with(DELIMITERS) { 1 } else { 0 };
+    let (base, delimiter) = head.split_at(head.len() - delimiter_len);
+    Some((base, delimiter, digits, extension))
+}
+
+pub fn scan_sequence(frame: &Path) -> std::io::Result<SequenceScan> {
+    let invalid = |reason: &str| std::io::Error::new(std::io::ErrorKind::InvalidInput, reason);
+    let dir = frame
+        .parent()
+        .ok_or_else(|| invalid("Frame has no parent folder"))?
+        .to_path_buf();
+    let name = frame
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| invalid("Invalid frame name"))?;
+    let (base, delimiter, digits, extension) =
+        split_frame_name(name).ok_or_else(|| invalid("File name has no frame number"))?;
+
+    let mut frames = Vec::new();
+    for entry in fs::read_dir(&dir)?.flatten() {
+        let file_name = entry.file_name();
+        let Some(parts) = file_name.to_str().and_then(split_frame_name) else {
+            continue;
+        };
+        if (parts.0, parts.1, parts.3) == (base, delimiter, extension) {
+            if let Ok(number) = parts.2.parse() {
+                frames.push((number, entry.path()));
+            }
+        }
+    }
+    frames.sort();
+
+    Ok(SequenceScan {
+        dir,
+        base: base.to_string(),
+        delimiter: delimiter.to_string(),
+        padding: digits.len(),
+        extension: extension.to_string(),
+        frames,
+    })
+}
+
+// Target naming; gaps in the source numbering are kept
+#[derive(Debug, Clone, PartialEq)]
+pub struct SequenceTemplate {
+    pub base: String,
+    pub delimiter: String,
+    pub padding: usize,
+    pub start: u32,
+}
+
+impl SequenceTemplate {
+    pub fn from_scan(scan: &SequenceScan) -> Self {
+        Self {
+            base: scan.base.clone(),
+            delimiter: scan.delimiter.clone(),
+            padding: scan.padding,
+            start: scan.frames.first().map_or(0, |(number, _)| *number),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenamePlan {
+    pub renames: Vec<(PathBuf, PathBuf)>,
+    // Targets already taken by files outside the sequence
+    pub conflicts: Vec<PathBuf>,
+}
+
+pub fn plan_renames(scan: &SequenceScan, template: &SequenceTemplate) -> RenamePlan {
+    let base = sanitize_base_name(&template.base);
+    let first = scan.frames.first().map_or(0, |(number, _)| *number);
+    let sources: HashSet<&Path> = scan.frames.iter().map(|(_, path)| path.as_path()).collect();
+
+    let mut renames = Vec::new();
+    let mut conflicts = Vec::new();
+    for (number, path) in &scan.frames {
+        let number = template.start as u64 + (*number - first) as u64;
+        let target = scan.dir.join(format!(
+            "{}{}{:0width$}.{}",
+            base,
+            template.delimiter,
+            number,
+            scan.extension,
+            width = template.padding
+        ));
+        if target == *path {
+            continue;
+        }
+        if target.exists() && !sources.contains(target.as_path()) {
+            conflicts.push(target.clone());
+        }
+        renames.push((path.clone(), target));
+    }
+    RenamePlan { renames, conflicts }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenumberStatus {
+    Renaming,
+    Done(usize),
+    Failed(String),
+}
+
+impl RenumberStatus {
+    pub fn describe(&self) -> String {
+        match self {
+            RenumberStatus::Renaming => "Renaming...".to_string(),
+            RenumberStatus::Done(count) => format!("Renamed {} frames", count),
+            RenumberStatus::Failed(e) => format!("Failed ({})", e),
+        }
+    }
+}
+
+// Two passes through temporary names, so shifting 1..N to 2..N+1 never
+// renames a frame over one that hasn't moved yet. A failure in either pass puts
+// every frame back under its original name.
+fn apply_renames(renames: &[(PathBuf, PathBuf)]) -> std::io::Result<usize> {
+    let mut staged = Vec::with_capacity(renames.len());
+    for (index, (source, target)) in renames.iter().enumerate() {
+        let temporary = source.with_file_name(format!(".renumber-{:08}.tmp", index));
+        if let Err(e) = fs::rename(source, &temporary) {
+            // Put back what already moved so the sequence is left as it was
+            for (source, temporary, _) in staged.iter().rev() {
+                let _ = fs::rename(temporary, source);
+            }
+            return Err(e);
+        }
+        staged.push((source, temporary, target));
+    }
+    for (index, (_, temporary, target)) in staged.iter().enumerate() {
+        if let Err(e) = fs::rename(temporary, target) {
+            // Finished frames may sit on another frame's original name, so they all
+            // go back to their temporary names before anything takes its original
+            for (_, temporary, target) in staged[..index].iter().rev() {
+                let _ = fs::rename(target, temporary);
+            }
+            for (source, temporary, _) in staged.iter().rev() {
+                let _ = fs::rename(temporary, source);
+            }
+            return Err(e);
+        }
+    }
+    Ok(staged.len())
+}
+
+pub fn spawn_renumber(plan: RenamePlan) -> Receiver<RenumberStatus> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let _ = sender.send(RenumberStatus::Renaming);
+        let status = match apply_renames(&plan.renames) {
+            Ok(count) => RenumberStatus::Done(count),
+            Err(e) => RenumberStatus::Failed(e.to_string()),
+        };
+        let _ = sender.send(status);
+    });
+
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_frames(dir: &Path, names: &[&str]) -> Vec<PathBuf> {
+        names
+            .iter()
+            .map(|name| {
+                let path = dir.join(name);
+                fs::write(&path, name).unwrap();
+                path
+            })
+            .collect()
+    }
+
+    fn contents(path: &Path) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+
+    fn leftover_temporaries(dir: &Path) -> usize {
+        fs::read_dir(dir)
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+            .count()
+    }
+
+    #[test]
+    fn swapped_frames_trade_places() {
+        let dir = tempfile::tempdir().unwrap();
+        let frames = write_frames(dir.path(), &["shot-0001.png", "shot-0002.png"]);
+        let renames = vec![
+            (frames[0].clone(), frames[1].clone()),
+            (frames[1].clone(), frames[0].clone()),
+        ];
+
+        assert_eq!(apply_renames(&renames).unwrap(), 2);
+        assert_eq!(contents(&frames[0]), "shot-0002.png");
+        assert_eq!(contents(&frames[1]), "shot-0001.png");
+        assert_eq!(leftover_temporaries(dir.path()), 0);
+    }
+
+    #[test]
+    fn shifting_a_sequence_up_keeps_every_frame() {
+        let dir = tempfile::tempdir().unwrap();
+        write_frames(
+            dir.path(),
+            &["shot-0001.png", "shot-0002.png", "shot-0003.png"],
+        );
+        let scan = scan_sequence(&dir.path().join("shot-0001.png")).unwrap();
+        let template = SequenceTemplate {
+            start: 2,
+            ..SequenceTemplate::from_scan(&scan)
+        };
+        let plan = plan_renames(&scan, &template);
+        assert!(plan.conflicts.is_empty());
+
+        assert_eq!(apply_renames(&plan.renames).unwrap(), 3);
+        for (number, original) in [
+            (2, "shot-0001.png"),
+            (3, "shot-0002.png"),
+            (4, "shot-0003.png"),
+        ] {
+            let path = dir.path().join(format!("shot-{:04}.png", number));
+            assert_eq!(contents(&path), original);
+        }
+        assert!(!dir.path().join("shot-0001.png").exists());
+    }
+
+    #[test]
+    fn failed_second_pass_restores_original_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let frames = write_frames(dir.path(), &["shot-0001.png", "shot-0002.png"]);
+        // The swap's first rename lands; the second target's folder does not exist
+        let renames = vec![
+            (frames[0].clone(), frames[1].clone()),
+            (
+                frames[1].clone(),
+                dir.path().join("missing").join("shot-0001.png"),
+            ),
+        ];
+
+        assert!(apply_renames(&renames).is_err());
+        assert_eq!(contents(&frames[0]), "shot-0001.png");
+        assert_eq!(contents(&frames[1]), "shot-0002.png");
+        assert_eq!(leftover_temporaries(dir.path()), 0);
+    }
+}
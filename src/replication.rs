@@ -0,0 +1,96 @@
+use anyhow::{anyhow, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+    thread,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplicationStatus {
+    Pending,
+    Copying { copied: usize, total: usize },
+    Done(usize),
+    Failed(String),
+}
+
+impl ReplicationStatus {
+    pub fn describe(&self) -> String {
+        match self {
+            ReplicationStatus::Pending => "Pending".to_string(),
+            ReplicationStatus::Copying { copied, total } => {
+                format!("Copying {}/{}", copied, total)
+            }
+            ReplicationStatus::Done(count) => format!("Done ({} files)", count),
+            ReplicationStatus::Failed(e) => format!("Failed: {}", e),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            ReplicationStatus::Done(_) | ReplicationStatus::Failed(_)
+        )
+    }
+}
+
+// Spawns one copy thread per destination; progress is reported by destination index
+pub fn replicate_all(
+    files: Vec<PathBuf>,
+    destinations: Vec<PathBuf>,
+    status_sender: Sender<(usize, ReplicationStatus)>,
+) -> Vec<thread::JoinHandle<()>> {
+    destinations
+        .into_iter()
+        .enumerate()
+        .map(|(index, destination)| {
+            let files = files.clone();
+            let sender = status_sender.clone();
+            thread::spawn(move || {
+                let status = match replicate(&files, &destination, index, &sender) {
+                    Ok(count) => ReplicationStatus::Done(count),
+                    Err(e) => ReplicationStatus::Failed(e.to_string()),
+                };
+                let _ = sender.send((index, status));
+            })
+        })
+        .collect()
+}
+
+fn replicate(
+    files: &[PathBuf],
+    destination: &Path,
+    index: usize,
+    sender: &Sender<(usize, ReplicationStatus)>,
+) -> Result<usize> {
+    fs::create_dir_all(destination)?;
+
+    let total = files.len();
+    for (copied, source) in files.iter().enumerate() {
+        let file_name = source
+            .file_name()
+            .ok_or_else(|| anyhow!("Invalid file path {}", source.display()))?;
+        let target = destination.join(file_name);
+
+        let written = fs::copy(source, &target)?;
+        let expected = fs::metadata(source)?.len();
+        if written != expected {
+            return Err(anyhow!(
+                "Size mismatch for {}: {} of {} bytes written",
+                target.display(),
+                written,
+                expected
+            ));
+        }
+
+        let _ = sender.send((
+            index,
+            ReplicationStatus::Copying {
+                copied: copied + 1,
+                total,
+            },
+        ));
+    }
+
+    Ok(total)
+}
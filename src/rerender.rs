@@ -0,0 +1,51 @@
+use std::{
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use crate::encoding::{rerender_frame, EncodingConfig};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RerenderStatus {
+    Rendering { done: usize, total: usize },
+    Done(usize),
+    Failed(String),
+}
+
+impl RerenderStatus {
+    pub fn describe(&self) -> String {
+        match self {
+            RerenderStatus::Rendering { done, total } => {
+                format!("Re-rendering {}/{}...", done, total)
+            }
+            RerenderStatus::Done(count) => format!("Re-rendered {} frames in place", count),
+            RerenderStatus::Failed(e) => format!("Failed ({})", e),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, RerenderStatus::Rendering { .. })
+    }
+}
+
+// Frames are overwritten in the delivery folder one at a time, so a failure
+// leaves the ones before it fixed
+pub fn spawn_rerender(config: EncodingConfig, frames: Vec<u32>) -> Receiver<RerenderStatus> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        for (done, &frame) in frames.iter().enumerate() {
+            let _ = sender.send(RerenderStatus::Rendering {
+                done,
+                total: frames.len(),
+            });
+            if let Err(e) = rerender_frame(&config, frame) {
+                let _ = sender.send(RerenderStatus::Failed(format!("frame {}: {}", frame, e)));
+                return;
+            }
+        }
+        let _ = sender.send(RerenderStatus::Done(frames.len()));
+    });
+
+    receiver
+}
@@ -0,0 +1,142 @@
+use std::time::{Duration, Instant};
+
+// CPU and memory of the ffmpeg child, read straight from the OS counters.
+// CPU is reported like top: 100% is one fully busy core.
+pub struct ResourceMonitor {
+    pid: u32,
+    last: Option<(Instant, Duration)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResourceUsage {
+    pub cpu_percent: f32,
+    pub rss_bytes: u64,
+}
+
+impl ResourceUsage {
+    pub fn describe(&self) -> String {
+        format!(
+            "CPU: {:.0}% | RAM: {} MB",
+            self.cpu_percent,
+            self.rss_bytes / (1024 * 1024)
+        )
+    }
+}
+
+impl ResourceMonitor {
+    pub fn new(pid: u32) -> Self {
+        Self { pid, last: None }
+    }
+
+    // ffmpeg's total CPU time as of the last sample
+    pub fn cpu_time(&self) -> Duration {
+        self.last.map_or(Duration::ZERO, |(_, cpu_time)| cpu_time)
+    }
+
+    // The first sample only primes the CPU counter and returns None
+    pub fn sample(&mut self) -> Option<ResourceUsage> {
+        let (cpu_time, rss_bytes) = read_counters(self.pid)?;
+        let now = Instant::now();
+
+        let usage = self.last.map(|(last_at, last_cpu)| {
+            let wall = now.duration_since(last_at).as_secs_f32().max(0.001);
+            let busy = cpu_time.saturating_sub(last_cpu).as_secs_f32();
+            ResourceUsage {
+                cpu_percent: busy / wall * 100.0,
+                rss_bytes,
+            }
+        });
+        self.last = Some((now, cpu_time));
+        usage
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn read_counters(pid: u32) -> Option<(Duration, u64)> {
+    // USER_HZ is 100 on every Linux target we ship to
+    const TICKS_PER_SEC: u64 = 100;
+
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name can contain spaces, so fields are counted after its closing paren
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    let ticks = utime + stime;
+    let cpu_time = Duration::from_millis(ticks * 1000 / TICKS_PER_SEC);
+
+    let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let rss_kb: u64 = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+
+    Some((cpu_time, rss_kb * 1024))
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn read_counters(pid: u32) -> Option<(Duration, u64)> {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "time=,rss=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut fields = text.split_whitespace();
+    let time = fields.next()?;
+    let rss_kb: u64 = fields.next()?.parse().ok()?;
+
+    // [dd-]hh:mm:ss or m:ss.cc depending on the platform
+    let (days, clock) = time.split_once('-').unwrap_or(("0", time));
+    let mut seconds = days.parse::<f64>().ok()? * 86_400.0;
+    for (position, part) in clock.rsplit(':').enumerate() {
+        seconds += part.parse::<f64>().ok()? * 60f64.powi(position as i32);
+    }
+
+    Some((Duration::from_secs_f64(seconds), rss_kb * 1024))
+}
+
+#[cfg(windows)]
+fn read_counters(pid: u32) -> Option<(Duration, u64)> {
+    use winapi::{
+        shared::minwindef::FILETIME,
+        um::{
+            handleapi::CloseHandle,
+            processthreadsapi::{GetProcessTimes, OpenProcess},
+            psapi::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS},
+            winnt::PROCESS_QUERY_LIMITED_INFORMATION,
+        },
+    };
+
+    // FILETIME counts 100ns intervals
+    let hundred_ns =
+        |time: FILETIME| ((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle.is_null() {
+            return None;
+        }
+
+        let mut creation: FILETIME = std::mem::zeroed();
+        let mut exit: FILETIME = std::mem::zeroed();
+        let mut kernel: FILETIME = std::mem::zeroed();
+        let mut user: FILETIME = std::mem::zeroed();
+        let times_ok =
+            GetProcessTimes(handle, &mut creation, &mut exit, &mut kernel, &mut user) != 0;
+
+        let mut counters: PROCESS_MEMORY_COUNTERS = std::mem::zeroed();
+        counters.cb = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+        let memory_ok = GetProcessMemoryInfo(handle, &mut counters, counters.cb) != 0;
+
+        CloseHandle(handle);
+        if !(times_ok && memory_ok) {
+            return None;
+        }
+
+        let cpu_time = Duration::from_nanos((hundred_ns(kernel) + hundred_ns(user)) * 100);
+        Some((cpu_time, counters.WorkingSetSize as u64))
+    }
+}
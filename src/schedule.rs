@@ -0,0 +1,237 @@
+use anyhow::{anyhow, Result};
+
+const DAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+// One "Mon-Fri 19:00-08:00" entry. A window that ends before it starts runs
+// past midnight into the next day; start == end is the whole day.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct WindowRule {
+    days: [bool; 7],
+    start: u32,
+    end: u32,
+}
+
+impl WindowRule {
+    fn allows(&self, weekday: usize, minute: u32) -> bool {
+        let yesterday = (weekday + 6) % 7;
+        if self.start == self.end {
+            self.days[weekday]
+        } else if self.start < self.end {
+            self.days[weekday] && (self.start..self.end).contains(&minute)
+        } else {
+            (self.days[weekday] && minute >= self.start)
+                || (self.days[yesterday] && minute < self.end)
+        }
+    }
+}
+
+// Hours queued jobs may start in, so heavy encodes stay off the network
+// during the working day. No rules allows any time.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EncodeWindows {
+    rules: Vec<WindowRule>,
+}
+
+fn parse_day(value: &str) -> Result<usize> {
+    let value = value.trim().to_lowercase();
+    DAYS.iter()
+        .position(|day| value.starts_with(day))
+        .ok_or_else(|| anyhow!("Unknown day '{}'", value))
+}
+
+// "Mon-Fri", "Sat,Sun", "daily"; a range may wrap, as in "Fri-Mon"
+fn parse_days(value: &str) -> Result<[bool; 7]> {
+    let mut days = [false; 7];
+    if matches!(value.to_lowercase().as_str(), "daily" | "every day") {
+        return Ok([true; 7]);
+    }
+    for part in value.split(',') {
+        match part.split_once('-') {
+            Some((from, to)) => {
+                let (from, to) = (parse_day(from)?, parse_day(to)?);
+                let mut day = from;
+                loop {
+                    days[day] = true;
+                    if day == to {
+                        break;
+                    }
+                    day = (day + 1) % 7;
+                }
+            }
+            None => days[parse_day(part)?] = true,
+        }
+    }
+    Ok(days)
+}
+
+fn parse_time(value: &str) -> Result<u32> {
+    let invalid = || anyhow!("Invalid time '{}', expected HH:MM", value);
+    let (hours, minutes) = value.trim().split_once(':').ok_or_else(invalid)?;
+    let hours: u32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u32 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 24 || minutes > 59 || (hours == 24 && minutes > 0) {
+        return Err(invalid());
+    }
+    Ok((hours * 60 + minutes) % MINUTES_PER_DAY)
+}
+
+impl EncodeWindows {
+    // Rules split by semicolons or lines, such as "Mon-Fri 19:00-08:00; Sat-Sun any".
+    // Days may be left out for every day.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+        for rule in text
+            .split([';', '\n'])
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+        {
+            let (days, hours) = match rule.rsplit_once(char::is_whitespace) {
+                Some((days, hours)) => (parse_days(days.trim())?, hours),
+                None => ([true; 7], rule),
+            };
+            let (start, end) = match hours.to_lowercase().as_str() {
+                "any" | "anytime" | "all-day" => (0, 0),
+                _ => {
+                    let (start, end) = hours.split_once('-').ok_or_else(|| {
+                        anyhow!("Invalid hours '{}', expected 19:00-08:00", hours)
+                    })?;
+                    (parse_time(start)?, parse_time(end)?)
+                }
+            };
+            rules.push(WindowRule { days, start, end });
+        }
+        Ok(Self { rules })
+    }
+
+    pub fn is_unrestricted(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    fn allows(&self, weekday: usize, minute: u32) -> bool {
+        self.is_unrestricted() || self.rules.iter().any(|rule| rule.allows(weekday, minute))
+    }
+
+    // Seconds until a job may start, 0 inside a window. `weekday` counts from
+    // Monday = 0 and `secs` from local midnight.
+    pub fn wait_secs(&self, weekday: usize, secs: u32) -> u64 {
+        let minute = secs / 60;
+        if self.allows(weekday, minute) {
+            return 0;
+        }
+        (1..=7 * MINUTES_PER_DAY)
+            .find(|ahead| {
+                let later = minute + ahead;
+                self.allows(
+                    (weekday + (later / MINUTES_PER_DAY) as usize) % 7,
+                    later % MINUTES_PER_DAY,
+                )
+            })
+            .map_or(0, |ahead| ahead as u64 * 60 - (secs % 60) as u64)
+    }
+
+    pub fn wait_now(&self) -> u64 {
+        if self.is_unrestricted() {
+            return 0;
+        }
+        let (weekday, secs) = local_week_time();
+        self.wait_secs(weekday, secs)
+    }
+}
+
+// (weekday from Monday = 0, seconds since midnight) on the station's clock
+#[cfg(unix)]
+fn local_week_time() -> (usize, u32) {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs()) as libc::time_t;
+    unsafe {
+        let mut local: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&now, &mut local).is_null() {
+            return (0, 0);
+        }
+        (
+            (local.tm_wday as usize + 6) % 7,
+            (local.tm_hour * 3600 + local.tm_min * 60 + local.tm_sec) as u32,
+        )
+    }
+}
+
+#[cfg(windows)]
+fn local_week_time() -> (usize, u32) {
+    use winapi::um::{minwinbase::SYSTEMTIME, sysinfoapi::GetLocalTime};
+
+    unsafe {
+        let mut local: SYSTEMTIME = std::mem::zeroed();
+        GetLocalTime(&mut local);
+        (
+            (local.wDayOfWeek as usize + 6) % 7,
+            local.wHour as u32 * 3600 + local.wMinute as u32 * 60 + local.wSecond as u32,
+        )
+    }
+}
+
+// "3h 05m" for the queue's countdown
+pub fn format_wait(secs: u64) -> String {
+    let minutes = secs.div_ceil(60);
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONDAY: usize = 0;
+    const FRIDAY: usize = 4;
+    const SATURDAY: usize = 5;
+
+    fn at(hours: u32, minutes: u32) -> u32 {
+        (hours * 60 + minutes) * 60
+    }
+
+    #[test]
+    fn window_across_midnight_runs_into_the_next_morning() {
+        let windows = EncodeWindows::parse("22:00-06:00").unwrap();
+        assert_eq!(windows.wait_secs(MONDAY, at(23, 0)), 0);
+        assert_eq!(windows.wait_secs(MONDAY, at(5, 59)), 0);
+        assert_eq!(windows.wait_secs(MONDAY, at(6, 0)), 16 * 3600);
+        assert_eq!(windows.wait_secs(MONDAY, at(21, 59) + 30), 30);
+        assert_eq!(windows.wait_secs(MONDAY, at(22, 0)), 0);
+    }
+
+    #[test]
+    fn window_across_midnight_belongs_to_the_day_it_starts() {
+        let windows = EncodeWindows::parse("Fri 22:00-06:00").unwrap();
+        assert_eq!(windows.wait_secs(FRIDAY, at(23, 0)), 0);
+        assert_eq!(windows.wait_secs(SATURDAY, at(5, 59)), 0);
+        assert_ne!(windows.wait_secs(FRIDAY, at(5, 59)), 0);
+        assert_eq!(
+            windows.wait_secs(SATURDAY, at(6, 0)),
+            6 * 24 * 3600 + 16 * 3600
+        );
+    }
+
+    #[test]
+    fn same_day_window_ends_at_its_end_time() {
+        let windows = EncodeWindows::parse("Mon-Fri 09:00-17:00").unwrap();
+        assert_eq!(windows.wait_secs(MONDAY, at(8, 59)), 60);
+        assert_eq!(windows.wait_secs(MONDAY, at(9, 0)), 0);
+        assert_eq!(windows.wait_secs(MONDAY, at(16, 59)), 0);
+        assert_eq!(windows.wait_secs(MONDAY, at(17, 0)), 16 * 3600);
+        assert_eq!(
+            windows.wait_secs(FRIDAY, at(17, 0)),
+            2 * 24 * 3600 + 16 * 3600
+        );
+    }
+
+    #[test]
+    fn no_rules_never_wait() {
+        let windows = EncodeWindows::parse("").unwrap();
+        assert!(windows.is_unrestricted());
+        assert_eq!(windows.wait_secs(SATURDAY, at(12, 0)), 0);
+    }
+}
@@ -0,0 +1,136 @@
+use eframe::egui;
+use image::RgbaImage;
+
+const LEVELS: usize = 256;
+const WAVEFORM_COLUMNS: usize = 256;
+
+// Broadcast-legal 8-bit luma range
+const LEGAL_MIN: usize = 16;
+const LEGAL_MAX: usize = 235;
+
+pub struct Scopes {
+    histogram: [[u32; LEVELS]; 3],
+    waveform: Vec<u32>,
+    illegal_fraction: f32,
+}
+
+impl Scopes {
+    pub fn compute(image: &RgbaImage) -> Self {
+        let mut histogram = [[0u32; LEVELS]; 3];
+        let mut waveform = vec![0u32; WAVEFORM_COLUMNS * LEVELS];
+        let mut illegal = 0u64;
+
+        let width = image.width().max(1) as usize;
+        for (x, _, pixel) in image.enumerate_pixels() {
+            let [r, g, b, _] = pixel.0;
+            histogram[0][r as usize] += 1;
+            histogram[1][g as usize] += 1;
+            histogram[2][b as usize] += 1;
+
+            // Rec.709 luma coefficients, on full-range RGB
+            let full = 0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32;
+            // The code value a limited-range encode carries, 16 + 219·Y/255, which is
+            // what the legal range and the waveform's guide lines are in
+            let luma =
+                (LEGAL_MIN as f32 + (LEGAL_MAX - LEGAL_MIN) as f32 * full / 255.0).round() as usize;
+            let luma = luma.min(LEVELS - 1);
+            if !(LEGAL_MIN..=LEGAL_MAX).contains(&luma) {
+                illegal += 1;
+            }
+
+            let column = x as usize * WAVEFORM_COLUMNS / width;
+            waveform[(LEVELS - 1 - luma) * WAVEFORM_COLUMNS + column] += 1;
+        }
+
+        let total = (image.width() as u64 * image.height() as u64).max(1);
+        Self {
+            histogram,
+            waveform,
+            illegal_fraction: illegal as f32 / total as f32,
+        }
+    }
+
+    pub fn waveform_image(&self) -> egui::ColorImage {
+        let peak = self.waveform.iter().copied().max().unwrap_or(1).max(1) as f32;
+        let pixels = self
+            .waveform
+            .iter()
+            .map(|&count| {
+                // Log scaling keeps sparse traces visible next to dense areas
+                let intensity = ((count as f32).ln_1p() / peak.ln_1p() * 255.0) as u8;
+                egui::Color32::from_rgb(intensity / 3, intensity, intensity / 3)
+            })
+            .collect();
+
+        egui::ColorImage {
+            size: [WAVEFORM_COLUMNS, LEVELS],
+            pixels,
+        }
+    }
+
+    pub fn show(&self, ui: &mut egui::Ui, waveform: &egui::TextureHandle) {
+        let scope_size = egui::vec2(256.0, 128.0);
+
+        ui.horizontal(|ui| {
+            let (rect, _) = ui.allocate_exact_size(scope_size, egui::Sense::hover());
+            let painter = ui.painter_at(rect);
+            painter.rect_filled(rect, 0.0, egui::Color32::from_gray(10));
+
+            let peak = self
+                .histogram
+                .iter()
+                .flat_map(|channel| channel.iter())
+                .copied()
+                .max()
+                .unwrap_or(1)
+                .max(1) as f32;
+            let colors = [
+                egui::Color32::from_rgb(230, 60, 60),
+                egui::Color32::from_rgb(60, 200, 60),
+                egui::Color32::from_rgb(70, 110, 240),
+            ];
+
+            for (channel, color) in self.histogram.iter().zip(colors) {
+                let points = channel
+                    .iter()
+                    .enumerate()
+                    .map(|(level, &count)| {
+                        let x = rect.left() + rect.width() * level as f32 / (LEVELS - 1) as f32;
+                        let y = rect.bottom() - rect.height() * count as f32 / peak;
+                        egui::pos2(x, y)
+                    })
+                    .collect();
+                painter.add(egui::Shape::line(points, egui::Stroke::new(1.0, color)));
+            }
+
+            let (rect, _) = ui.allocate_exact_size(scope_size, egui::Sense::hover());
+            let painter = ui.painter_at(rect);
+            let full_uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+            painter.image(waveform.id(), rect, full_uv, egui::Color32::WHITE);
+
+            for level in [LEGAL_MIN, LEGAL_MAX] {
+                let y = rect.bottom() - rect.height() * level as f32 / (LEVELS - 1) as f32;
+                painter.hline(
+                    rect.x_range(),
+                    y,
+                    egui::Stroke::new(1.0, egui::Color32::from_rgb(200, 150, 50)),
+                );
+            }
+        });
+
+        let color = if self.illegal_fraction > 0.0 {
+            egui::Color32::LIGHT_RED
+        } else {
+            egui::Color32::LIGHT_GREEN
+        };
+        ui.colored_label(
+            color,
+            format!(
+                "Luma outside {}-{}: {:.2}% of pixels",
+                LEGAL_MIN,
+                LEGAL_MAX,
+                self.illegal_fraction * 100.0
+            ),
+        );
+    }
+}
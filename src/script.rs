@@ -0,0 +1,127 @@
+use anyhow::Result;
+use std::{ffi::OsStr, fs, path::Path};
+
+use crate::{
+    encoding::{plan_encoding, EncodingConfig},
+    errors::{EncoderError, IoSide},
+};
+
+// Shell script with the exact ffmpeg commands the app would run, one job after
+// another in the order given, for machines without the GUI. A .bat target gets
+// cmd syntax, anything else POSIX sh.
+pub fn export_script(configs: &[EncodingConfig], path: &Path) -> Result<()> {
+    let batch = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("bat"));
+
+    let mut script = String::new();
+    if batch {
+        script.push_str("@echo off\r\n");
+    } else {
+        script.push_str("#!/bin/sh\nset -e\n");
+    }
+    for config in configs {
+        script.push_str(&job_commands(config, batch)?);
+    }
+
+    fs::write(path, script).map_err(|e| EncoderError::from_io(&e, path, IoSide::Output))?;
+
+    #[cfg(unix)]
+    if !batch {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(())
+}
+
+fn job_commands(config: &EncodingConfig, batch: bool) -> Result<String> {
+    let plan = plan_encoding(config, false)?;
+    let quote = if batch { quote_batch } else { quote_sh };
+    let newline = if batch { "\r\n" } else { "\n" };
+    let comment = if batch { "rem" } else { "#" };
+
+    let mut script = String::new();
+    script.push_str(&format!(
+        "{} {} ({}) - generated by Delivery Encoder {}{}",
+        comment,
+        config.base_name,
+        config.output_format.as_str(),
+        env!("CARGO_PKG_VERSION"),
+        newline
+    ));
+    if config.audio_stems.is_some() || config.output_format.is_imf() {
+        script.push_str(&format!(
+            "{} Audio stems and IMF packaging are done by the app and are not included{}",
+            comment, newline
+        ));
+    }
+
+    let mut vars: Vec<(String, String)> = Vec::new();
+    if let Some(dir) = &config.environment.scratch_dir {
+        let dir = dir.to_string_lossy().into_owned();
+        vars.extend(["TMPDIR", "TEMP", "TMP"].map(|name| (name.to_string(), dir.clone())));
+    }
+    vars.extend(config.environment.vars.iter().cloned());
+    for (name, value) in vars {
+        if batch {
+            script.push_str(&format!(
+                "set \"{}={}\"\r\n",
+                name,
+                value.replace('%', "%%")
+            ));
+        } else {
+            script.push_str(&format!("export {}={}\n", name, quote(OsStr::new(&value))));
+        }
+    }
+
+    let output_dir = quote(config.output_dir.as_os_str());
+    if batch {
+        script.push_str(&format!(
+            "if not exist {dir} mkdir {dir}\r\n",
+            dir = output_dir
+        ));
+    } else {
+        script.push_str(&format!("mkdir -p {}\n", output_dir));
+    }
+
+    let args: Vec<String> = plan
+        .args(config, None)
+        .iter()
+        .map(|arg| quote(arg))
+        .collect();
+    // The pipe command is passed through as typed, like the app's shell would see it
+    if batch && config.pipe_command.is_some() {
+        script.push_str(&format!("cd /d {}\r\n", output_dir));
+    }
+    let pipe = match &config.pipe_command {
+        Some(command) if batch => format!(" | {}", command),
+        Some(command) => format!(" | (cd {} && {})", output_dir, command),
+        None => String::new(),
+    };
+    script.push_str(&format!(
+        "{} {}{}{}",
+        quote(config.ffmpeg_path.as_os_str()),
+        args.join(" "),
+        pipe,
+        newline
+    ));
+    // sh stops on the first failure through set -e; cmd has to be told
+    if batch {
+        script.push_str("if errorlevel 1 exit /b 1\r\n");
+    }
+    Ok(script)
+}
+
+fn quote_sh(arg: &OsStr) -> String {
+    format!("'{}'", arg.to_string_lossy().replace('\'', "'\\''"))
+}
+
+// cmd expands %VAR% even inside quotes, and a doubled quote stays a literal quote
+fn quote_batch(arg: &OsStr) -> String {
+    format!(
+        "\"{}\"",
+        arg.to_string_lossy()
+            .replace('%', "%%")
+            .replace('"', "\"\"")
+    )
+}
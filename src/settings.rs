@@ -0,0 +1,150 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::{env, fs, path::PathBuf};
+
+use crate::{
+    energy::EnergyRates,
+    permissions::OutputPermissions,
+    purge::RetentionPolicy,
+    schedule::EncodeWindows,
+    utils::{app_data_dir, JobEnvironment},
+};
+
+// Station-wide preferences persisted between launches
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub telemetry_enabled: bool,
+    pub telemetry_url: String,
+    pub machine_id: String,
+    pub active_show: String,
+    // Seconds without a new frame before a running job counts as stalled
+    pub stall_timeout_secs: u64,
+    // Minutes a job may stay stalled before it is killed and resumed; 0 leaves it to the operator
+    pub auto_resume_stalled_mins: u32,
+    // Per-phase limits before a dead mount counts as a timeout
+    pub probe_timeout_secs: u64,
+    pub first_frame_timeout_secs: u64,
+    pub copy_stall_timeout_secs: u64,
+    // Fast local disk for temp files; None uses the OS temp directory
+    pub scratch_dir: Option<PathBuf>,
+    // Extra KEY=VALUE lines passed to every ffmpeg run
+    pub job_env: String,
+    // Octal mode and group for delivered files on Unix; empty leaves the umask default
+    pub output_mode: String,
+    pub output_group: String,
+    // Version purge keeps this many versions of each delivery
+    pub keep_versions: usize,
+    // ...and also purges older versions past this many days; 0 turns age off
+    pub purge_after_days: u32,
+    // Source masters are copied here after a successful delivery; None skips archiving
+    pub archive_dir: Option<PathBuf>,
+    // Remove the original once the archived copy has verified
+    pub archive_move: bool,
+    // Other tools submit jobs by dropping JSON files here; None turns the spool off
+    pub spool_dir: Option<PathBuf>,
+    // Full-load CPU package power and electricity price for the per-job cost ledger
+    pub cpu_tdp_watts: f64,
+    pub energy_price_per_kwh: f64,
+    // When the queue may start jobs, e.g. "Mon-Fri 19:00-08:00; Sat-Sun any"; empty is any time
+    pub encode_windows: String,
+    // Volumes a sequence continues on, in order, when its output volume is too small;
+    // empty never splits
+    pub overflow_dirs: Vec<PathBuf>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let machine_id = env::var("COMPUTERNAME")
+            .or_else(|_| env::var("HOSTNAME"))
+            .unwrap_or_else(|_| "unknown-station".to_string());
+
+        Self {
+            telemetry_enabled: false,
+            telemetry_url: env::var("DELIVERY_ENCODER_TELEMETRY_URL").unwrap_or_default(),
+            machine_id,
+            active_show: String::new(),
+            stall_timeout_secs: 120,
+            auto_resume_stalled_mins: 0,
+            probe_timeout_secs: 30,
+            first_frame_timeout_secs: 300,
+            copy_stall_timeout_secs: 300,
+            scratch_dir: None,
+            job_env: String::new(),
+            output_mode: String::new(),
+            output_group: String::new(),
+            keep_versions: 2,
+            purge_after_days: 0,
+            archive_dir: None,
+            archive_move: false,
+            spool_dir: None,
+            cpu_tdp_watts: 125.0,
+            energy_price_per_kwh: 0.0,
+            encode_windows: String::new(),
+            overflow_dirs: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    fn path() -> PathBuf {
+        app_data_dir().join("settings.json")
+    }
+
+    pub fn load() -> Self {
+        fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn job_environment(&self) -> JobEnvironment {
+        let vars = self
+            .job_env
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .filter(|(name, _)| !name.is_empty())
+            .collect();
+        JobEnvironment {
+            scratch_dir: self.scratch_dir.clone(),
+            vars,
+        }
+    }
+
+    pub fn energy_rates(&self) -> EnergyRates {
+        EnergyRates {
+            cpu_tdp_watts: self.cpu_tdp_watts,
+            price_per_kwh: self.energy_price_per_kwh,
+        }
+    }
+
+    pub fn output_permissions(&self) -> OutputPermissions {
+        let group = self.output_group.trim();
+        OutputPermissions {
+            mode: OutputPermissions::parse_mode(&self.output_mode),
+            group: (!group.is_empty()).then(|| group.to_string()),
+        }
+    }
+
+    pub fn retention_policy(&self) -> RetentionPolicy {
+        RetentionPolicy {
+            keep_versions: self.keep_versions,
+            max_age_days: (self.purge_after_days > 0).then_some(self.purge_after_days),
+        }
+    }
+
+    // A rule that doesn't parse is flagged in the settings panel and ignored here
+    pub fn encode_windows(&self) -> EncodeWindows {
+        EncodeWindows::parse(&self.encode_windows).unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,196 @@
+use anyhow::{anyhow, Result};
+use std::{
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+use crate::{
+    encoding::{subtitle_filter, OverlayGraph},
+    models::{FitMode, MatteSpec, OverlaySpec, Resolution, SubtitleSpec},
+    utils::JobEnvironment,
+};
+
+// Small enough to paste into chat without a download link
+const SHARE_WIDTH: u32 = 480;
+const SHARE_FPS: u32 = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShareFormat {
+    Gif,
+    WebP,
+    // For chat clients that only inline video
+    Mp4,
+}
+
+impl ShareFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShareFormat::Gif => "GIF",
+            ShareFormat::WebP => "WebP",
+            ShareFormat::Mp4 => "MP4 (low-res)",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ShareFormat::Gif => "gif",
+            ShareFormat::WebP => "webp",
+            ShareFormat::Mp4 => "mp4",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShareStatus {
+    Exporting,
+    Done(PathBuf),
+    Failed(String),
+}
+
+impl ShareStatus {
+    pub fn describe(&self) -> String {
+        match self {
+            ShareStatus::Exporting => "Exporting...".to_string(),
+            ShareStatus::Done(path) => format!(
+                "Saved {}",
+                path.file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            ),
+            ShareStatus::Failed(e) => format!("Failed ({})", e),
+        }
+    }
+}
+
+pub struct ShareRequest {
+    pub ffmpeg_path: PathBuf,
+    pub input_video: PathBuf,
+    pub overlays: Vec<OverlaySpec>,
+    pub matte: Option<MatteSpec>,
+    pub source_size: (u32, u32),
+    // The delivery's frame size and fit, so the preview frames the picture as delivered
+    pub fixed_size: Option<(u32, u32)>,
+    pub fit: FitMode,
+    pub subtitles: Option<SubtitleSpec>,
+    pub start_secs: f64,
+    // Delivery frame at start_secs, so frame-ranged burn-ins line up with the delivery
+    pub first_frame: u32,
+    pub duration_secs: f64,
+    pub format: ShareFormat,
+    pub output: PathBuf,
+    pub environment: JobEnvironment,
+}
+
+pub fn spawn_share(request: ShareRequest) -> Receiver<ShareStatus> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let _ = sender.send(ShareStatus::Exporting);
+        let status = match export_share(&request) {
+            Ok(()) => ShareStatus::Done(request.output.clone()),
+            Err(e) => ShareStatus::Failed(e.to_string()),
+        };
+        let _ = sender.send(status);
+    });
+
+    receiver
+}
+
+fn export_share(request: &ShareRequest) -> Result<()> {
+    let start_time_str = format!("{:.3}", request.start_secs);
+    let subtitle_filter = subtitle_filter(request.subtitles.as_ref(), &start_time_str)?;
+
+    // Composited at delivery size so the overlay matches the delivery, then shrunk
+    let graph = OverlayGraph {
+        fixed_size: request.fixed_size,
+        source_size: request.source_size,
+        fit: request.fit,
+        overlays: &request.overlays,
+        flags: Resolution::K6.filter_flags(),
+        matte: request.matte,
+        retime_fps: None,
+        inverse_telecine: false,
+        seek: None,
+        first_frame: request.first_frame,
+        subtitle_filter: &subtitle_filter,
+        output_filters: "",
+        pix_fmt: "rgb24",
+    }
+    .build();
+    let shrink = format!(
+        "[out]fps={},scale={}:-2:flags=lanczos",
+        SHARE_FPS, SHARE_WIDTH
+    );
+    let filter_complex = match request.format {
+        // A per-clip palette avoids the banding of the default GIF palette
+        ShareFormat::Gif => format!(
+            "{}; {},split[a][b]; [a]palettegen=stats_mode=diff[p]; [b][p]paletteuse=dither=sierra2_4a[share]",
+            graph, shrink
+        ),
+        ShareFormat::WebP | ShareFormat::Mp4 => format!("{}; {}[share]", graph, shrink),
+    };
+
+    let mut command = Command::new(&request.ffmpeg_path);
+    request.environment.apply(&mut command);
+    command
+        .args(["-v", "error", "-ss", &start_time_str])
+        .args(["-t", &format!("{:.3}", request.duration_secs)])
+        .arg("-i")
+        .arg(&request.input_video);
+    for layer in &request.overlays {
+        command.arg("-i").arg(&layer.image);
+    }
+    command
+        .arg("-filter_complex")
+        .arg(&filter_complex)
+        .args(["-map", "[share]", "-an"]);
+    match request.format {
+        ShareFormat::Gif => {
+            command.args(["-loop", "0"]);
+        }
+        ShareFormat::WebP => {
+            command.args(["-loop", "0", "-c:v", "libwebp_anim", "-quality", "75"]);
+        }
+        ShareFormat::Mp4 => {
+            command.args([
+                "-c:v",
+                "libx264",
+                "-crf",
+                "28",
+                "-pix_fmt",
+                "yuv420p",
+                "-movflags",
+                "+faststart",
+            ]);
+        }
+    }
+    command
+        .arg("-y")
+        .arg(&request.output)
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    let output = {
+        #[cfg(windows)]
+        {
+            command.creation_flags(0x08000000).output()?
+        }
+        #[cfg(not(windows))]
+        {
+            command.output()?
+        }
+    };
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "FFmpeg failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,181 @@
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use crate::{
+    crash,
+    errors::{EncoderError, IoSide},
+};
+
+// Same headroom the storage check keeps on a single volume
+const SPACE_BUFFER: f64 = 1.2;
+
+// One volume's share of a sequence, from first_frame up to the next part's
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SplitPart {
+    pub dir: PathBuf,
+    pub first_frame: u32,
+}
+
+// Written into the first volume's folder when a sequence didn't fit on one
+// volume, so whoever picks up the delivery knows where the rest went
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SplitManifest {
+    pub base_name: String,
+    pub extension: String,
+    pub total_frames: u32,
+    pub parts: Vec<SplitPart>,
+}
+
+impl SplitManifest {
+    pub fn path(primary_dir: &Path, base_name: &str) -> PathBuf {
+        primary_dir.join(format!("{}_split.json", base_name))
+    }
+
+    pub fn read(primary_dir: &Path, base_name: &str) -> Option<Self> {
+        fs::read_to_string(Self::path(primary_dir, base_name))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+    }
+
+    pub fn write(&self, primary_dir: &Path) -> Result<()> {
+        let path = Self::path(primary_dir, &self.base_name);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .map_err(|e| EncoderError::from_io(&e, &path, IoSide::Output))?;
+        Ok(())
+    }
+
+    // Frames [first, end) of part `index`
+    pub fn frame_range(&self, index: usize) -> (u32, u32) {
+        let end = self
+            .parts
+            .get(index + 1)
+            .map_or(self.total_frames, |next| next.first_frame);
+        (self.parts[index].first_frame, end)
+    }
+}
+
+/// Spreads `total_frames` over `volumes` in order, each given as (folder, free
+/// bytes), filling one before starting the next. None when even all of them
+/// together are too small.
+pub fn plan_split(
+    volumes: &[(PathBuf, u64)],
+    bytes_per_frame: u64,
+    total_frames: u32,
+) -> Option<Vec<SplitPart>> {
+    let mut parts = Vec::new();
+    let mut next_frame = 0;
+    for (dir, free) in volumes {
+        if next_frame >= total_frames {
+            break;
+        }
+        let fits = (*free as f64 / SPACE_BUFFER / bytes_per_frame.max(1) as f64) as u32;
+        if fits == 0 {
+            continue;
+        }
+        parts.push(SplitPart {
+            dir: dir.clone(),
+            first_frame: next_frame,
+        });
+        next_frame = next_frame.saturating_add(fits);
+    }
+    (next_frame >= total_frames).then_some(parts)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsolidateStatus {
+    Moving { done: u32, total: u32 },
+    Done(u32),
+    Failed(String),
+}
+
+impl ConsolidateStatus {
+    pub fn describe(&self) -> String {
+        match self {
+            ConsolidateStatus::Moving { done, total } => {
+                format!("Moving frames... {}/{}", done, total)
+            }
+            ConsolidateStatus::Done(moved) => format!("Moved {} frames back together", moved),
+            ConsolidateStatus::Failed(e) => format!("Failed: {}", e),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            ConsolidateStatus::Done(_) | ConsolidateStatus::Failed(_)
+        )
+    }
+}
+
+// A rename where the volumes allow it, otherwise a copy that only drops the
+// original once it has fully landed
+fn move_frame(from: &Path, to: &Path) -> Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    fs::copy(from, to).map_err(|e| EncoderError::from_io(&e, to, IoSide::Output))?;
+    fs::remove_file(from).map_err(|e| EncoderError::from_io(&e, from, IoSide::Output))?;
+    Ok(())
+}
+
+fn consolidate(
+    primary_dir: &Path,
+    base_name: &str,
+    sender: &Sender<ConsolidateStatus>,
+) -> Result<u32> {
+    let manifest = SplitManifest::read(primary_dir, base_name)
+        .ok_or_else(|| anyhow!("No split manifest for {}", base_name))?;
+    let total = manifest.total_frames - manifest.frame_range(0).1;
+
+    let mut moved = 0;
+    for index in 1..manifest.parts.len() {
+        let (first, end) = manifest.frame_range(index);
+        for frame in first..end {
+            let name = format!("{}-{:06}.{}", base_name, frame, manifest.extension);
+            let from = manifest.parts[index].dir.join(&name);
+            if from.exists() {
+                move_frame(&from, &primary_dir.join(&name))?;
+            }
+            moved += 1;
+            if moved % 50 == 0 {
+                let _ = sender.send(ConsolidateStatus::Moving { done: moved, total });
+            }
+        }
+    }
+
+    let path = SplitManifest::path(primary_dir, base_name);
+    fs::remove_file(&path).map_err(|e| EncoderError::from_io(&e, &path, IoSide::Output))?;
+    Ok(moved)
+}
+
+// Brings a split sequence back into its first folder once that volume has room.
+// The manifest is only removed after every frame has moved, so a failed run can
+// simply be started again.
+pub fn spawn_consolidate(primary_dir: PathBuf, base_name: String) -> Receiver<ConsolidateStatus> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let _ = sender.send(ConsolidateStatus::Moving { done: 0, total: 0 });
+        let status = match consolidate(&primary_dir, &base_name, &sender) {
+            Ok(moved) => {
+                crash::log(format!(
+                    "Consolidated {} split frames of {} into {}",
+                    moved,
+                    base_name,
+                    primary_dir.display()
+                ));
+                ConsolidateStatus::Done(moved)
+            }
+            Err(e) => ConsolidateStatus::Failed(e.to_string()),
+        };
+        let _ = sender.send(status);
+    });
+
+    receiver
+}
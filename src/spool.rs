@@ -0,0 +1,115 @@
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::Duration,
+};
+
+use crate::models::{OutputFormat, Resolution};
+
+pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+// Files this fresh may still be being written by the submitting tool
+const SETTLE_TIME: Duration = Duration::from_secs(2);
+
+// One job per file, e.g.
+// {"input_video": "/san/show/ep01.mov", "output_dir": "/san/out", "resolution": "K4"}
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JobRequest {
+    pub input_video: PathBuf,
+    pub output_dir: PathBuf,
+    pub resolution: Resolution,
+    #[serde(default = "default_format")]
+    pub output_format: OutputFormat,
+    #[serde(default)]
+    pub notes: String,
+}
+
+fn default_format() -> OutputFormat {
+    OutputFormat::PngSequence
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpoolEvent {
+    Accepted(JobRequest),
+    Rejected { file: PathBuf, reason: String },
+}
+
+fn read_request(path: &Path) -> Result<JobRequest> {
+    let request: JobRequest = serde_json::from_str(&fs::read_to_string(path)?)?;
+    if !request.input_video.is_file() {
+        return Err(anyhow!("Input {} not found", request.input_video.display()));
+    }
+    if !request.output_dir.is_dir() {
+        return Err(anyhow!(
+            "Output directory {} not found",
+            request.output_dir.display()
+        ));
+    }
+    Ok(request)
+}
+
+// Picked-up files are renamed so a request is never queued twice; rejected ones
+// get the reason written next to them for whoever submitted it
+fn sweep(spool_dir: &Path) -> Vec<SpoolEvent> {
+    let Ok(entries) = fs::read_dir(spool_dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter(|path| {
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| modified.elapsed().unwrap_or_default() >= SETTLE_TIME)
+        })
+        .collect();
+    files.sort();
+
+    files
+        .into_iter()
+        .map(|file| match read_request(&file) {
+            Ok(request) => {
+                let _ = fs::rename(&file, file.with_extension("json.queued"));
+                SpoolEvent::Accepted(request)
+            }
+            Err(e) => {
+                let reason = e.to_string();
+                let _ = fs::write(file.with_extension("json.error.txt"), &reason);
+                let _ = fs::rename(&file, file.with_extension("json.rejected"));
+                SpoolEvent::Rejected { file, reason }
+            }
+        })
+        .collect()
+}
+
+// Dropping the watcher stops its thread before the next sweep, so a changed
+// spool folder never has two threads claiming files
+pub struct SpoolWatcher {
+    pub events: Receiver<SpoolEvent>,
+    _stop: Sender<()>,
+}
+
+pub fn spawn_watcher(spool_dir: PathBuf) -> SpoolWatcher {
+    let (sender, events) = mpsc::channel();
+    let (stop, stop_receiver) = mpsc::channel::<()>();
+
+    thread::spawn(move || loop {
+        for event in sweep(&spool_dir) {
+            let _ = sender.send(event);
+        }
+        match stop_receiver.recv_timeout(POLL_INTERVAL) {
+            Err(RecvTimeoutError::Timeout) => {}
+            _ => return,
+        }
+    });
+
+    SpoolWatcher {
+        events,
+        _stop: stop,
+    }
+}
@@ -0,0 +1,87 @@
+use anyhow::Result;
+use fs2::FileExt;
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    crash,
+    errors::{EncoderError, IoSide},
+    utils::JobEnvironment,
+};
+
+const STAGING_DIR: &str = "delivery_encoder_jobs";
+const LOCK_FILE: &str = ".lock";
+
+// Per-job folder under the scratch directory for the progress file and ffmpeg log.
+// It is removed when the job ends; a folder left behind by a crash still has its
+// lock released, which is how the next launch tells it apart from a live job.
+pub struct JobStaging {
+    dir: PathBuf,
+    lock: Option<File>,
+}
+
+impl JobStaging {
+    pub fn create(environment: &JobEnvironment) -> Result<Self> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+        let dir = staging_root(environment).join(format!("{}-{}", std::process::id(), nanos));
+        fs::create_dir_all(&dir).map_err(|e| EncoderError::from_io(&e, &dir, IoSide::Output))?;
+
+        let lock = File::create(dir.join(LOCK_FILE))
+            .map_err(|e| EncoderError::from_io(&e, &dir, IoSide::Output))?;
+        lock.try_lock_exclusive()
+            .map_err(|e| EncoderError::from_io(&e, &dir, IoSide::Output))?;
+
+        Ok(Self {
+            dir,
+            lock: Some(lock),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+impl Drop for JobStaging {
+    fn drop(&mut self) {
+        // Windows refuses to delete a folder with an open file in it
+        drop(self.lock.take());
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn staging_root(environment: &JobEnvironment) -> PathBuf {
+    environment.temp_dir().join(STAGING_DIR)
+}
+
+// Removes staging folders whose job no longer holds the lock, returning how many went
+pub fn sweep_orphans(environment: &JobEnvironment) -> usize {
+    let Ok(entries) = fs::read_dir(staging_root(environment)) else {
+        return 0;
+    };
+
+    let mut removed = 0;
+    for dir in entries.flatten().map(|entry| entry.path()) {
+        let orphaned = match File::open(dir.join(LOCK_FILE)) {
+            Ok(lock) => {
+                let free = lock.try_lock_exclusive().is_ok();
+                let _ = lock.unlock();
+                free
+            }
+            // Another station may be mid-way through creating it
+            Err(_) => false,
+        };
+
+        if orphaned && fs::remove_dir_all(&dir).is_ok() {
+            crash::log(format!("Removed orphaned staging folder {}", dir.display()));
+            removed += 1;
+        }
+    }
+    removed
+}
@@ -0,0 +1,160 @@
+use anyhow::{anyhow, Result};
+use std::{
+    fs,
+    path::PathBuf,
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+use crate::{
+    encoding::{
+        leader_frames, output_frame_rate, subtitle_filter, EncodingConfig, FrameSeek, OverlayGraph,
+    },
+    errors::{EncoderError, IoSide},
+    models::OutputFormat,
+    utils::{get_resolution, get_start_time},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StillStatus {
+    Exporting { done: usize, total: usize },
+    Done(PathBuf),
+    Failed(String),
+}
+
+impl StillStatus {
+    pub fn describe(&self) -> String {
+        match self {
+            StillStatus::Exporting { done, total } => format!("Exporting {}/{}...", done, total),
+            StillStatus::Done(_) => "Done".to_string(),
+            StillStatus::Failed(e) => format!("Failed ({})", e),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, StillStatus::Exporting { .. })
+    }
+}
+
+// Stills go to their own folder so they never look like a partial sequence to resume
+pub fn stills_dir(config: &EncodingConfig) -> PathBuf {
+    config.output_dir.join("stills")
+}
+
+pub fn spawn_stills(config: EncodingConfig, frames: Vec<u32>) -> Receiver<StillStatus> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let status = match export_stills(&config, &frames, &sender) {
+            Ok(()) => StillStatus::Done(stills_dir(&config)),
+            Err(e) => StillStatus::Failed(e.to_string()),
+        };
+        let _ = sender.send(status);
+    });
+
+    receiver
+}
+
+fn export_stills(
+    config: &EncodingConfig,
+    frames: &[u32],
+    sender: &Sender<StillStatus>,
+) -> Result<()> {
+    let frame_rate = output_frame_rate(config)?;
+    let source_size = get_resolution(&config.input_video, &config.ffprobe_path)?;
+    let stream_start = get_start_time(&config.input_video, &config.ffprobe_path)?;
+    let format = OutputFormat::PngSequence;
+    let leader_frames = leader_frames(config, frame_rate);
+
+    let dir = stills_dir(config);
+    fs::create_dir_all(&dir).map_err(|e| EncoderError::from_io(&e, &dir, IoSide::Output))?;
+
+    for (done, &frame) in frames.iter().enumerate() {
+        let _ = sender.send(StillStatus::Exporting {
+            done,
+            total: frames.len(),
+        });
+
+        // Marks are delivery frames, which count the leader; its own frames have no
+        // source picture to pull
+        let Some(source_frame) = frame.checked_sub(leader_frames) else {
+            continue;
+        };
+        let seek = FrameSeek {
+            frame: source_frame,
+            frame_rate,
+            stream_start,
+        };
+        let subtitle_filter = subtitle_filter(config.subtitles.as_ref(), &seek.start_time_str())?;
+        let filter_complex = OverlayGraph {
+            fixed_size: config
+                .output_format
+                .frame_size()
+                .or(config.resolution.target_size()),
+            source_size,
+            fit: config.fit,
+            flags: config.resolution.filter_flags(),
+            overlays: &config.overlays,
+            matte: config.matte,
+            retime_fps: None,
+            inverse_telecine: config.inverse_telecine,
+            seek: Some(seek),
+            first_frame: frame,
+            subtitle_filter: &subtitle_filter,
+            output_filters: "",
+            pix_fmt: format.pix_fmt(),
+        }
+        .build();
+        let output = dir.join(format!(
+            "{}-{:06}.{}",
+            config.base_name,
+            frame,
+            config.naming.extension(format)
+        ));
+
+        let mut command = Command::new(&config.ffmpeg_path);
+        config.environment.apply(&mut command);
+        command
+            .args(["-v", "error"])
+            .args(seek.input_args())
+            .arg("-i")
+            .arg(&config.input_video);
+        for layer in &config.overlays {
+            command.arg("-i").arg(&layer.image);
+        }
+        command
+            .arg("-filter_complex")
+            .arg(&filter_complex)
+            .args(["-map", "[out]", "-frames:v", "1"])
+            .args(format.codec_args())
+            .arg("-y")
+            .arg(&output)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+
+        let result = {
+            #[cfg(windows)]
+            {
+                command.creation_flags(0x08000000).output()?
+            }
+            #[cfg(not(windows))]
+            {
+                command.output()?
+            }
+        };
+
+        if !result.status.success() {
+            return Err(anyhow!(
+                "frame {}: {}",
+                frame,
+                String::from_utf8_lossy(&result.stderr).trim()
+            ));
+        }
+    }
+
+    config.permissions.apply_tree(&dir)
+}
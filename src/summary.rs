@@ -0,0 +1,94 @@
+use sha1::{Digest, Sha1};
+use std::path::{Path, PathBuf};
+
+use crate::{models::OutputFormat, utils::output_frame_number};
+
+// What the delivery email or tracker comment needs to say about one job
+pub struct DeliverySummary<'a> {
+    pub base_name: &'a str,
+    pub output_dir: &'a Path,
+    pub extension: &'a str,
+    pub format: OutputFormat,
+    pub size: Option<(u32, u32)>,
+    pub fps: Option<f64>,
+    pub notes: &'a str,
+}
+
+fn frame_number(base_name: &str, extension: &str, path: &Path) -> Option<u32> {
+    output_frame_number(base_name, extension, path.file_name()?.to_str()?)
+}
+
+// SHA-1 over "name size" lines in name order, so the receiving end can tell
+// from a listing alone whether they got the same files
+fn listing_sha1(files: &mut [(String, u64)]) -> String {
+    files.sort();
+    let mut hasher = Sha1::new();
+    for (name, size) in files.iter() {
+        hasher.update(format!("{} {}\n", name, size));
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+impl DeliverySummary<'_> {
+    // Markdown that still reads as plain text when pasted somewhere that doesn't render it
+    pub fn render(&self, files: &[PathBuf]) -> String {
+        let mut listing: Vec<(String, u64)> = files
+            .iter()
+            .filter_map(|path| {
+                let name = path.file_name()?.to_string_lossy().into_owned();
+                let size = std::fs::metadata(path).ok()?.len();
+                Some((name, size))
+            })
+            .collect();
+        let bytes: u64 = listing.iter().map(|(_, size)| size).sum();
+
+        let mut text = format!("**{}**\n\n", self.base_name);
+        text.push_str(&format!("- Path: `{}`\n", self.output_dir.display()));
+        if self.format.is_sequence() {
+            let mut frames: Vec<u32> = files
+                .iter()
+                .filter_map(|path| frame_number(self.base_name, self.extension, path))
+                .collect();
+            frames.sort_unstable();
+            if let (Some(first), Some(last)) = (frames.first(), frames.last()) {
+                let missing = (last - first + 1) as usize - frames.len();
+                text.push_str(&format!(
+                    "- Frames: {:06}-{:06} ({} frames{})\n",
+                    first,
+                    last,
+                    frames.len(),
+                    if missing > 0 {
+                        format!(", {} missing", missing)
+                    } else {
+                        String::new()
+                    }
+                ));
+            }
+        }
+        text.push_str(&format!("- Format: {}\n", self.format.as_str()));
+        if let Some((width, height)) = self.size {
+            text.push_str(&format!("- Resolution: {}x{}\n", width, height));
+        }
+        if let Some(fps) = self.fps {
+            text.push_str(&format!("- Frame rate: {:.3} fps\n", fps));
+        }
+        text.push_str(&format!(
+            "- Size: {} file{}, {:.2} GB\n",
+            listing.len(),
+            if listing.len() == 1 { "" } else { "s" },
+            bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+        ));
+        text.push_str(&format!(
+            "- Listing SHA-1: `{}`\n",
+            listing_sha1(&mut listing)
+        ));
+        if !self.notes.is_empty() {
+            text.push_str(&format!("- Notes: {}\n", self.notes));
+        }
+        text
+    }
+}
@@ -0,0 +1,50 @@
+use serde::Serialize;
+use std::{
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{crash, settings::Settings};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TelemetryEvent {
+    pub event: &'static str,
+    pub job: String,
+    pub resolution: String,
+    pub duration_secs: Option<f64>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Payload<'a> {
+    #[serde(flatten)]
+    event: &'a TelemetryEvent,
+    machine_id: &'a str,
+    version: &'static str,
+    timestamp: u64,
+}
+
+// Fire-and-forget: a slow or missing endpoint must never hold up encoding
+pub fn send(settings: &Settings, event: TelemetryEvent) {
+    if !settings.telemetry_enabled || settings.telemetry_url.trim().is_empty() {
+        return;
+    }
+
+    let url = settings.telemetry_url.clone();
+    let machine_id = settings.machine_id.clone();
+    thread::spawn(move || {
+        let payload = Payload {
+            event: &event,
+            machine_id: &machine_id,
+            version: env!("CARGO_PKG_VERSION"),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        };
+
+        if let Err(e) = ureq::post(&url).send_json(&payload) {
+            crash::log(format!("Telemetry post failed: {}", e));
+        }
+    });
+}
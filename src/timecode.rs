@@ -0,0 +1,237 @@
+use anyhow::{anyhow, Result};
+use std::{fmt, str::FromStr};
+
+// Timecode rate snapped from a probed frame rate. NTSC rates run at
+// nominal * 1000/1001; 29.97 and 59.94 count in drop-frame so the
+// timecode stays on wall-clock time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimecodeRate {
+    pub fps: f64,
+    pub nominal: u32,
+    pub drop_frame: bool,
+}
+
+impl TimecodeRate {
+    pub fn from_fps(fps: f64) -> Self {
+        let nominal = (fps * 1001.0 / 1000.0).round();
+        let ntsc_fps = nominal * 1000.0 / 1001.0;
+        if (fps - fps.round()).abs() > 0.005 && (fps - ntsc_fps).abs() < 0.005 {
+            let nominal = nominal as u32;
+            return Self {
+                fps: ntsc_fps,
+                nominal,
+                drop_frame: nominal.is_multiple_of(30),
+            };
+        }
+
+        // Whole rates snap exactly; anything else keeps its probed value
+        let fps = if (fps - fps.round()).abs() < 0.005 {
+            fps.round()
+        } else {
+            fps
+        };
+        Self {
+            fps,
+            nominal: (fps.round() as u32).max(1),
+            drop_frame: false,
+        }
+    }
+
+    pub fn is_ntsc(&self) -> bool {
+        self.fps.fract() != 0.0 && (self.fps - self.nominal as f64 * 1000.0 / 1001.0).abs() < 1e-9
+    }
+
+    pub fn frames_to_secs(&self, frames: f64) -> f64 {
+        frames / self.fps
+    }
+
+    // Nearest frame to a wall-clock time
+    pub fn secs_to_frames(&self, secs: f64) -> u32 {
+        (secs * self.fps).round().max(0.0) as u32
+    }
+
+    // Labels skipped per minute, except every tenth minute
+    fn dropped_per_minute(&self) -> u32 {
+        if self.drop_frame {
+            self.nominal / 15
+        } else {
+            0
+        }
+    }
+
+    pub fn timecode_at(&self, frame: u32) -> Timecode {
+        let drop = self.dropped_per_minute();
+        let mut frame = frame as u64;
+        if drop > 0 {
+            let drop = drop as u64;
+            let per_minute = self.nominal as u64 * 60 - drop;
+            let per_ten_minutes = self.nominal as u64 * 600 - drop * 9;
+            let tens = frame / per_ten_minutes;
+            let rest = frame % per_ten_minutes;
+            frame += drop * 9 * tens;
+            if rest > drop {
+                frame += drop * ((rest - drop) / per_minute);
+            }
+        }
+
+        let nominal = self.nominal as u64;
+        Timecode {
+            hours: (frame / (nominal * 3600)) as u32,
+            minutes: (frame / (nominal * 60) % 60) as u32,
+            seconds: (frame / nominal % 60) as u32,
+            frames: (frame % nominal) as u32,
+            drop_frame: self.drop_frame,
+        }
+    }
+
+    pub fn frame_at(&self, timecode: &Timecode) -> Result<u32> {
+        if timecode.frames >= self.nominal || timecode.minutes >= 60 || timecode.seconds >= 60 {
+            return Err(anyhow!(
+                "{} is out of range at {} fps",
+                timecode,
+                self.nominal
+            ));
+        }
+        let drop = self.dropped_per_minute();
+        if drop > 0
+            && timecode.seconds == 0
+            && timecode.frames < drop
+            && !timecode.minutes.is_multiple_of(10)
+        {
+            return Err(anyhow!("{} is skipped in drop-frame timecode", timecode));
+        }
+
+        let total_minutes = timecode.hours as u64 * 60 + timecode.minutes as u64;
+        let labels = (total_minutes * 60 + timecode.seconds as u64) * self.nominal as u64
+            + timecode.frames as u64;
+        let dropped = drop as u64 * (total_minutes - total_minutes / 10);
+        u32::try_from(labels - dropped).map_err(|_| anyhow!("{} is too long", timecode))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timecode {
+    pub hours: u32,
+    pub minutes: u32,
+    pub seconds: u32,
+    pub frames: u32,
+    pub drop_frame: bool,
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{}{:02}",
+            self.hours,
+            self.minutes,
+            self.seconds,
+            if self.drop_frame { ';' } else { ':' },
+            self.frames
+        )
+    }
+}
+
+// Accepts HH:MM:SS:FF, with ';' or ',' before the frames marking drop-frame
+impl FromStr for Timecode {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let drop_frame = text.contains([';', ',']);
+        let parts: Vec<u32> = text
+            .split([':', ';', ','])
+            .map(|part| part.parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| anyhow!("Invalid timecode '{}'", text))?;
+        let [hours, minutes, seconds, frames] = parts[..] else {
+            return Err(anyhow!("Timecode '{}' needs HH:MM:SS:FF", text));
+        };
+        Ok(Self {
+            hours,
+            minutes,
+            seconds,
+            frames,
+            drop_frame,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tc(text: &str) -> Timecode {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn snaps_probed_rates() {
+        for (fps, nominal, drop_frame) in [
+            (23.976, 24, false),
+            (24.0, 24, false),
+            (25.0, 25, false),
+            (29.97, 30, true),
+            (30.0, 30, false),
+            (50.0, 50, false),
+            (59.94, 60, true),
+            (60.0, 60, false),
+        ] {
+            let rate = TimecodeRate::from_fps(fps);
+            assert_eq!(rate.nominal, nominal, "{} fps", fps);
+            assert_eq!(rate.drop_frame, drop_frame, "{} fps", fps);
+        }
+    }
+
+    #[test]
+    fn frames_round_trip_through_timecode() {
+        for fps in [23.976, 24.0, 25.0, 29.97, 30.0, 50.0, 59.94, 60.0] {
+            let rate = TimecodeRate::from_fps(fps);
+            // Every frame of the first 11 minutes, then sparser out to 24 hours
+            let early = 0..rate.nominal * 60 * 11;
+            let late = (0..rate.nominal * 86_400).step_by(997);
+            for frame in early.chain(late) {
+                let timecode = rate.timecode_at(frame);
+                let parsed = tc(&timecode.to_string());
+                assert_eq!(parsed, timecode, "{} fps frame {}", fps, frame);
+                assert_eq!(
+                    rate.frame_at(&parsed).unwrap(),
+                    frame,
+                    "{} fps {}",
+                    fps,
+                    timecode
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn drop_frame_skips_labels_at_minute_boundaries() {
+        let rate = TimecodeRate::from_fps(29.97);
+        assert_eq!(rate.timecode_at(1799).to_string(), "00:00:59;29");
+        assert_eq!(rate.timecode_at(1800).to_string(), "00:01:00;02");
+        assert_eq!(rate.frame_at(&tc("00:01:00;02")).unwrap(), 1800);
+        assert!(rate.frame_at(&tc("00:01:00;00")).is_err());
+        assert!(rate.frame_at(&tc("00:01:00;01")).is_err());
+
+        let rate = TimecodeRate::from_fps(59.94);
+        assert_eq!(rate.timecode_at(3599).to_string(), "00:00:59;59");
+        assert_eq!(rate.timecode_at(3600).to_string(), "00:01:00;04");
+        assert!(rate.frame_at(&tc("00:01:00;03")).is_err());
+    }
+
+    #[test]
+    fn drop_frame_keeps_every_tenth_minute() {
+        let rate = TimecodeRate::from_fps(29.97);
+        assert_eq!(rate.timecode_at(17_981).to_string(), "00:09:59;29");
+        assert_eq!(rate.timecode_at(17_982).to_string(), "00:10:00;00");
+        assert_eq!(rate.frame_at(&tc("00:10:00;00")).unwrap(), 17_982);
+        assert_eq!(rate.frame_at(&tc("00:10:00;01")).unwrap(), 17_983);
+        assert_eq!(rate.timecode_at(17_982 + 1800).to_string(), "00:11:00;02");
+        assert_eq!(rate.timecode_at(107_892).to_string(), "01:00:00;00");
+
+        let rate = TimecodeRate::from_fps(59.94);
+        assert_eq!(rate.timecode_at(35_964).to_string(), "00:10:00;00");
+        assert_eq!(rate.frame_at(&tc("00:10:00;02")).unwrap(), 35_966);
+    }
+}
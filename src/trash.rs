@@ -0,0 +1,51 @@
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrashSummary {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+impl TrashSummary {
+    pub fn of(paths: &[PathBuf]) -> Self {
+        Self {
+            files: paths.len(),
+            bytes: paths.iter().map(|path| size_on_disk(path)).sum(),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        format!(
+            "{} file{} ({:.2} GB)",
+            self.files,
+            if self.files == 1 { "" } else { "s" },
+            self.bytes as f64 / (1024.0 * 1024.0 * 1024.0)
+        )
+    }
+}
+
+fn size_on_disk(path: &Path) -> u64 {
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() => std::fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| size_on_disk(&entry.path()))
+                    .sum()
+            })
+            .unwrap_or(0),
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    }
+}
+
+// Finder's Trash keeps "Put Back" working, and the freedesktop trash uses the
+// volume's own trash folder, so frames on the SAN are never copied back to the
+// system disk
+pub fn move_to_trash(paths: &[PathBuf]) -> Result<()> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    trash::delete_all(paths).map_err(|e| anyhow!("Could not move files to trash: {}", e))
+}
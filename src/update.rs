@@ -0,0 +1,143 @@
+use anyhow::{anyhow, Result};
+use ring::digest;
+use serde::Deserialize;
+use std::{
+    env, fs,
+    io::{Read, Write},
+    path::PathBuf,
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use crate::utils::app_data_dir;
+
+// Release manifest served by the internal update endpoint
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ReleaseManifest {
+    pub version: String,
+    pub url: String,
+    // Hex SHA-256 of the package; a manifest without one is not installed
+    pub sha256: String,
+    #[serde(default)]
+    pub notes: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpdateStatus {
+    UpToDate,
+    Available(ReleaseManifest),
+    Downloading(ReleaseManifest),
+    Staged(PathBuf),
+    Failed(String),
+}
+
+// The check only runs when the studio has configured an endpoint
+pub fn update_url() -> Option<String> {
+    env::var("DELIVERY_ENCODER_UPDATE_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+}
+
+fn parse_version(version: &str) -> Vec<u32> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+fn is_newer(candidate: &str, current: &str) -> bool {
+    parse_version(candidate) > parse_version(current)
+}
+
+pub fn spawn_check(url: String) -> Receiver<UpdateStatus> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let status = match fetch_manifest(&url) {
+            Ok(manifest) if is_newer(&manifest.version, env!("CARGO_PKG_VERSION")) => {
+                UpdateStatus::Available(manifest)
+            }
+            Ok(_) => UpdateStatus::UpToDate,
+            Err(e) => UpdateStatus::Failed(e.to_string()),
+        };
+        let _ = sender.send(status);
+    });
+
+    receiver
+}
+
+fn fetch_manifest(url: &str) -> Result<ReleaseManifest> {
+    ureq::get(url)
+        .call()
+        .map_err(|e| anyhow!("Update check failed: {}", e))?
+        .into_json::<ReleaseManifest>()
+        .map_err(|e| anyhow!("Invalid update manifest: {}", e))
+}
+
+pub fn spawn_download(manifest: ReleaseManifest) -> Receiver<UpdateStatus> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let _ = sender.send(UpdateStatus::Downloading(manifest.clone()));
+        let status = match download(&manifest) {
+            Ok(path) => UpdateStatus::Staged(path),
+            Err(e) => UpdateStatus::Failed(e.to_string()),
+        };
+        let _ = sender.send(status);
+    });
+
+    receiver
+}
+
+fn download(manifest: &ReleaseManifest) -> Result<PathBuf> {
+    let file_name = manifest
+        .url
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("delivery_encoder-{}", manifest.version));
+
+    let staging_dir = app_data_dir().join("updates");
+    fs::create_dir_all(&staging_dir)?;
+    let staged = staging_dir.join(file_name);
+    let partial = staged.with_extension("partial");
+
+    let response = ureq::get(&manifest.url)
+        .call()
+        .map_err(|e| anyhow!("Download failed: {}", e))?;
+    let mut file = fs::File::create(&partial)?;
+    let mut reader = response.into_reader();
+    let mut hasher = digest::Context::new(&digest::SHA256);
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        file.write_all(&buffer[..read])?;
+    }
+    drop(file);
+
+    // Nothing is staged for the installer unless it is the build the manifest names
+    let actual: String = hasher
+        .finish()
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    if !actual.eq_ignore_ascii_case(manifest.sha256.trim()) {
+        let _ = fs::remove_file(&partial);
+        return Err(anyhow!(
+            "Downloaded update does not match the manifest checksum (expected {}, got {})",
+            manifest.sha256.trim(),
+            actual
+        ));
+    }
+
+    fs::rename(&partial, &staged)?;
+    Ok(staged)
+}
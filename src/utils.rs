@@ -1,13 +1,24 @@
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use std::{
     env,
+    io::Read,
     path::{Path, PathBuf},
     process::{Command, Stdio},
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+    time::{Duration, Instant},
 };
 
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 
+use crate::{
+    audio::AudioLayout,
+    crash,
+    errors::{EncoderError, IoSide},
+    models::{FieldOrder, OutputFormat, PictureTags},
+};
+
 pub fn open_folder(path: &Path) {
     let command = if cfg!(target_os = "windows") {
         "explorer"
@@ -20,7 +31,218 @@ pub fn open_folder(path: &Path) {
     let _ = Command::new(command).arg(path).spawn();
 }
 
-pub fn find_ffmpeg() -> (PathBuf, PathBuf, String) {
+// Scratch location and extra variables for the ffmpeg processes a job starts
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JobEnvironment {
+    pub scratch_dir: Option<PathBuf>,
+    pub vars: Vec<(String, String)>,
+}
+
+impl JobEnvironment {
+    pub fn temp_dir(&self) -> PathBuf {
+        self.scratch_dir.clone().unwrap_or_else(env::temp_dir)
+    }
+
+    pub fn apply(&self, command: &mut Command) {
+        // ffmpeg and its libraries look these up for their own scratch files
+        if let Some(dir) = &self.scratch_dir {
+            for name in ["TMPDIR", "TEMP", "TMP"] {
+                command.env(name, dir);
+            }
+        }
+        command.envs(self.vars.iter().map(|(name, value)| (name, value)));
+    }
+}
+
+// (year, month, day) in UTC for a Unix timestamp, without pulling in a date crate
+pub fn civil_date(unix_secs: i64) -> (i64, i64, i64) {
+    let days = unix_secs.div_euclid(86_400);
+
+    // Howard Hinnant's civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// Per-user directory for crash reports and other app state
+pub fn app_data_dir() -> PathBuf {
+    let base = if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| {
+            PathBuf::from(home)
+                .join("Library")
+                .join("Application Support")
+        })
+    } else {
+        env::var_os("XDG_DATA_HOME").map(PathBuf::from).or_else(|| {
+            env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+        })
+    };
+
+    base.unwrap_or_else(env::temp_dir).join("delivery_encoder")
+}
+
+// The image2 muxer treats '%' as the start of a frame number pattern,
+// so literal percent signs in the output path have to be doubled.
+pub const STILL_EXTENSIONS: [&str; 3] = ["png", "tif", "tiff"];
+
+pub fn is_still_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            STILL_EXTENSIONS
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(ext))
+        })
+}
+
+// Reads only the PNG header, so checking a 6K overlay is instant
+pub fn png_has_alpha(path: &Path) -> Result<bool> {
+    use image::ImageDecoder;
+
+    let file =
+        std::fs::File::open(path).map_err(|e| EncoderError::from_io(&e, path, IoSide::Source))?;
+    let decoder = image::codecs::png::PngDecoder::new(std::io::BufReader::new(file))
+        .map_err(|e| EncoderError::source_unreadable(path, e))?;
+    Ok(decoder.color_type().has_alpha())
+}
+
+pub fn escape_image2_pattern(text: &str) -> String {
+    text.replace('%', "%%")
+}
+
+// Escapes a filter option value for use inside a -filter_complex graph: once
+// for the option parser and once more for the graph parser.
+pub fn escape_filter_value(text: &str) -> String {
+    let mut value = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '\'' | ':') {
+            value.push('\\');
+        }
+        value.push(c);
+    }
+
+    let mut graph = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '\'' | '[' | ']' | ',' | ';') {
+            graph.push('\\');
+        }
+        graph.push(c);
+    }
+    graph
+}
+
+// Frame number of `<base>-<digits>.<ext>`. Anything else is not this job's frame,
+// e.g. `shot_v2-000001.png` when the base is `shot`.
+pub fn output_frame_number(base_name: &str, extension: &str, file_name: &str) -> Option<u32> {
+    let digits = file_name
+        .strip_prefix(base_name)?
+        .strip_prefix('-')?
+        .strip_suffix(extension)?
+        .strip_suffix('.')?;
+    if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+// A frame of this job's sequence, or its movie file `<base>.<ext>`
+pub fn is_output_file(base_name: &str, extension: &str, file_name: &str) -> bool {
+    output_frame_number(base_name, extension, file_name).is_some()
+        || file_name
+            .strip_prefix(base_name)
+            .and_then(|rest| rest.strip_prefix('.'))
+            == Some(extension)
+}
+
+// Anything a run writes next to its frames: the frames or movie, review copy,
+// audio stems, QC samples, IMF package and reports. Another shot whose name only
+// starts with `base_name` is never matched.
+pub fn is_job_file(base_name: &str, extension: &str, file_name: &str) -> bool {
+    if is_output_file(base_name, extension, file_name) {
+        return true;
+    }
+    let Some(rest) = file_name
+        .strip_prefix(base_name)
+        .and_then(|rest| rest.strip_prefix('_'))
+    else {
+        return false;
+    };
+    match rest {
+        "qc" | "IMF" | "decode_errors.txt" | "split.json" => true,
+        _ => {
+            rest.strip_prefix("review.")
+                .is_some_and(|ext| !ext.is_empty() && !ext.contains('.'))
+                || rest.strip_suffix(".wav").is_some_and(|label| {
+                    !label.is_empty()
+                        && label.bytes().all(|byte| {
+                            byte.is_ascii_uppercase() || byte.is_ascii_digit() || byte == b'-'
+                        })
+                })
+        }
+    }
+}
+
+// Replaces characters that are invalid in file names on common filesystems, and
+// leading dashes so a bare name is never read as an ffmpeg option
+pub fn sanitize_base_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    let trimmed = sanitized.trim().trim_end_matches('.');
+    if trimmed.is_empty() {
+        return "video".to_string();
+    }
+    let name = trimmed.trim_start_matches('-');
+    format!("{}{}", "_".repeat(trimmed.len() - name.len()), name)
+}
+
+// Parses "12, 240, 1000-1004" into sorted, de-duplicated frame numbers
+pub fn parse_frame_list(list: &str) -> Result<Vec<u32>> {
+    let mut frames = Vec::new();
+    for token in list
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+    {
+        let parse = |value: &str| {
+            value
+                .trim()
+                .parse::<u32>()
+                .map_err(|_| anyhow::anyhow!("Invalid frame '{}'", token))
+        };
+        match token.split_once('-') {
+            Some((first, last)) => {
+                let (first, last) = (parse(first)?, parse(last)?);
+                if last < first {
+                    return Err(anyhow::anyhow!("Invalid frame range '{}'", token));
+                }
+                frames.extend(first..=last);
+            }
+            None => frames.push(parse(token)?),
+        }
+    }
+    frames.sort_unstable();
+    frames.dedup();
+    Ok(frames)
+}
+
+/// Bundled or PATH ffmpeg and ffprobe; bare names when neither has a pair, so the
+/// first run reports the missing binary.
+pub fn find_ffmpeg() -> (PathBuf, PathBuf) {
     let (ffmpeg_name, ffprobe_name) = if cfg!(windows) {
         ("ffmpeg.exe", "ffprobe.exe")
     } else {
@@ -36,7 +258,7 @@ pub fn find_ffmpeg() -> (PathBuf, PathBuf, String) {
     for path in &locations {
         let ffprobe_path = path.with_file_name(ffprobe_name);
         if path.exists() && ffprobe_path.exists() {
-            return (path.clone(), ffprobe_path, String::new());
+            return (path.clone(), ffprobe_path);
         }
     }
 
@@ -45,161 +267,517 @@ pub fn find_ffmpeg() -> (PathBuf, PathBuf, String) {
             let ffmpeg_path = dir.join(ffmpeg_name);
             let ffprobe_path = dir.join(ffprobe_name);
             if ffmpeg_path.exists() && ffprobe_path.exists() {
-                return (ffmpeg_path, ffprobe_path, String::new());
+                return (ffmpeg_path, ffprobe_path);
             }
         }
     }
 
-    (
-        PathBuf::from(ffmpeg_name),
-        PathBuf::from(ffprobe_name),
-        String::new(),
-    )
+    (PathBuf::from(ffmpeg_name), PathBuf::from(ffprobe_name))
 }
 
-pub fn get_resolution(input: &Path, ffprobe_path: &Path) -> Result<(u32, u32)> {
+// Attempts and initial backoff for probes of files on network storage
+const PROBE_ATTEMPTS: u32 = 3;
+const PROBE_BACKOFF: Duration = Duration::from_millis(250);
+
+// Station setting, shared by every probe call
+static PROBE_TIMEOUT_SECS: AtomicU64 = AtomicU64::new(30);
+
+pub fn set_probe_timeout(timeout: Duration) {
+    PROBE_TIMEOUT_SECS.store(timeout.as_secs().max(1), Ordering::Relaxed);
+}
+
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+// FFprobe errors that come from the storage rather than the file itself
+fn is_transient_probe_error(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+    [
+        "input/output error",
+        "resource temporarily unavailable",
+        "connection",
+        "timed out",
+        "network",
+        "stale file handle",
+        "no such file or directory",
+    ]
+    .iter()
+    .any(|pattern| stderr.contains(pattern))
+}
+
+// Runs ffprobe until it answers, backing off between transient failures; an
+// invalid file fails on the first attempt
+fn probe(input: &Path, ffprobe_path: &Path, args: &[&str]) -> Result<String> {
+    let mut backoff = PROBE_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match probe_once(input, ffprobe_path, args) {
+            Err(e @ EncoderError::Timeout { .. }) if attempt < PROBE_ATTEMPTS => {
+                crash::log(format!(
+                    "Probe of {} failed (attempt {}): {}",
+                    input.display(),
+                    attempt,
+                    e
+                ));
+                attempt += 1;
+            }
+            Err(EncoderError::SourceUnavailable { reason, .. }) if attempt < PROBE_ATTEMPTS => {
+                crash::log(format!(
+                    "Probe of {} failed (attempt {}): {}",
+                    input.display(),
+                    attempt,
+                    reason
+                ));
+                thread::sleep(backoff);
+                backoff *= 4;
+                attempt += 1;
+            }
+            result => return result.map_err(Into::into),
+        }
+    }
+}
+
+// Runs ffprobe on `input` and returns its trimmed stdout
+fn probe_once(
+    input: &Path,
+    ffprobe_path: &Path,
+    args: &[&str],
+) -> std::result::Result<String, EncoderError> {
     let input_str = input
         .to_str()
-        .ok_or_else(|| anyhow!("Invalid video path"))?;
+        .ok_or_else(|| EncoderError::source_unreadable(input, "Invalid video path"))?;
 
     let mut command = Command::new(ffprobe_path);
     command
-        .args([
-            "-v",
-            "error",
-            "-select_streams",
-            "v:0",
-            "-show_entries",
-            "stream=width,height",
-            "-of",
-            "csv=p=0",
-            input_str,
-        ])
+        .args(["-v", "error"])
+        .args(args)
+        .arg(input_str)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
-    let output = {
+    let mut child = {
         #[cfg(windows)]
         {
-            command.creation_flags(0x08000000).output()?
+            command.creation_flags(0x08000000).spawn()
         }
         #[cfg(not(windows))]
         {
-            command.output()?
+            command.spawn()
+        }
+    }
+    .map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => EncoderError::MissingBinary {
+            name: "FFprobe",
+            path: ffprobe_path.to_path_buf(),
+        },
+        _ => EncoderError::source_unavailable(input, e),
+    })?;
+
+    // ffprobe blocks for as long as a dead mount does, so it gets a deadline
+    let stdout_reader = read_pipe(child.stdout.take());
+    let stderr_reader = read_pipe(child.stderr.take());
+    let timeout = PROBE_TIMEOUT_SECS.load(Ordering::Relaxed);
+    let deadline = Instant::now() + Duration::from_secs(timeout);
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                // Not waited for: a process stuck in the kernel may never exit
+                let _ = child.kill();
+                return Err(EncoderError::Timeout {
+                    phase: "Probe",
+                    secs: timeout,
+                });
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(20)),
+            Err(e) => return Err(EncoderError::source_unavailable(input, e)),
         }
     };
+    let stdout = stdout_reader.join().unwrap_or_default();
+    let stderr = stderr_reader.join().unwrap_or_default();
+
+    if !status.success() {
+        let stderr = String::from_utf8_lossy(&stderr);
+        let reason = format!("FFprobe failed: {}", stderr.trim());
+        return Err(if is_transient_probe_error(&stderr) {
+            EncoderError::source_unavailable(input, reason)
+        } else {
+            EncoderError::source_unreadable(input, reason)
+        });
+    }
+
+    let stdout =
+        String::from_utf8(stdout).map_err(|e| EncoderError::source_unreadable(input, e))?;
+    Ok(stdout.trim().to_string())
+}
+
+// ffprobe prints nothing for a stream selector that matches nothing, which
+// would otherwise surface as a parse error in whichever field was asked for
+fn probe_video(input: &Path, ffprobe_path: &Path, args: &[&str]) -> Result<String> {
+    let output = probe(
+        input,
+        ffprobe_path,
+        &[&["-select_streams", "v:0"], args].concat(),
+    )?;
+    if output.is_empty() {
+        return Err(EncoderError::NoVideoStream {
+            path: input.to_path_buf(),
+        }
+        .into());
+    }
+    Ok(output)
+}
 
-    if !output.status.success() {
-        return Err(anyhow!(
-            "FFprobe failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
+/// Whether the file has a video stream at all.
+pub fn has_video_stream(input: &Path, ffprobe_path: &Path) -> Result<bool> {
+    match probe_video(
+        input,
+        ffprobe_path,
+        &["-show_entries", "stream=index", "-of", "csv=p=0"],
+    ) {
+        Ok(_) => Ok(true),
+        Err(e) if matches!(e.downcast_ref(), Some(EncoderError::NoVideoStream { .. })) => Ok(false),
+        Err(e) => Err(e),
     }
+}
+
+/// Width and height of the first video stream.
+pub fn get_resolution(input: &Path, ffprobe_path: &Path) -> Result<(u32, u32)> {
+    let res_str = probe_video(
+        input,
+        ffprobe_path,
+        &["-show_entries", "stream=width,height", "-of", "csv=p=0"],
+    )?;
 
-    let res_str = String::from_utf8(output.stdout)?.trim().to_string();
     let parts: Vec<&str> = res_str.split(',').collect();
     if parts.len() != 2 {
-        return Err(anyhow!("Unexpected resolution format: {}", res_str));
+        return Err(EncoderError::source_unreadable(
+            input,
+            format!("Unexpected resolution format: {}", res_str),
+        )
+        .into());
     }
 
-    let width = parts[0].parse::<u32>()?;
-    let height = parts[1].parse::<u32>()?;
+    let parse = |value: &str| {
+        value
+            .parse::<u32>()
+            .map_err(|e| EncoderError::source_unreadable(input, e))
+    };
+    let width = parse(parts[0])?;
+    let height = parse(parts[1])?;
 
     Ok((width, height))
 }
 
-pub fn get_duration(input: &Path, ffprobe_path: &Path) -> Result<f32> {
-    let input_str = input
-        .to_str()
-        .ok_or_else(|| anyhow!("Invalid video path"))?;
-
-    let mut command = Command::new(ffprobe_path);
-    command
-        .args([
-            "-v",
-            "error",
+/// Container duration in seconds.
+pub fn get_duration(input: &Path, ffprobe_path: &Path) -> Result<f64> {
+    probe(
+        input,
+        ffprobe_path,
+        &[
             "-show_entries",
             "format=duration",
             "-of",
             "default=noprint_wrappers=1:nokey=1",
-            input_str,
-        ])
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
+        ],
+    )?
+    .parse::<f64>()
+    .map_err(|e| {
+        EncoderError::source_unreadable(input, format!("Duration parse error: {}", e)).into()
+    })
+}
 
-    let output = {
-        #[cfg(windows)]
-        {
-            command.creation_flags(0x08000000).output()?
-        }
-        #[cfg(not(windows))]
-        {
-            command.output()?
-        }
+/// Frame rate of the first video stream.
+pub fn get_frame_rate(input: &Path, ffprobe_path: &Path) -> Result<f64> {
+    let rate_str = probe_video(
+        input,
+        ffprobe_path,
+        &[
+            "-show_entries",
+            "stream=avg_frame_rate", // Changed to avg_frame_rate
+            "-of",
+            "default=noprint_wrappers=1:nokey=1",
+        ],
+    )?;
+
+    let parse_error = |e: std::num::ParseFloatError| {
+        EncoderError::source_unreadable(input, format!("Frame rate parse error: {}", e))
     };
 
-    if !output.status.success() {
-        return Err(anyhow!(
-            "FFprobe failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
+    if let Some((num, den)) = rate_str.split_once('/') {
+        let numerator: f64 = num.parse().map_err(parse_error)?;
+        let denominator: f64 = den.parse().map_err(parse_error)?;
+        Ok(numerator / denominator)
+    } else {
+        Ok(rate_str.parse::<f64>().map_err(parse_error)?)
     }
-
-    String::from_utf8(output.stdout)?
-        .trim()
-        .parse::<f32>()
-        .map_err(|e| anyhow!("Duration parse error: {}", e))
 }
 
-pub fn get_frame_rate(input: &Path, ffprobe_path: &Path) -> Result<f32> {
-    let input_str = input
-        .to_str()
-        .ok_or_else(|| anyhow!("Invalid video path"))?;
-
-    let mut command = Command::new(ffprobe_path);
-    command
-        .args([
-            "-v",
-            "error",
+/// Timestamp of the first video frame; containers without one start at zero.
+pub fn get_start_time(input: &Path, ffprobe_path: &Path) -> Result<f64> {
+    let start_str = probe(
+        input,
+        ffprobe_path,
+        &[
             "-select_streams",
             "v:0",
             "-show_entries",
-            "stream=avg_frame_rate", // Changed to avg_frame_rate
+            "stream=start_time",
             "-of",
             "default=noprint_wrappers=1:nokey=1",
-            input_str,
-        ])
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
+        ],
+    )?;
+    Ok(start_str.parse().unwrap_or(0.0))
+}
+
+/// Field order, aspect, colour and rotation tags of the first video stream.
+pub fn get_picture_tags(input: &Path, ffprobe_path: &Path) -> Result<PictureTags> {
+    let tags_str = probe_video(
+        input,
+        ffprobe_path,
+        &[
+            "-show_entries",
+            "stream=field_order,sample_aspect_ratio,color_primaries,color_transfer,color_space:\
+             stream_tags=rotate:stream_side_data=rotation",
+            "-of",
+            "default=noprint_wrappers=1",
+        ],
+    )?;
+
+    let mut tags = PictureTags::default();
+    for line in tags_str.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        let known = !value.is_empty() && value != "unknown" && value != "N/A";
+        match key {
+            "field_order" => tags.field_order = FieldOrder::from_probe(value),
+            // 0:1 means unset
+            "sample_aspect_ratio" => {
+                tags.sample_aspect = value
+                    .split_once(':')
+                    .and_then(|(num, den)| Some((num.parse().ok()?, den.parse().ok()?)))
+                    .filter(|&(num, den): &(u32, u32)| num > 0 && den > 0)
+            }
+            "color_primaries" if known => tags.color_primaries = Some(value.to_string()),
+            "color_transfer" if known => tags.color_transfer = Some(value.to_string()),
+            "color_space" if known => tags.color_space = Some(value.to_string()),
+            "rotation" | "TAG:rotate" => tags.rotation = value.parse().ok().or(tags.rotation),
+            _ => {}
+        }
+    }
+    Ok(tags)
+}
+
+/// Returns None when the source has no audio stream.
+pub fn get_audio_layout(input: &Path, ffprobe_path: &Path) -> Result<Option<AudioLayout>> {
+    let layout_str = probe(
+        input,
+        ffprobe_path,
+        &[
+            "-select_streams",
+            "a:0",
+            "-show_entries",
+            "stream=channels,channel_layout",
+            "-of",
+            "csv=p=0",
+        ],
+    )?;
+
+    if layout_str.is_empty() {
+        return Ok(None);
+    }
+
+    let (channels, layout) = layout_str.split_once(',').unwrap_or((&layout_str, ""));
+    let channels = channels.parse::<u32>().map_err(|e| {
+        EncoderError::source_unreadable(input, format!("Channel count parse error: {}", e))
+    })?;
+
+    Ok(Some(AudioLayout {
+        channels,
+        layout: layout.trim().to_string(),
+    }))
+}
+
+// Lists the muxers, encoders or filters compiled into the FFmpeg build
+fn ffmpeg_components(ffmpeg_path: &Path, kind: &str) -> Result<String> {
+    let mut command = Command::new(ffmpeg_path);
+    command.args(["-hide_banner", kind]);
 
     let output = {
         #[cfg(windows)]
         {
-            command.creation_flags(0x08000000).output()?
+            command.creation_flags(0x08000000).output()
         }
         #[cfg(not(windows))]
         {
-            command.output()?
+            command.output()
         }
-    };
+    }
+    .map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => EncoderError::MissingBinary {
+            name: "FFmpeg",
+            path: ffmpeg_path.to_path_buf(),
+        },
+        _ => EncoderError::EncoderFailed {
+            stderr: e.to_string(),
+            resumes: false,
+        },
+    })?;
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
 
-    if !output.status.success() {
-        return Err(anyhow!(
-            "FFprobe failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
+fn lists_component(listing: &str, name: &str) -> bool {
+    listing
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .any(|component| component.split(',').any(|alias| alias == name))
+}
+
+/// What one ffmpeg build can write, listed once so the form can grey out the rest.
+#[derive(Debug, Clone)]
+pub struct FfmpegCapabilities {
+    muxers: String,
+    encoders: String,
+    filters: String,
+}
+
+impl FfmpegCapabilities {
+    pub fn probe(ffmpeg_path: &Path) -> Result<Self> {
+        Ok(Self {
+            muxers: ffmpeg_components(ffmpeg_path, "-muxers")?,
+            encoders: ffmpeg_components(ffmpeg_path, "-encoders")?,
+            filters: ffmpeg_components(ffmpeg_path, "-filters")?,
+        })
     }
 
-    let rate_str = String::from_utf8(output.stdout)?;
-    let rate_str = rate_str.trim();
+    pub fn has_filter(&self, name: &str) -> bool {
+        lists_component(&self.filters, name)
+    }
 
-    if let Some((num, den)) = rate_str.split_once('/') {
-        let numerator: f32 = num.parse()?;
-        let denominator: f32 = den.parse()?;
-        Ok(numerator / denominator)
-    } else {
-        rate_str
-            .parse::<f32>()
-            .map_err(|e| anyhow!("Frame rate parse error: {}", e))
+    /// Why this build can't write `format`, or None when it can.
+    pub fn missing_for(&self, format: OutputFormat) -> Option<String> {
+        let (muxer, encoder) = format.requirements()?;
+        if !lists_component(&self.muxers, muxer) {
+            return Some(format!("{} needs the {} muxer", format.as_str(), muxer));
+        }
+        if !lists_component(&self.encoders, encoder) {
+            return Some(format!("{} needs the {} encoder", format.as_str(), encoder));
+        }
+        None
+    }
+
+    pub fn check(&self, format: OutputFormat) -> Result<()> {
+        match self.missing_for(format) {
+            Some(reason) => Err(EncoderError::UnsupportedFormat { reason }.into()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image2_pattern_escapes_percent_only() {
+        assert_eq!(escape_image2_pattern("shot"), "shot");
+        assert_eq!(escape_image2_pattern("50%"), "50%%");
+        assert_eq!(escape_image2_pattern("%%d"), "%%%%d");
+        assert_eq!(escape_image2_pattern("a%06d"), "a%%06d");
+        assert_eq!(escape_image2_pattern("[v1],'a':b;c\\"), "[v1],'a':b;c\\");
+    }
+
+    #[test]
+    fn filter_value_escapes_for_option_then_graph() {
+        assert_eq!(escape_filter_value("plain"), "plain");
+        assert_eq!(escape_filter_value("50%"), "50%");
+        assert_eq!(escape_filter_value("a:b"), "a\\\\:b");
+        assert_eq!(escape_filter_value("it's"), "it\\\\\\'s");
+        assert_eq!(escape_filter_value("[v1]"), "\\[v1\\]");
+        assert_eq!(escape_filter_value("a,b;c"), "a\\,b\\;c");
+        assert_eq!(escape_filter_value("C:\\subs"), "C\\\\:\\\\\\\\subs");
+    }
+
+    #[test]
+    fn output_files_match_the_whole_name() {
+        assert_eq!(
+            output_frame_number("shot", "png", "shot-000012.png"),
+            Some(12)
+        );
+        assert_eq!(output_frame_number("shot", "png", "shot-12.png"), Some(12));
+        assert_eq!(
+            output_frame_number("shot", "png", "shot_v2-000001.png"),
+            None
+        );
+        assert_eq!(
+            output_frame_number("shot", "png", "shot-v2-000001.png"),
+            None
+        );
+        assert_eq!(output_frame_number("shot", "png", "shot--000001.png"), None);
+        assert_eq!(output_frame_number("shot", "png", "shot-+00001.png"), None);
+        assert_eq!(output_frame_number("shot", "png", "shot-000001.tif"), None);
+        assert_eq!(output_frame_number("shot", "png", "shot-.png"), None);
+        assert_eq!(output_frame_number("shot", "png", "shot-000001xpng"), None);
+
+        assert!(is_output_file("shot", "mov", "shot.mov"));
+        assert!(is_output_file("shot", "png", "shot-000001.png"));
+        assert!(!is_output_file("shot", "mov", "shot_v2.mov"));
+        assert!(!is_output_file("shot", "mov", "shot.mov.partial"));
+    }
+
+    #[test]
+    fn job_files_cover_side_outputs_of_the_same_shot_only() {
+        for name in [
+            "shot-000001.exr",
+            "shot_review.mp4",
+            "shot_FL.wav",
+            "shot_A1-2.wav",
+            "shot_qc",
+            "shot_IMF",
+            "shot_decode_errors.txt",
+            "shot_split.json",
+        ] {
+            assert!(is_job_file("shot", "exr", name), "{}", name);
+        }
+        for name in [
+            "shot_v2-000001.exr",
+            "shot_v2_0001.exr",
+            "shot_v2_review.mp4",
+            "shot_v2.wav",
+            "shot_qc2",
+            "shotgun-000001.exr",
+            "shot_review.mp4.partial",
+        ] {
+            assert!(!is_job_file("shot", "exr", name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn base_name_loses_invalid_characters() {
+        assert_eq!(sanitize_base_name("shot_010"), "shot_010");
+        assert_eq!(sanitize_base_name("a:b/c\\d"), "a_b_c_d");
+        assert_eq!(sanitize_base_name("what?*\"<>|"), "what______");
+        assert_eq!(sanitize_base_name("50%[v1],'a';b"), "50%[v1],'a';b");
+        assert_eq!(sanitize_base_name("  name.. "), "name");
+        assert_eq!(sanitize_base_name(" . "), "video");
+        assert_eq!(sanitize_base_name("tab\there"), "tab_here");
+    }
+
+    #[test]
+    fn base_name_never_starts_with_a_dash() {
+        assert_eq!(sanitize_base_name("-y"), "_y");
+        assert_eq!(sanitize_base_name("--frames"), "__frames");
+        assert_eq!(sanitize_base_name(" -shot-v2"), "_shot-v2");
+        assert_eq!(sanitize_base_name("---"), "___");
     }
 }